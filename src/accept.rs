@@ -0,0 +1,210 @@
+//! The `Accept` family of headers, where each item may carry a q-value.
+
+/// A weight of 0 to 1, as the thousandths the wire format can express.
+pub(crate) fn qvalue_millis(q: f32) -> Option<u16> {
+    if !(0.0..=1.0).contains(&q) {
+        return None;
+    }
+    Some((q * 1000.0).round() as u16)
+}
+
+/// The `;q=` parameter for `q`, as chunks that are written back to back.
+///
+/// Trailing zeros are dropped, so `1.0` is written as `q=1` and `0.50` as
+/// `q=0.5`. An out of range `q` is written as `q=1`; callers reject it first.
+pub(crate) fn qvalue_chunks(q: Option<f32>) -> [&'static [u8]; 6] {
+    let Some(q) = q else {
+        return [b""; 6];
+    };
+    let millis = qvalue_millis(q).unwrap_or(1000);
+    if millis == 1000 {
+        return [b";q=", b"1", b"", b"", b"", b""];
+    }
+
+    let digit = |d: u16, keep: bool| -> &'static [u8] {
+        match keep {
+            true => crate::field::digit(d),
+            false => b"",
+        }
+    };
+    let point: &[u8] = if millis > 0 { b"." } else { b"" };
+    [
+        b";q=",
+        b"0",
+        point,
+        digit(millis / 100, millis > 0),
+        digit(millis / 10 % 10, !millis.is_multiple_of(100)),
+        digit(millis % 10, !millis.is_multiple_of(10)),
+    ]
+}
+
+/// Parses a `qvalue` (RFC 9110, section 12.4.2) into thousandths.
+pub(crate) fn parse_qvalue(q: &str) -> Option<u16> {
+    let (int, frac) = q.split_once('.').unwrap_or((q, ""));
+    if frac.len() > 3 || !frac.bytes().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    let frac = frac.bytes().chain(core::iter::repeat(b'0')).take(3).fold(0, |n, ch| n * 10 + u16::from(ch - b'0'));
+    match int {
+        "0" => Some(frac),
+        "1" if frac == 0 => Some(1000),
+        _ => None,
+    }
+}
+
+/// Picks the coding from `available`, in the server's order of preference,
+/// that the client weights highest in `accept_encoding`.
+///
+/// Entries that do not parse are skipped. A coding the header does not
+/// name gets the weight of `*`, if present; `identity` is acceptable unless
+/// it is excluded with `q=0`, either by name or through `*`. An empty header
+/// only accepts `identity`. Ties go to the coding listed first.
+///
+/// Returns `None` if no available coding is acceptable; a server then
+/// answers `415` or sends `identity` regardless. A request without the
+/// header at all accepts any coding and should not be passed here.
+pub fn choose_encoding<'s>(accept_encoding: &str, available: &[&'s str]) -> Option<&'s str> {
+    let mut weights = Vec::new();
+    for item in accept_encoding.split(',') {
+        let mut params = item.split(';').map(str::trim);
+        let coding = params.next().unwrap_or_default();
+        if coding != "*" && crate::invalid_token_pos(coding.as_bytes()).is_some() {
+            continue;
+        }
+        let mut q = Some(1000);
+        for param in params {
+            q = match param.split_once('=') {
+                Some((name, value)) if name.trim().eq_ignore_ascii_case("q") => parse_qvalue(value.trim()),
+                _ => q,
+            };
+        }
+        if let Some(q) = q {
+            weights.push((coding, q));
+        }
+    }
+
+    let weight = |coding: &str| {
+        let named = weights.iter().find(|(c, _)| c.eq_ignore_ascii_case(coding));
+        let any = weights.iter().find(|(c, _)| *c == "*");
+        match (named, any) {
+            (Some(&(_, q)), _) | (None, Some(&(_, q))) => q,
+            // acceptable, but below anything the client asked for
+            (None, None) if coding.eq_ignore_ascii_case("identity") => 1,
+            (None, None) => 0,
+        }
+    };
+
+    let mut best = None;
+    for &coding in available {
+        let q = weight(coding);
+        if q > 0 && best.is_none_or(|(_, best_q)| q > best_q) {
+            best = Some((coding, q));
+        }
+    }
+    best.map(|(coding, _)| coding)
+}
+
+/// Checks an RFC 4647 basic language range: `*`, or `-`-separated segments
+/// of 1 to 8 letters, where segments after the first may also hold digits.
+///
+/// Returns the position of the first offending byte.
+pub(crate) fn check_language_range(range: &str) -> Result<(), usize> {
+    if range == "*" {
+        return Ok(());
+    }
+
+    let mut start = 0;
+    for (i, segment) in range.split('-').enumerate() {
+        let bad = segment.bytes().position(|ch| match i {
+            0 => !ch.is_ascii_alphabetic(),
+            _ => !ch.is_ascii_alphanumeric(),
+        });
+        if let Some(pos) = bad {
+            return Err(start + pos);
+        }
+        if segment.is_empty() || segment.len() > 8 {
+            return Err(start + segment.len().min(8));
+        }
+        start += segment.len() + 1;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+fn qvalue(q: Option<f32>) -> String {
+    String::from_utf8(qvalue_chunks(q).concat()).unwrap()
+}
+
+#[test]
+fn qvalue_format() {
+    assert_eq!(qvalue(None), "");
+    assert_eq!(qvalue(Some(1.0)), ";q=1");
+    assert_eq!(qvalue(Some(0.0)), ";q=0");
+    assert_eq!(qvalue(Some(0.5)), ";q=0.5");
+    assert_eq!(qvalue(Some(0.05)), ";q=0.05");
+    assert_eq!(qvalue(Some(0.123)), ";q=0.123");
+    assert_eq!(qvalue(Some(0.9999)), ";q=1");
+    assert_eq!(qvalue_millis(1.5), None);
+    assert_eq!(qvalue_millis(-0.1), None);
+    assert_eq!(qvalue_millis(f32::NAN), None);
+}
+
+#[test]
+fn qvalue_parse() {
+    assert_eq!(parse_qvalue("1"), Some(1000));
+    assert_eq!(parse_qvalue("1.000"), Some(1000));
+    assert_eq!(parse_qvalue("0.8"), Some(800));
+    assert_eq!(parse_qvalue("0.001"), Some(1));
+    assert_eq!(parse_qvalue("0"), Some(0));
+    assert_eq!(parse_qvalue("1.5"), None);
+    assert_eq!(parse_qvalue("0.1234"), None);
+    assert_eq!(parse_qvalue("2"), None);
+    assert_eq!(parse_qvalue(".5"), None);
+    assert_eq!(parse_qvalue(""), None);
+}
+
+#[test]
+fn choose_encoding_rfc_examples() {
+    let available = ["br", "gzip", "compress", "identity"];
+    assert_eq!(choose_encoding("compress, gzip", &available), Some("gzip"));
+    assert_eq!(choose_encoding("*", &available), Some("br"));
+    assert_eq!(choose_encoding("compress;q=0.5, gzip;q=1.0", &available), Some("gzip"));
+    assert_eq!(choose_encoding("gzip;q=1.0, identity; q=0.5, *;q=0", &available), Some("gzip"));
+    assert_eq!(choose_encoding("gzip;q=1.0, br;q=0.8, *;q=0.1", &available), Some("gzip"));
+    // ties go to the server's order
+    assert_eq!(choose_encoding("gzip, br", &available), Some("br"));
+    assert_eq!(choose_encoding("GZIP;Q=0.9", &["gzip"]), Some("gzip"));
+}
+
+#[test]
+fn choose_encoding_identity() {
+    assert_eq!(choose_encoding("br", &["gzip", "identity"]), Some("identity"));
+    assert_eq!(choose_encoding("br, identity;q=0", &["gzip", "identity"]), None);
+    assert_eq!(choose_encoding("*;q=0", &["gzip", "identity"]), None);
+    assert_eq!(choose_encoding("*;q=0, identity;q=0.1", &["gzip", "identity"]), Some("identity"));
+    assert_eq!(choose_encoding("identity;q=0, *", &["identity", "gzip"]), Some("gzip"));
+
+    // an empty header only accepts identity
+    assert_eq!(choose_encoding("", &["gzip", "identity"]), Some("identity"));
+    assert_eq!(choose_encoding("", &["gzip"]), None);
+}
+
+#[test]
+fn choose_encoding_garbage() {
+    assert_eq!(choose_encoding("gzip;q=2, br;q=abc, compress;q=0.5", &["gzip", "br", "compress"]), Some("compress"));
+    assert_eq!(choose_encoding("g zip, ;;, ,,,", &["gzip", "identity"]), Some("identity"));
+    assert_eq!(choose_encoding("\u{0}\u{7f}", &["gzip"]), None);
+    assert_eq!(choose_encoding("gzip", &[]), None);
+}
+
+#[test]
+fn language_ranges() {
+    assert_eq!(check_language_range("*"), Ok(()));
+    assert_eq!(check_language_range("fr"), Ok(()));
+    assert_eq!(check_language_range("de-CH-1996"), Ok(()));
+    assert_eq!(check_language_range("en_US"), Err(2));
+    assert_eq!(check_language_range("1en"), Err(0));
+    assert_eq!(check_language_range("en-"), Err(3));
+    assert_eq!(check_language_range(""), Err(0));
+    assert_eq!(check_language_range("abcdefghi"), Err(8));
+}
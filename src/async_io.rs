@@ -0,0 +1,336 @@
+//! Writing messages to a [`tokio::io::AsyncWrite`].
+
+use crate::{Body, BodyError, Field, Framing, Query, Request, RequestWriteError, Response, ResponseWriteError, Version};
+use std::io::{self, Write};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// The size of the buffer a streamed body is copied through.
+const BUF_LEN: usize = 8 * 1024;
+
+impl<'a, T, Q, V, B> Request<'a, T, Q, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    Q: Iterator<Item = Query<'a>>,
+    V: Version<'a>,
+    B: Body,
+{
+    /// Like [`Request::write_to`], for an async writer.
+    ///
+    /// The request is written into a buffer first, so every check has run,
+    /// and a request that fails one has sent nothing, before the first write
+    /// is awaited.
+    pub async fn write_to_async<W: AsyncWrite + Unpin + ?Sized>(&mut self, w: &mut W) -> Result<usize, RequestWriteError> {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf)?;
+        let mut written = 0;
+        write_all(w, &buf, &mut written).await.map_err(|source| RequestWriteError::Io { source, written })?;
+        Ok(written)
+    }
+}
+
+impl<'a, T, V, B> Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+    B: Body,
+{
+    /// Like [`Request::write_to_async`].
+    pub async fn write_to_async<W: AsyncWrite + Unpin + ?Sized>(&mut self, w: &mut W) -> Result<usize, ResponseWriteError> {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf)?;
+        let mut written = 0;
+        write_all(w, &buf, &mut written).await.map_err(|source| ResponseWriteError::Io { source, written })?;
+        Ok(written)
+    }
+}
+
+impl<'a, T, Q, V> Request<'a, T, Q, V>
+where
+    T: Iterator<Item = Field<'a>>,
+    Q: Iterator<Item = Query<'a>>,
+    V: Version<'a>,
+{
+    /// Writes the request with a body streamed from `body`: exactly `len`
+    /// bytes of it with a `Content-Length`, or all of it chunked if `len` is
+    /// `None`. A reader that ends short of `len` fails with
+    /// [`io::ErrorKind::UnexpectedEof`]. Headers that frame the body other
+    /// than that fail with [`RequestWriteError::ConflictingFraming`].
+    ///
+    /// The head is checked like [`Request::write_to_async`] before anything
+    /// is sent. Dropping the future part way may leave a partial
+    /// message on `w`, after which the connection has to be closed.
+    pub async fn write_with_body_async<W, R>(self, w: &mut W, body: &mut R, len: Option<u64>) -> Result<usize, RequestWriteError>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+        R: AsyncRead + Unpin + ?Sized,
+    {
+        let framing = Streamed::new(len);
+        let mut head = Vec::new();
+        self.set_body(framing).write_to(&mut head)?;
+
+        let mut written = 0;
+        write_all(w, &head, &mut written).await.map_err(|source| RequestWriteError::Io { source, written })?;
+        copy_body(w, body, framing.0, &mut written).await.map_err(|source| RequestWriteError::Io { source, written })?;
+        Ok(written)
+    }
+}
+
+impl<'a, T, V> Response<'a, T, V>
+where
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+{
+    /// Like [`Request::write_with_body_async`]. A response that sends no body,
+    /// such as a `204` or one to `HEAD`, reads nothing from `body`.
+    pub async fn write_with_body_async<W, R>(self, w: &mut W, body: &mut R, len: Option<u64>) -> Result<usize, ResponseWriteError>
+    where
+        W: AsyncWrite + Unpin + ?Sized,
+        R: AsyncRead + Unpin + ?Sized,
+    {
+        let streamed = Streamed::new(len);
+        let mut res = self.set_body(streamed);
+        let mut head = Vec::new();
+        res.write_to(&mut head)?;
+        let framing = if res.sends_body() {
+            streamed.0
+        } else {
+            Framing::None
+        };
+
+        let mut written = 0;
+        write_all(w, &head, &mut written).await.map_err(|source| ResponseWriteError::Io { source, written })?;
+        copy_body(w, body, framing, &mut written).await.map_err(|source| ResponseWriteError::Io { source, written })?;
+        Ok(written)
+    }
+}
+
+/// A body streamed after the head, which only announces its framing.
+#[derive(Clone, Copy)]
+struct Streamed(Framing);
+
+impl Streamed {
+    fn new(len: Option<u64>) -> Self {
+        Self(len.map_or(Framing::Chunked, Framing::Length))
+    }
+}
+
+impl Body for Streamed {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        Ok(self.0)
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, _: &mut W) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+/// Copies the body from `r` to `w`, encoded for `framing`.
+async fn copy_body<W, R>(w: &mut W, r: &mut R, framing: Framing, written: &mut usize) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+    R: AsyncRead + Unpin + ?Sized,
+{
+    if framing == Framing::None {
+        return Ok(());
+    }
+    let mut buf = vec![0; BUF_LEN];
+    match framing {
+        Framing::None => {}
+        Framing::Length(mut left) => {
+            while left > 0 {
+                let max = usize::try_from(left).map_or(BUF_LEN, |left| left.min(BUF_LEN));
+                let n = read(r, &mut buf[..max]).await?;
+                if n == 0 {
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "body ended before its Content-Length"));
+                }
+                write_all(w, &buf[..n], written).await?;
+                left -= n as u64;
+            }
+        }
+        Framing::Chunked => loop {
+            let n = read(r, &mut buf).await?;
+            if n == 0 {
+                return write_all(w, b"0\r\n\r\n", written).await;
+            }
+            let mut size = [0; crate::body::CHUNK_SIZE_LEN];
+            write_all(w, crate::body::chunk_size(&mut size, n), written).await?;
+            write_all(w, &buf[..n], written).await?;
+            write_all(w, b"\r\n", written).await?;
+        },
+    }
+    Ok(())
+}
+
+/// Reads into `buf`, retrying when interrupted.
+async fn read<R: AsyncRead + Unpin + ?Sized>(r: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match r.read(buf).await {
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            res => return res,
+        }
+    }
+}
+
+/// Writes all of `buf`, adding what `w` accepted to `written`, so it is
+/// still counted if a later write fails.
+async fn write_all<W: AsyncWrite + Unpin + ?Sized>(w: &mut W, buf: &[u8], written: &mut usize) -> io::Result<()> {
+    let mut pos = 0;
+    while pos < buf.len() {
+        match w.write(&buf[pos..]).await {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => {
+                pos += n;
+                *written += n;
+            }
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) fn block_on<F: Future>(f: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(f)
+}
+
+#[test]
+fn write_to_async() {
+    let (len, buf) = streamed(|mut w| async move {
+        let mut req = Request::post().path("/items").v1_1().header("Host", b"example.com").body(b"{\"a\":1}");
+        req.write_to_async(&mut w).await.unwrap()
+    });
+    assert_eq!(len, buf.len());
+
+    let mut headers = [httparse::EMPTY_HEADER; 4];
+    let mut parsed = httparse::Request::new(&mut headers);
+    let httparse::Status::Complete(head) = parsed.parse(&buf).unwrap() else {
+        panic!("incomplete head");
+    };
+    assert_eq!(parsed.method, Some("POST"));
+    assert_eq!(parsed.path, Some("/items"));
+    assert_eq!(parsed.headers[1].name, "Content-Length");
+    assert_eq!(&buf[head..], b"{\"a\":1}");
+}
+
+#[test]
+fn write_to_async_checks_first() {
+    let mut out = Vec::new();
+    let err = block_on(Request::get().path("/").v1_1().header("X-Bad", b"a\r\nb").write_to_async(&mut out));
+    assert!(matches!(err, Err(RequestWriteError::InvalidHeader { .. })));
+    assert!(out.is_empty());
+
+    let len = block_on(Response::new(http::StatusCode::OK).v1_1().body(b"hi").write_to_async(&mut out)).unwrap();
+    assert_eq!(len, out.len());
+    assert_eq!(out, b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nhi");
+}
+
+/// A reader that hands out a few bytes at a time, and is not ready every
+/// other poll.
+#[cfg(test)]
+struct Trickle<'b> {
+    data: &'b [u8],
+    ready: bool,
+}
+
+#[cfg(test)]
+impl AsyncRead for Trickle<'_> {
+    fn poll_read(
+        mut self: core::pin::Pin<&mut Self>,
+        cx: &mut core::task::Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> core::task::Poll<io::Result<()>> {
+        if !core::mem::replace(&mut self.ready, true) {
+            cx.waker().wake_by_ref();
+            return core::task::Poll::Pending;
+        }
+        self.ready = false;
+        let n = self.data.len().min(buf.remaining()).min(3);
+        buf.put_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        core::task::Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+fn streamed<F: Future<Output = usize>>(write: impl FnOnce(tokio::io::DuplexStream) -> F) -> (usize, Vec<u8>) {
+    block_on(async {
+        // a small pipe, so the message goes out in several writes
+        let (client, mut server) = tokio::io::duplex(16);
+        let read = async {
+            let mut buf = Vec::new();
+            server.read_to_end(&mut buf).await.unwrap();
+            buf
+        };
+        tokio::join!(write(client), read)
+    })
+}
+
+#[test]
+fn write_with_body_async() {
+    let body = b"a body longer than a few bytes";
+
+    let (len, buf) = streamed(|mut w| async move {
+        let mut r = Trickle { data: body, ready: false };
+        let req = Request::post().path("/upload").v1_1().header("Host", b"example.com");
+        req.write_with_body_async(&mut w, &mut r, None).await.unwrap()
+    });
+    assert_eq!(len, buf.len());
+    let mut headers = [httparse::EMPTY_HEADER; 4];
+    let mut parsed = httparse::Request::new(&mut headers);
+    let httparse::Status::Complete(head) = parsed.parse(&buf).unwrap() else {
+        panic!("incomplete head");
+    };
+    assert_eq!(parsed.headers[1].name, "Transfer-Encoding");
+    assert_eq!(crate::body::decode_chunked(&buf[head..]), body);
+
+    let (len, buf) = streamed(|mut w| async move {
+        let mut r = Trickle { data: body, ready: false };
+        let res = Response::new(http::StatusCode::OK).v1_1();
+        res.write_with_body_async(&mut w, &mut r, Some(6)).await.unwrap()
+    });
+    assert_eq!(len, buf.len());
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\na body");
+}
+
+#[test]
+fn write_with_body_async_framing() {
+    let mut out = Vec::new();
+    let mut r = Trickle { data: b"abc", ready: false };
+    let err = block_on(Response::new(http::StatusCode::OK).v1_1().write_with_body_async(&mut out, &mut r, Some(4)));
+    let Err(ResponseWriteError::Io { source, written }) = err else {
+        panic!("expected an I/O error");
+    };
+    assert_eq!(source.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(written, out.len());
+    assert!(out.ends_with(b"\r\n\r\nabc"));
+
+    let mut out = Vec::new();
+    let res = Response::new(http::StatusCode::NO_CONTENT).v1_1();
+    block_on(res.write_with_body_async(&mut out, &mut r, None)).unwrap();
+    assert_eq!(out, b"HTTP/1.1 204 No Content\r\n\r\n");
+
+    let mut out = Vec::new();
+    let res = Response::ok().v1_1().to_head_request();
+    block_on(res.write_with_body_async(&mut out, &mut r, Some(3))).unwrap();
+    assert_eq!(out, b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\n");
+
+    let mut out = Vec::new();
+    let req = Request::get().path("/").v1_1().header("Host", b"h");
+    block_on(req.write_with_body_async(&mut out, &mut r, Some(0))).unwrap();
+    assert_eq!(out, b"GET / HTTP/1.1\r\nHost: h\r\n\r\n");
+
+    let req = Request::trace().path("/").v1_1().header("Host", b"h");
+    let err = block_on(req.write_with_body_async(&mut Vec::new(), &mut r, None));
+    assert!(matches!(err, Err(RequestWriteError::BodyNotAllowed)));
+
+    let res = Response::ok().v1_1().header("Content-Length", b"3");
+    let err = block_on(res.write_with_body_async(&mut Vec::new(), &mut r, Some(4)));
+    assert!(matches!(err, Err(ResponseWriteError::ConflictingFraming { .. })));
+
+    let mut out = Vec::new();
+    let req = Request::post().path("/").v1_1().header("X-Bad", b"a\r\nb");
+    let err = block_on(req.write_with_body_async(&mut out, &mut r, None));
+    assert!(matches!(err, Err(RequestWriteError::InvalidHeader { .. })));
+    assert!(out.is_empty());
+}
@@ -0,0 +1,110 @@
+//! A minimal base64 codec, so the crate does not need a dependency for it.
+
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+#[cfg(feature = "base64")]
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// The alphabet [`Request::header_base64_with`](crate::Request::header_base64_with)
+/// encodes with. Both pad with `=`.
+#[cfg(feature = "base64")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// `+` and `/`, per RFC 4648, section 4.
+    #[default]
+    Standard,
+    /// `-` and `_`, per RFC 4648, section 5.
+    UrlSafe,
+}
+
+/// Encodes the bytes of `parts`, back to back, as the one-byte chunks they
+/// are written in, a 3-byte group at a time, so nothing is buffered.
+#[cfg(feature = "base64")]
+pub(crate) fn encode_chunks<'r>(parts: [&'r [u8]; 3], alphabet: Base64Alphabet) -> impl Iterator<Item = &'r [u8]> + 'r {
+    let table = match alphabet {
+        Base64Alphabet::Standard => STANDARD,
+        Base64Alphabet::UrlSafe => URL_SAFE,
+    };
+    let mut bytes = parts.into_iter().flatten().copied();
+    core::iter::from_fn(move || {
+        let group = [Some(bytes.next()?), bytes.next(), bytes.next()];
+        let len = group.iter().flatten().count();
+        let n = group.iter().enumerate().fold(0u32, |n, (i, b)| n | u32::from(b.unwrap_or(0)) << (16 - 8 * i));
+        let symbol = |j: usize| -> &'r [u8] {
+            match j <= len {
+                true => {
+                    let v = (n >> (18 - 6 * j) & 63) as usize;
+                    &table[v..v + 1]
+                }
+                false => b"=",
+            }
+        };
+        Some([symbol(0), symbol(1), symbol(2), symbol(3)])
+    })
+    .flatten()
+}
+
+/// Decodes padded standard base64, returning the position of the first bad
+/// byte on failure.
+#[cfg(any(feature = "serde", test))]
+pub(crate) fn decode(input: &[u8]) -> Result<Vec<u8>, usize> {
+    if !input.len().is_multiple_of(4) {
+        return Err(input.len());
+    }
+
+    let mut out = Vec::with_capacity(input.len() / 4 * 3);
+    for (i, group) in input.chunks_exact(4).enumerate() {
+        let last = (i + 1) * 4 == input.len();
+        let pad = group.iter().rev().take_while(|&&ch| ch == b'=').count();
+        if pad > 2 || (pad > 0 && !last) {
+            return Err(i * 4 + 4 - pad);
+        }
+
+        let mut n = 0u32;
+        for (j, &ch) in group[..4 - pad].iter().enumerate() {
+            let v = STANDARD
+                .iter()
+                .position(|&c| c == ch)
+                .ok_or(i * 4 + j)?;
+            n |= (v as u32) << (18 - 6 * j);
+        }
+
+        let bytes = n.to_be_bytes();
+        out.extend_from_slice(&bytes[1..4 - pad]);
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn decode_vectors() {
+    assert_eq!(decode(b"").unwrap(), b"");
+    assert_eq!(decode(b"Zg==").unwrap(), b"f");
+    assert_eq!(decode(b"Zm8=").unwrap(), b"fo");
+    assert_eq!(decode(b"Zm9v").unwrap(), b"foo");
+    assert_eq!(decode(b"Zm9vYmFy").unwrap(), b"foobar");
+    assert_eq!(decode(b"Zm9"), Err(3));
+    assert_eq!(decode(b"Zm=v"), Err(2));
+    assert_eq!(decode(b"Zm*v"), Err(2));
+}
+
+#[cfg(feature = "base64")]
+#[test]
+fn encode_vectors() {
+    let encode = |raw: &[u8], alphabet| encode_chunks([raw, b"", b""], alphabet).collect::<Vec<_>>().concat();
+    // RFC 4648, section 10
+    for (raw, encoded) in [
+        (&b""[..], &b""[..]),
+        (b"f", b"Zg=="),
+        (b"fo", b"Zm8="),
+        (b"foo", b"Zm9v"),
+        (b"foob", b"Zm9vYg=="),
+        (b"fooba", b"Zm9vYmE="),
+        (b"foobar", b"Zm9vYmFy"),
+    ] {
+        assert_eq!(encode(raw, Base64Alphabet::Standard), encoded);
+        assert_eq!(encode(raw, Base64Alphabet::UrlSafe), encoded);
+    }
+    assert_eq!(encode(&[0xfb, 0xff, 0xbf], Base64Alphabet::Standard), b"+/+/");
+    assert_eq!(encode(&[0xfb, 0xff, 0xbf], Base64Alphabet::UrlSafe), b"-_-_");
+    assert_eq!(encode(&[0xff], Base64Alphabet::UrlSafe), b"_w==");
+}
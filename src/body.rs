@@ -0,0 +1,356 @@
+//! Message bodies, and the framing headers the head announces for them.
+
+use crate::Field;
+use std::io::{self, Write};
+
+/// An error a [`Body`] reports before anything is written.
+pub type BodyError = Box<dyn std::error::Error + Send + Sync>;
+
+/// How the head frames the body that follows it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Framing {
+    /// The message has no body, so no framing header is added.
+    None,
+    /// A `Content-Length` of this many bytes.
+    Length(u64),
+    /// `Transfer-Encoding: chunked`, for a body whose length is not known up
+    /// front.
+    Chunked,
+}
+
+/// The body written after the head of a [`Request`](crate::Request) or
+/// [`Response`](crate::Response).
+pub trait Body {
+    /// Called before the head is written, so a body that has to be encoded
+    /// first can fail without leaving a partial message behind.
+    fn framing(&mut self) -> Result<Framing, BodyError>;
+
+    /// Writes the body, already encoded for the [`Framing`] it announced.
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize>;
+}
+
+/// The body of a message without one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NoBody;
+
+impl Body for NoBody {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        Ok(Framing::None)
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, _: &mut W) -> io::Result<usize> {
+        Ok(0)
+    }
+}
+
+impl Body for &[u8] {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        Ok(Framing::Length(self.len() as u64))
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize> {
+        w.write_all(self)?;
+        Ok(self.len())
+    }
+}
+
+impl Body for Vec<u8> {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        self.as_slice().framing()
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize> {
+        self.as_slice().write_body(w)
+    }
+}
+
+/// Ends the head, first adding the header for `framing` unless the headers
+/// were already `framed`.
+pub(crate) fn write_framing<W: Write + ?Sized>(w: &mut W, framing: Framing, framed: bool) -> io::Result<usize> {
+    let mut len = 0;
+    if let Some(field) = framing_field(framing, framed) {
+        // SAFETY: the crate builds these headers itself
+        len += unsafe { field.write_unchecked(w)? };
+    }
+    w.write_all(b"\r\n")?;
+    Ok(len + 2)
+}
+
+/// The header for `framing`, unless the headers were already `framed`.
+pub(crate) fn framing_field(framing: Framing, framed: bool) -> Option<Field<'static>> {
+    match (framing, framed) {
+        (Framing::Length(len), false) => Some(Field::content_length(len)),
+        (Framing::Chunked, false) => Some(crate::consts::TRANSFER_ENCODING_CHUNKED.into()),
+        _ => None,
+    }
+}
+
+/// Frames every write as one chunk of a chunked body.
+pub(crate) struct ChunkedWriter<'w, W: ?Sized> {
+    inner: &'w mut W,
+    written: usize,
+}
+
+impl<'w, W: Write + ?Sized> ChunkedWriter<'w, W> {
+    pub(crate) fn new(inner: &'w mut W) -> Self {
+        Self { inner, written: 0 }
+    }
+
+    /// Writes the last, empty chunk and returns the bytes written in total.
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    pub(crate) fn finish(self) -> io::Result<usize> {
+        // SAFETY: there are no trailers to check
+        unsafe { self.finish_with(core::iter::empty()) }
+    }
+
+    /// Like [`ChunkedWriter::finish`], with `trailers` between the last chunk
+    /// and the blank line that ends the body.
+    ///
+    /// # Safety
+    ///
+    /// Caller must guarantee that every trailer is valid.
+    pub(crate) unsafe fn finish_with<'t>(self, trailers: impl Iterator<Item = Field<'t>>) -> io::Result<usize> {
+        self.inner.write_all(b"0\r\n")?;
+        let mut len = self.written + 3;
+        for trailer in trailers {
+            // SAFETY: guaranteed by the caller
+            len += unsafe { trailer.write_unchecked(self.inner)? };
+        }
+        self.inner.write_all(b"\r\n")?;
+        Ok(len + 2)
+    }
+}
+
+/// The longest chunk-size line: 16 hex digits and its CRLF.
+pub(crate) const CHUNK_SIZE_LEN: usize = 18;
+
+/// Formats the size line of a chunk of `len` bytes into `buf`.
+pub(crate) fn chunk_size(buf: &mut [u8; CHUNK_SIZE_LEN], len: usize) -> &[u8] {
+    let mut rest = buf.as_mut_slice();
+    // a usize has at most 16 hex digits, so this always fits
+    let _ = write!(rest, "{len:x}\r\n");
+    let left = rest.len();
+    &buf[..CHUNK_SIZE_LEN - left]
+}
+
+impl<W: Write + ?Sized> Write for ChunkedWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut size = [0; CHUNK_SIZE_LEN];
+        let size = chunk_size(&mut size, buf.len());
+        self.inner.write_all(size)?;
+        self.inner.write_all(buf)?;
+        self.inner.write_all(b"\r\n")?;
+        self.written += size.len() + buf.len() + 2;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A body generated piece by piece, without a known length, sent as one
+/// chunk per item with `Transfer-Encoding: chunked`.
+///
+/// Empty items are skipped, as an empty chunk would end the body.
+pub struct ChunkedBody<I, H = core::iter::Empty<Field<'static>>> {
+    chunks: I,
+    trailers: H,
+}
+
+impl<I: Iterator> ChunkedBody<I> {
+    pub fn new(chunks: impl IntoIterator<IntoIter = I>) -> Self {
+        Self {
+            chunks: chunks.into_iter(),
+            trailers: core::iter::empty(),
+        }
+    }
+}
+
+impl<I, H> ChunkedBody<I, H> {
+    /// Sends `trailers` after the last chunk, for values only known once the
+    /// body is out, such as a checksum. They are checked like headers before
+    /// the head is written.
+    ///
+    /// Recipients may drop trailers they were not told about, so name each
+    /// one in a `Trailer` header.
+    pub fn trailers<'t, H2>(self, trailers: H2) -> ChunkedBody<I, H2::IntoIter>
+    where
+        H2: IntoIterator<Item = Field<'t>>,
+        H2::IntoIter: Clone,
+    {
+        ChunkedBody {
+            chunks: self.chunks,
+            trailers: trailers.into_iter(),
+        }
+    }
+}
+
+impl<'t, I, H> Body for ChunkedBody<I, H>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+    H: Iterator<Item = Field<'t>> + Clone,
+{
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        for trailer in self.trailers.clone() {
+            trailer
+                .validate()
+                .map_err(|err| format!("invalid trailer {:?}: {err}", trailer.name()))?;
+        }
+        Ok(Framing::Chunked)
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize> {
+        let mut chunked = ChunkedWriter::new(w);
+        for chunk in &mut self.chunks {
+            chunked.write_all(chunk.as_ref())?;
+        }
+        // SAFETY: the trailers were checked by `framing`
+        unsafe { chunked.finish_with(&mut self.trailers) }
+    }
+}
+
+/// A body of `len` bytes copied from a reader while it is written, so it is
+/// never held in memory as a whole.
+///
+/// A reader that ends before `len` bytes fails the write with
+/// [`io::ErrorKind::UnexpectedEof`]; one with more is read no further.
+pub struct ReaderBody<R> {
+    reader: R,
+    len: u64,
+}
+
+impl<R: io::Read> ReaderBody<R> {
+    pub fn new(reader: R, len: u64) -> Self {
+        Self { reader, len }
+    }
+}
+
+impl<R: io::Read> Body for ReaderBody<R> {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        Ok(Framing::Length(self.len))
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize> {
+        let copied = io::copy(&mut io::Read::take(&mut self.reader, self.len), w)?;
+        if copied < self.len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("body reader ended after {copied} of {} bytes", self.len),
+            ));
+        }
+        Ok(copied as usize)
+    }
+}
+
+/// Serializes the value to JSON before the head is written, so it can be
+/// sent with an exact `Content-Length`.
+#[cfg(feature = "json")]
+pub struct Json<'v, T: ?Sized> {
+    value: &'v T,
+    encoded: Vec<u8>,
+}
+
+#[cfg(feature = "json")]
+impl<'v, T: serde::Serialize + ?Sized> Json<'v, T> {
+    pub fn new(value: &'v T) -> Self {
+        Self {
+            value,
+            encoded: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: serde::Serialize + ?Sized> Body for Json<'_, T> {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        self.encoded = serde_json::to_vec(self.value)?;
+        self.encoded.framing()
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize> {
+        self.encoded.write_body(w)
+    }
+}
+
+/// Serializes the value to JSON while it is written, as a chunked body, so
+/// a large value is never held in memory whole.
+///
+/// A serialization error surfaces as an I/O error partway through the body.
+#[cfg(feature = "json")]
+pub struct JsonStream<'v, T: ?Sized> {
+    value: &'v T,
+}
+
+#[cfg(feature = "json")]
+impl<'v, T: serde::Serialize + ?Sized> JsonStream<'v, T> {
+    pub fn new(value: &'v T) -> Self {
+        Self { value }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<T: serde::Serialize + ?Sized> Body for JsonStream<'_, T> {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        Ok(Framing::Chunked)
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize> {
+        let mut chunked = ChunkedWriter::new(w);
+        {
+            // so the serializer's many small writes become a few large chunks
+            let mut buffered = io::BufWriter::with_capacity(8 * 1024, &mut chunked);
+            serde_json::to_writer(&mut buffered, self.value)?;
+            buffered.flush()?;
+        }
+        chunked.finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn decode_chunked(body: &[u8]) -> Vec<u8> {
+    let (out, trailers) = decode_chunked_trailers(body);
+    assert!(trailers.is_empty());
+    out
+}
+
+/// The body and the trailers of a chunked body.
+#[cfg(test)]
+pub(crate) fn decode_chunked_trailers(mut body: &[u8]) -> (Vec<u8>, Vec<(String, Vec<u8>)>) {
+    let mut out = Vec::new();
+    loop {
+        let line = body.iter().position(|&ch| ch == b'\r').unwrap();
+        let size = usize::from_str_radix(core::str::from_utf8(&body[..line]).unwrap(), 16).unwrap();
+        body = &body[line + 2..];
+        if size == 0 {
+            let mut headers = [httparse::EMPTY_HEADER; 8];
+            let httparse::Status::Complete((len, trailers)) = httparse::parse_headers(body, &mut headers).unwrap() else {
+                panic!("incomplete trailers");
+            };
+            assert_eq!(len, body.len());
+            let trailers = trailers.iter().map(|h| (h.name.to_owned(), h.value.to_vec())).collect();
+            return (out, trailers);
+        }
+        out.extend_from_slice(&body[..size]);
+        assert_eq!(&body[size..size + 2], b"\r\n");
+        body = &body[size + 2..];
+    }
+}
+
+#[test]
+fn chunked_writer() {
+    let mut buf = Vec::new();
+    let mut chunked = ChunkedWriter::new(&mut buf);
+    chunked.write_all(b"hello, ").unwrap();
+    chunked.write_all(b"").unwrap();
+    chunked.write_all(b"chunked world").unwrap();
+    let len = chunked.finish().unwrap();
+
+    assert_eq!(buf, b"7\r\nhello, \r\nd\r\nchunked world\r\n0\r\n\r\n");
+    assert_eq!(len, buf.len());
+    assert_eq!(decode_chunked(&buf), b"hello, chunked world");
+}
@@ -0,0 +1,161 @@
+//! Bodies held in [`Bytes`] segments, sent without copying them.
+
+use crate::{Body, BodyError, Field, Framing, Response, ResponseWriteError, Version};
+use bytes::Bytes;
+use crate::vectored::write_all_vectored;
+use std::io::{self, Write};
+#[cfg(test)]
+use std::io::IoSlice;
+
+/// A body of [`Bytes`] segments, framed with the sum of their lengths; see
+/// [`Response::body_bytes`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BytesBody {
+    segments: Vec<Bytes>,
+}
+
+impl BytesBody {
+    pub fn new(segments: impl IntoIterator<Item = Bytes>) -> Self {
+        Self {
+            segments: segments.into_iter().collect(),
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.segments.iter().map(|s| s.len() as u64).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.iter().all(Bytes::is_empty)
+    }
+}
+
+impl Body for BytesBody {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        Ok(Framing::Length(self.len()))
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize> {
+        write_all_vectored(w, self.segments.iter().map(|s| &s[..]))
+    }
+}
+
+impl<'a, T, V> Response<'a, T, V>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Sends `segments` back to back, with a `Content-Length` of their total
+    /// length.
+    pub fn body_bytes(self, segments: impl IntoIterator<Item = Bytes>) -> Response<'a, T, V, BytesBody> {
+        self.set_body(BytesBody::new(segments))
+    }
+}
+
+impl<'a, T, V> Response<'a, T, V, BytesBody>
+where
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+{
+    /// Like [`Response::write_to`], but the head is serialized first and
+    /// then sent along with every segment in `write_vectored` calls, so the
+    /// body is never copied.
+    ///
+    /// A write that stops partway through the head or a segment is resumed
+    /// where it stopped.
+    pub fn write_vectored_to<W: Write + ?Sized>(&mut self, w: &mut W) -> Result<usize, ResponseWriteError> {
+        let mut head = Vec::with_capacity(256);
+        self.write_head(&mut head)?;
+        let mut w = crate::Counter::new(w);
+        let segments = if self.sends_body() { self.body.segments.as_slice() } else { &[] };
+        let bufs = core::iter::once(head.as_slice()).chain(segments.iter().map(|s| &s[..]));
+        write_all_vectored(&mut w, bufs).map_err(|e| ResponseWriteError::from(e).with_written(w.written))
+    }
+}
+
+/// Accepts at most `max` bytes per call, spread over the buffers it is
+/// given, and records how many buffers each call saw.
+#[cfg(test)]
+struct ShortVectored {
+    out: Vec<u8>,
+    max: usize,
+    calls: Vec<usize>,
+}
+
+#[cfg(test)]
+impl Write for ShortVectored {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_vectored(&[IoSlice::new(buf)])
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.calls.push(bufs.len());
+        let mut n = 0;
+        for buf in bufs {
+            let take = buf.len().min(self.max - n);
+            self.out.extend_from_slice(&buf[..take]);
+            n += take;
+            if n == self.max {
+                break;
+            }
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn response(segments: &[&'static [u8]]) -> Response<'static, impl Iterator<Item = Field<'static>>, crate::version::V1_1, BytesBody> {
+    Response::new(http::StatusCode::OK)
+        .v1_1()
+        .header("Content-Type", b"application/octet-stream")
+        .body_bytes(segments.iter().map(|s| Bytes::from_static(s)))
+}
+
+#[test]
+fn short_vectored_writes() {
+    let shared = Bytes::from_static(b"0123456789abcdefghij");
+    let segments = [shared.slice(0..7), shared.slice(7..8), Bytes::new(), shared.slice(8..)];
+    let mut expected = b"HTTP/1.1 200 OK\r\nContent-Length: 20\r\n\r\n".to_vec();
+    expected.extend_from_slice(&shared);
+
+    for max in [1, 3, 7, 19, 4096] {
+        let mut w = ShortVectored { out: Vec::new(), max, calls: Vec::new() };
+        let mut res = Response::new(http::StatusCode::OK).v1_1().body_bytes(segments.clone());
+        let len = res.write_vectored_to(&mut w).unwrap();
+        assert_eq!(len, expected.len());
+        assert_eq!(w.out, expected, "max {max}");
+        // the head and the three non-empty segments went out together
+        assert_eq!(w.calls[0], 4);
+    }
+
+    let mut plain = Vec::new();
+    Response::new(http::StatusCode::OK).v1_1().body_bytes(segments).write_to(&mut plain).unwrap();
+    assert_eq!(plain, expected);
+}
+
+#[test]
+fn empty_segments() {
+    for segments in [&[][..], &[b"".as_slice(), b""]] {
+        let mut w = ShortVectored { out: Vec::new(), max: 5, calls: Vec::new() };
+        let len = response(segments).write_vectored_to(&mut w).unwrap();
+        let expected = b"HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: 0\r\n\r\n";
+        assert_eq!(w.out, expected);
+        assert_eq!(len, expected.len());
+        assert!(w.calls.iter().all(|&n| n == 1));
+    }
+
+    let mut full = [0u8; 20];
+    let err = response(&[b"body"]).write_vectored_to(&mut full.as_mut_slice());
+    assert!(matches!(
+        err,
+        Err(ResponseWriteError::Io { ref source, written: 20 }) if source.kind() == io::ErrorKind::WriteZero
+    ));
+
+    let mut w = Vec::new();
+    let err = Response::new(http::StatusCode::OK).v1_1().header("Bad Name", b"x").body_bytes([]).write_vectored_to(&mut w);
+    assert!(matches!(err, Err(ResponseWriteError::InvalidHeader { .. })));
+    assert!(w.is_empty());
+}
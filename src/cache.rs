@@ -0,0 +1,164 @@
+//! `Cache-Control`, `Expires` and `Age` that agree with each other.
+
+use crate::consts::names;
+use crate::field::Inline;
+use crate::{Field, Response};
+use core::iter::{Chain, Flatten};
+use core::time::Duration;
+use std::time::SystemTime;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Directives {
+    Public(Duration),
+    Private(Duration),
+    NoStore,
+    NoCache,
+}
+
+/// How a response may be cached, written by [`Response::cache_policy`] as
+/// one consistent set of headers.
+///
+/// Only one kind of policy can be picked, so `no-store` never meets a
+/// `max-age`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CachePolicy {
+    directives: Directives,
+    generated_at: Option<SystemTime>,
+}
+
+/// The headers attached by [`Response::cache_policy`].
+pub type CacheHeaders<'a> = Flatten<core::array::IntoIter<Option<Field<'a>>, 3>>;
+
+impl CachePolicy {
+    /// `Cache-Control: public, max-age=…`, for shared caches too.
+    pub fn public_for(max_age: Duration) -> Self {
+        Self::new(Directives::Public(max_age))
+    }
+
+    /// `Cache-Control: private, max-age=…`, for the client's cache only.
+    pub fn private_for(max_age: Duration) -> Self {
+        Self::new(Directives::Private(max_age))
+    }
+
+    /// `Cache-Control: no-store`, and nothing else.
+    pub fn no_store() -> Self {
+        Self::new(Directives::NoStore)
+    }
+
+    /// `Cache-Control: no-cache`, so a stored response is revalidated before
+    /// every use.
+    pub fn revalidate_always() -> Self {
+        Self::new(Directives::NoCache)
+    }
+
+    /// When the content was generated, such as when an upstream response
+    /// was received.
+    ///
+    /// With the `date` feature, a cacheable response gets an `Expires` that
+    /// far from it; a time in the past adds an `Age` as well. A `no-store`
+    /// response ignores it.
+    pub fn generated_at(mut self, when: SystemTime) -> Self {
+        self.generated_at = Some(when);
+        self
+    }
+
+    fn new(directives: Directives) -> Self {
+        Self {
+            directives,
+            generated_at: None,
+        }
+    }
+
+    fn headers<'a>(&self, now: SystemTime) -> CacheHeaders<'a> {
+        let max_age = |prefix, age: Duration| Field::inline(names::CACHE_CONTROL, Inline::prefixed(prefix, age.as_secs()));
+        let (cache_control, max_age) = match self.directives {
+            Directives::Public(age) => (max_age(b"public, max-age=", age), age),
+            Directives::Private(age) => (max_age(b"private, max-age=", age), age),
+            Directives::NoStore => {
+                let no_store = crate::consts::CACHE_CONTROL_NO_STORE.into();
+                return [Some(no_store), None, None].into_iter().flatten();
+            }
+            Directives::NoCache => (crate::consts::CACHE_CONTROL_NO_CACHE.into(), Duration::ZERO),
+        };
+        let age = self
+            .generated_at
+            .and_then(|at| now.duration_since(at).ok())
+            .filter(|age| age.as_secs() > 0)
+            .map(|age| Field::inline(names::AGE, Inline::decimal(age.as_secs())));
+        [Some(cache_control), self.expires(max_age), age].into_iter().flatten()
+    }
+
+    #[cfg(feature = "date")]
+    fn expires<'a>(&self, max_age: Duration) -> Option<Field<'a>> {
+        let expires = self.generated_at?.checked_add(Duration::from_secs(max_age.as_secs()))?;
+        Some(Field::inline(names::EXPIRES, Inline::new(&crate::date::format_clamped(expires))))
+    }
+
+    #[cfg(not(feature = "date"))]
+    fn expires<'a>(&self, _: Duration) -> Option<Field<'a>> {
+        None
+    }
+}
+
+impl<'a, T, V, B> Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Adds the headers of `policy`, with an `Age` as of now.
+    pub fn cache_policy(self, policy: &CachePolicy) -> Response<'a, Chain<T, CacheHeaders<'a>>, V, B> {
+        self.cache_policy_at(policy, SystemTime::now())
+    }
+
+    /// Like [`Response::cache_policy`], with an `Age` as of `now`.
+    pub fn cache_policy_at(self, policy: &CachePolicy, now: SystemTime) -> Response<'a, Chain<T, CacheHeaders<'a>>, V, B> {
+        self.fields(policy.headers(now))
+    }
+}
+
+#[cfg(test)]
+fn written(policy: CachePolicy, now: SystemTime) -> String {
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::OK).v1_1().cache_policy_at(&policy, now).write_to(&mut buf).unwrap();
+    String::from_utf8(buf).unwrap().strip_prefix("HTTP/1.1 200 OK\r\n").unwrap().to_owned()
+}
+
+#[test]
+fn policies() {
+    let now = std::time::UNIX_EPOCH + Duration::from_secs(784111777);
+    let hour = Duration::from_secs(3600);
+    assert_eq!(written(CachePolicy::public_for(hour), now), "Cache-Control: public, max-age=3600\r\n\r\n");
+    assert_eq!(
+        written(CachePolicy::private_for(Duration::from_millis(90_500)), now),
+        "Cache-Control: private, max-age=90\r\n\r\n"
+    );
+    assert_eq!(written(CachePolicy::revalidate_always(), now), "Cache-Control: no-cache\r\n\r\n");
+    assert_eq!(written(CachePolicy::no_store(), now), "Cache-Control: no-store\r\n\r\n");
+
+    // generated in the future, such as with a clock skew, has no age
+    let ahead = CachePolicy::public_for(hour).generated_at(now + Duration::from_secs(5));
+    assert!(!written(ahead, now).contains("Age"));
+}
+
+#[cfg(feature = "date")]
+#[test]
+fn generated_at() {
+    let now = std::time::UNIX_EPOCH + Duration::from_secs(784111777);
+    let hour = Duration::from_secs(3600);
+    let earlier = now - Duration::from_secs(120);
+    assert_eq!(
+        written(CachePolicy::public_for(hour).generated_at(earlier), now),
+        "Cache-Control: public, max-age=3600\r\n\
+         Expires: Sun, 06 Nov 1994 09:47:37 GMT\r\n\
+         Age: 120\r\n\r\n"
+    );
+    assert_eq!(
+        written(CachePolicy::private_for(hour).generated_at(now), now),
+        "Cache-Control: private, max-age=3600\r\nExpires: Sun, 06 Nov 1994 09:49:37 GMT\r\n\r\n"
+    );
+    assert_eq!(
+        written(CachePolicy::revalidate_always().generated_at(earlier), now),
+        "Cache-Control: no-cache\r\nExpires: Sun, 06 Nov 1994 08:47:37 GMT\r\nAge: 120\r\n\r\n"
+    );
+    // no-store stands alone
+    assert_eq!(written(CachePolicy::no_store().generated_at(earlier), now), "Cache-Control: no-store\r\n\r\n");
+}
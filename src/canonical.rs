@@ -0,0 +1,160 @@
+//! A canonical form of a request, for cache keys and signature bases.
+
+use crate::{Body, Field, Query, Request, RequestWriteError, Version};
+
+/// First line of every canonical form. Bumped whenever the format changes,
+/// so hashes of different versions never collide silently.
+pub const CANONICAL_VERSION: &[u8] = b"http-write-canonical/2";
+
+impl<'a, T, Q, V, B> Request<'a, T, Q, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    Q: Iterator<Item = Query<'a>>,
+    V: Version<'a>,
+    B: Body,
+{
+    /// Serializes the request into a stable canonical form, distinct from the
+    /// wire form.
+    ///
+    /// The same checks as [`Request::write_to`] run first. The output is a
+    /// series of `\n`-terminated lines:
+    ///
+    /// 1. [`CANONICAL_VERSION`],
+    /// 2. the method,
+    /// 3. the target as written on the request line: the path, after
+    ///    [`Request::normalize_path`] if it is enabled, behind the
+    ///    `scheme://authority` of [`Request::absolute`]; the `host:port` of
+    ///    [`Request::authority`]; or the `*` of [`Request::asterisk`],
+    /// 4. the query segments sorted bytewise and joined with `&`, or the
+    ///    [`Request::query_separator`],
+    /// 5. `HTTP/` and the version,
+    /// 6. one `name:value` line per header, where names are lowercased,
+    ///    values have surrounding whitespace trimmed and inner runs of
+    ///    spaces and tabs collapsed to one space, and headers are sorted by
+    ///    name while headers of the same name keep their relative order.
+    ///
+    /// Like `write_to`, this drains the header and query iterators.
+    pub fn canonical_bytes(&mut self) -> Result<Vec<u8>, RequestWriteError> {
+        let version = self.checked_version()?;
+        let path = self.checked_path()?;
+
+        let mut queries = Vec::new();
+        for q in &mut self.queries {
+            queries.push(q.checked().map_err(|_| RequestWriteError::InvalidQuery)?);
+        }
+        if !self.target.has_path() && !queries.is_empty() {
+            return Err(RequestWriteError::QueryNotAllowed);
+        }
+        queries.sort_unstable();
+
+        let mut headers = Vec::new();
+        for header in self.drain_headers() {
+            // nothing has been written yet, hence the zero offset
+            header.validate().map_err(|e| (0, e))?;
+            headers.push((header.name().to_ascii_lowercase(), header));
+        }
+        headers.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut out = Vec::new();
+        out.extend_from_slice(CANONICAL_VERSION);
+        out.push(b'\n');
+        out.extend_from_slice(self.method.as_str().as_bytes());
+        out.push(b'\n');
+        self.write_target(&mut out, path)?;
+        out.push(b'\n');
+        out.extend_from_slice(queries.join(self.query_separator.as_str()).as_bytes());
+        out.push(b'\n');
+        out.extend_from_slice(b"HTTP/");
+        out.extend_from_slice(version.as_bytes());
+        out.push(b'\n');
+
+        for (name, field) in &headers {
+            out.extend_from_slice(name.as_bytes());
+            out.push(b':');
+            push_collapsed(&mut out, &field.value_chunks().collect::<Vec<_>>().concat());
+            out.push(b'\n');
+        }
+        Ok(out)
+    }
+}
+
+fn push_collapsed(out: &mut Vec<u8>, value: &[u8]) {
+    let is_ws = |ch: &u8| matches!(ch, b' ' | b'\t');
+    let mut words = value.split(is_ws).filter(|w| !w.is_empty());
+    if let Some(first) = words.next() {
+        out.extend_from_slice(first);
+        for word in words {
+            out.push(b' ');
+            out.extend_from_slice(word);
+        }
+    }
+}
+
+#[test]
+fn canonical_ignores_order() {
+    let a = Request::get()
+        .v1_1()
+        .path("/x")
+        .query("b=2")
+        .query("a=1")
+        .header("Host", b"example.com")
+        .header("Accept", b"  text/html,\t  */* ")
+        .canonical_bytes()
+        .unwrap();
+
+    let b = Request::get()
+        .v1_1()
+        .path("/x")
+        .query("a=1")
+        .query("b=2")
+        .header("accept", b"text/html, */*")
+        .header("HOST", b"example.com")
+        .canonical_bytes()
+        .unwrap();
+
+    assert_eq!(a, b);
+}
+
+#[test]
+fn canonical_golden() {
+    let canonical = Request::post()
+        .v1_1()
+        .path("/a/./b/../c")
+        .normalize_path()
+        .query("z=1")
+        .query("y=2")
+        .header("X-B", b"2")
+        .header("x-a", b"1")
+        .header("X-B", b"1")
+        .canonical_bytes()
+        .unwrap();
+
+    assert_eq!(
+        canonical,
+        b"http-write-canonical/2\nPOST\n/a/c\ny=2&z=1\nHTTP/1.1\nx-a:1\nx-b:2\nx-b:1\n"
+    );
+}
+
+#[test]
+fn canonical_target_forms() {
+    let origin = Request::get().v1_1().path("/x").query("a=1").canonical_bytes().unwrap();
+    assert_eq!(origin, b"http-write-canonical/2\nGET\n/x\na=1\nHTTP/1.1\n");
+
+    let absolute = Request::get()
+        .v1_1()
+        .absolute("http", "example.com:8080")
+        .path("/x")
+        .query("a=1")
+        .canonical_bytes()
+        .unwrap();
+    assert_eq!(absolute, b"http-write-canonical/2\nGET\nhttp://example.com:8080/x\na=1\nHTTP/1.1\n");
+
+    let authority = Request::connect().v1_1().authority("example.com", 443).canonical_bytes().unwrap();
+    assert_eq!(authority, b"http-write-canonical/2\nCONNECT\nexample.com:443\n\nHTTP/1.1\n");
+
+    let asterisk = Request::options().v1_1().asterisk().canonical_bytes().unwrap();
+    assert_eq!(asterisk, b"http-write-canonical/2\nOPTIONS\n*\n\nHTTP/1.1\n");
+
+    let err = Request::options().v1_1().asterisk().query("a=1").canonical_bytes();
+    assert!(matches!(err, Err(RequestWriteError::QueryNotAllowed)));
+}
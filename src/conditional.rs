@@ -0,0 +1,144 @@
+use crate::consts::names;
+use crate::field::Inline;
+use crate::{ETag, EmptyHeaders, Field, Response, version};
+use core::iter::Chain;
+use core::option;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The validators of a resource that conditional requests are checked
+/// against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ResourceMeta<'a> {
+    pub etag: Option<ETag<'a>>,
+    pub last_modified: Option<SystemTime>,
+}
+
+/// The `ETag` and `Last-Modified` headers attached by
+/// [`Response::conditional`].
+pub type Validators<'a> =
+    Chain<Chain<EmptyHeaders<'a>, option::IntoIter<Field<'a>>>, option::IntoIter<Field<'a>>>;
+
+/// What to answer a conditional `GET` or `HEAD` with.
+pub enum ConditionalOutcome<'a> {
+    /// A `304 Not Modified` that is ready to be written.
+    NotModified(Response<'a, Validators<'a>, version::UNSPECIFIED>),
+    /// The request should be served; the `200 OK` only needs its body and
+    /// any other headers.
+    Proceed(Response<'a, Validators<'a>, version::UNSPECIFIED>),
+}
+
+impl<'a> Response<'a, Validators<'a>, version::UNSPECIFIED> {
+    /// Evaluates `If-None-Match` and `If-Modified-Since` against `resource`.
+    ///
+    /// As in RFC 9110, `If-Modified-Since` is ignored when `If-None-Match` is
+    /// present. Either way the outcome carries the resource's validators.
+    pub fn conditional(
+        resource: &ResourceMeta<'a>,
+        if_none_match: Option<&str>,
+        if_modified_since: Option<SystemTime>,
+    ) -> ConditionalOutcome<'a> {
+        let not_modified = match (if_none_match, if_modified_since) {
+            (Some(value), _) => crate::etag::none_match_list(value, resource.etag.as_ref()),
+            (None, Some(since)) => resource
+                .last_modified
+                .is_some_and(|modified| secs(modified) <= secs(since)),
+            (None, None) => false,
+        };
+
+        let code = match not_modified {
+            true => http::StatusCode::NOT_MODIFIED,
+            false => http::StatusCode::OK,
+        };
+
+        let etag = resource.etag.map(ETag::field);
+        let last_modified = resource
+            .last_modified
+            .and_then(crate::date::format)
+            .map(|date| Field::inline(names::LAST_MODIFIED, Inline::new(&date)));
+
+        let response = Response {
+            code,
+            reason: None,
+            head_request: false,
+            unique_headers: &[],
+            version: version::UNSPECIFIED,
+            headers: EmptyHeaders::new().chain(etag).chain(last_modified),
+            body: crate::NoBody,
+        };
+
+        match not_modified {
+            true => ConditionalOutcome::NotModified(response),
+            false => ConditionalOutcome::Proceed(response),
+        }
+    }
+}
+
+/// Whole seconds since the epoch, since HTTP dates carry no fractions.
+fn secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+fn written(outcome: ConditionalOutcome<'_>) -> (bool, String) {
+    let (not_modified, response) = match outcome {
+        ConditionalOutcome::NotModified(r) => (true, r),
+        ConditionalOutcome::Proceed(r) => (false, r),
+    };
+    let mut buf = Vec::new();
+    response.v1_1().write_to(&mut buf).unwrap();
+    (not_modified, String::from_utf8(buf).unwrap())
+}
+
+#[cfg(test)]
+const MODIFIED: u64 = 784111777;
+
+#[cfg(test)]
+fn meta() -> ResourceMeta<'static> {
+    ResourceMeta {
+        etag: Some(ETag::weak("v1").unwrap()),
+        last_modified: Some(UNIX_EPOCH + std::time::Duration::from_millis(MODIFIED * 1000 + 750)),
+    }
+}
+
+#[test]
+fn match_by_etag() {
+    let outcome = Response::conditional(&meta(), Some("\"v0\", \"v1\""), None);
+    assert_eq!(
+        written(outcome),
+        (
+            true,
+            "HTTP/1.1 304 Not Modified\r\nETag: W/\"v1\"\r\nLast-Modified: Sun, 06 Nov 1994 08:49:37 GMT\r\n\r\n"
+                .to_string()
+        )
+    );
+
+    let outcome = Response::conditional(&ResourceMeta::default(), Some("*"), None);
+    assert!(written(outcome).0);
+}
+
+#[test]
+fn match_by_date() {
+    let since = UNIX_EPOCH + std::time::Duration::from_secs(MODIFIED);
+    assert!(written(Response::conditional(&meta(), None, Some(since))).0);
+
+    let earlier = since - std::time::Duration::from_secs(1);
+    assert!(!written(Response::conditional(&meta(), None, Some(earlier))).0);
+}
+
+#[test]
+fn etag_takes_precedence() {
+    let since = UNIX_EPOCH + std::time::Duration::from_secs(MODIFIED);
+    assert!(!written(Response::conditional(&meta(), Some("\"v0\""), Some(since))).0);
+}
+
+#[test]
+fn no_match() {
+    assert_eq!(
+        written(Response::conditional(&meta(), None, None)),
+        (
+            false,
+            "HTTP/1.1 200 OK\r\nETag: W/\"v1\"\r\nLast-Modified: Sun, 06 Nov 1994 08:49:37 GMT\r\n\r\n"
+                .to_string()
+        )
+    );
+}
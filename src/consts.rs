@@ -0,0 +1,172 @@
+//! Common headers, validated at compile time.
+//!
+//! ```
+//! use http_write::{Request, consts};
+//!
+//! let mut buf = Vec::new();
+//! Request::get()
+//!     .v1_1()
+//!     .headers([consts::ACCEPT_ANY, consts::CONNECTION_CLOSE])
+//!     .write_to(&mut buf)
+//!     .unwrap();
+//! ```
+
+use crate::ValidatedHeader;
+
+/// Header names, for composing custom values.
+pub mod names {
+    pub const ACCEPT: &str = "Accept";
+    pub const ACCEPT_ENCODING: &str = "Accept-Encoding";
+    pub const ACCEPT_LANGUAGE: &str = "Accept-Language";
+    pub const ACCEPT_RANGES: &str = "Accept-Ranges";
+    pub const ACCESS_CONTROL_ALLOW_ORIGIN: &str = "Access-Control-Allow-Origin";
+    pub const ACCESS_CONTROL_REQUEST_HEADERS: &str = "Access-Control-Request-Headers";
+    pub const ACCESS_CONTROL_REQUEST_METHOD: &str = "Access-Control-Request-Method";
+    pub const AGE: &str = "Age";
+    pub const AUTHORIZATION: &str = "Authorization";
+    pub const CACHE_CONTROL: &str = "Cache-Control";
+    pub const CONNECTION: &str = "Connection";
+    pub const CONTENT_ENCODING: &str = "Content-Encoding";
+    pub const CONTENT_LENGTH: &str = "Content-Length";
+    pub const CONTENT_RANGE: &str = "Content-Range";
+    pub const CONTENT_SECURITY_POLICY: &str = "Content-Security-Policy";
+    pub const CONTENT_SECURITY_POLICY_REPORT_ONLY: &str = "Content-Security-Policy-Report-Only";
+    pub const CONTENT_TYPE: &str = "Content-Type";
+    pub const COOKIE: &str = "Cookie";
+    pub const DATE: &str = "Date";
+    pub const DEPRECATION: &str = "Deprecation";
+    pub const ETAG: &str = "ETag";
+    pub const EXPECT: &str = "Expect";
+    pub const EXPIRES: &str = "Expires";
+    pub const HOST: &str = "Host";
+    pub const IF_MATCH: &str = "If-Match";
+    pub const IF_MODIFIED_SINCE: &str = "If-Modified-Since";
+    pub const IF_NONE_MATCH: &str = "If-None-Match";
+    pub const IF_UNMODIFIED_SINCE: &str = "If-Unmodified-Since";
+    pub const LAST_MODIFIED: &str = "Last-Modified";
+    pub const LINK: &str = "Link";
+    pub const LOCATION: &str = "Location";
+    pub const ORIGIN: &str = "Origin";
+    pub const PRAGMA: &str = "Pragma";
+    pub const PRIORITY: &str = "Priority";
+    pub const REFERER: &str = "Referer";
+    pub const SERVER: &str = "Server";
+    pub const SERVER_TIMING: &str = "Server-Timing";
+    pub const SET_COOKIE: &str = "Set-Cookie";
+    pub const SUNSET: &str = "Sunset";
+    pub const STRICT_TRANSPORT_SECURITY: &str = "Strict-Transport-Security";
+    pub const TRACEPARENT: &str = "traceparent";
+    pub const TRACESTATE: &str = "tracestate";
+    pub const TRANSFER_ENCODING: &str = "Transfer-Encoding";
+    pub const UPGRADE: &str = "Upgrade";
+    pub const USER_AGENT: &str = "User-Agent";
+    pub const VARY: &str = "Vary";
+    pub const X_CONTENT_TYPE_OPTIONS: &str = "X-Content-Type-Options";
+    pub const X_FRAME_OPTIONS: &str = "X-Frame-Options";
+}
+
+/// Headers a message may hold at most once, for
+/// [`Request::unique_headers`](crate::Request::unique_headers).
+pub const UNIQUE_HEADERS: &[&str] = &[names::CONTENT_LENGTH, names::CONTENT_TYPE, names::HOST];
+
+const fn validated(name: &'static str, value: &'static str) -> ValidatedHeader<'static> {
+    match ValidatedHeader::new(name, value.as_bytes()) {
+        Ok(h) => h,
+        Err(_) => panic!("invalid header constant"),
+    }
+}
+
+pub const ACCEPT_ANY: ValidatedHeader<'static> = validated(names::ACCEPT, "*/*");
+pub const ACCEPT_JSON: ValidatedHeader<'static> = validated(names::ACCEPT, "application/json");
+pub const ACCEPT_ENCODING_IDENTITY: ValidatedHeader<'static> =
+    validated(names::ACCEPT_ENCODING, "identity");
+pub const ACCEPT_ENCODING_GZIP: ValidatedHeader<'static> =
+    validated(names::ACCEPT_ENCODING, "gzip");
+pub const ACCEPT_RANGES_BYTES: ValidatedHeader<'static> = validated(names::ACCEPT_RANGES, "bytes");
+pub const ACCEPT_RANGES_NONE: ValidatedHeader<'static> = validated(names::ACCEPT_RANGES, "none");
+pub const ACCESS_CONTROL_ALLOW_ORIGIN_ANY: ValidatedHeader<'static> =
+    validated(names::ACCESS_CONTROL_ALLOW_ORIGIN, "*");
+pub const CACHE_CONTROL_NO_CACHE: ValidatedHeader<'static> =
+    validated(names::CACHE_CONTROL, "no-cache");
+pub const CACHE_CONTROL_NO_STORE: ValidatedHeader<'static> =
+    validated(names::CACHE_CONTROL, "no-store");
+pub const CONNECTION_CLOSE: ValidatedHeader<'static> = validated(names::CONNECTION, "close");
+pub const CONNECTION_KEEP_ALIVE: ValidatedHeader<'static> =
+    validated(names::CONNECTION, "keep-alive");
+pub const CONNECTION_UPGRADE: ValidatedHeader<'static> = validated(names::CONNECTION, "Upgrade");
+pub const CONTENT_ENCODING_GZIP: ValidatedHeader<'static> =
+    validated(names::CONTENT_ENCODING, "gzip");
+pub const CONTENT_LENGTH_ZERO: ValidatedHeader<'static> = validated(names::CONTENT_LENGTH, "0");
+pub const CONTENT_TYPE_FORM: ValidatedHeader<'static> =
+    validated(names::CONTENT_TYPE, "application/x-www-form-urlencoded");
+pub const CONTENT_TYPE_HTML: ValidatedHeader<'static> =
+    validated(names::CONTENT_TYPE, "text/html; charset=utf-8");
+pub const CONTENT_TYPE_JSON: ValidatedHeader<'static> =
+    validated(names::CONTENT_TYPE, "application/json");
+pub const CONTENT_TYPE_OCTET_STREAM: ValidatedHeader<'static> =
+    validated(names::CONTENT_TYPE, "application/octet-stream");
+pub const CONTENT_TYPE_TEXT: ValidatedHeader<'static> =
+    validated(names::CONTENT_TYPE, "text/plain; charset=utf-8");
+pub const EXPECT_CONTINUE: ValidatedHeader<'static> = validated(names::EXPECT, "100-continue");
+pub const PRAGMA_NO_CACHE: ValidatedHeader<'static> = validated(names::PRAGMA, "no-cache");
+pub const STRICT_TRANSPORT_SECURITY_YEAR: ValidatedHeader<'static> =
+    validated(names::STRICT_TRANSPORT_SECURITY, "max-age=31536000; includeSubDomains");
+pub const TRANSFER_ENCODING_CHUNKED: ValidatedHeader<'static> =
+    validated(names::TRANSFER_ENCODING, "chunked");
+pub const UPGRADE_WEBSOCKET: ValidatedHeader<'static> = validated(names::UPGRADE, "websocket");
+pub const VARY_ACCEPT_ENCODING: ValidatedHeader<'static> =
+    validated(names::VARY, "Accept-Encoding");
+pub const X_CONTENT_TYPE_OPTIONS_NOSNIFF: ValidatedHeader<'static> =
+    validated(names::X_CONTENT_TYPE_OPTIONS, "nosniff");
+pub const X_FRAME_OPTIONS_DENY: ValidatedHeader<'static> = validated(names::X_FRAME_OPTIONS, "DENY");
+
+// Build-time check that user code can construct these in a const, too.
+const _: ValidatedHeader<'static> = match ValidatedHeader::new("X-Const", b"yes") {
+    Ok(h) => h,
+    Err(_) => panic!(),
+};
+
+#[test]
+fn constants_serialize() {
+    let cases = [
+        (ACCEPT_ANY, "Accept: */*"),
+        (ACCEPT_JSON, "Accept: application/json"),
+        (ACCEPT_ENCODING_IDENTITY, "Accept-Encoding: identity"),
+        (ACCEPT_ENCODING_GZIP, "Accept-Encoding: gzip"),
+        (ACCEPT_RANGES_BYTES, "Accept-Ranges: bytes"),
+        (ACCEPT_RANGES_NONE, "Accept-Ranges: none"),
+        (ACCESS_CONTROL_ALLOW_ORIGIN_ANY, "Access-Control-Allow-Origin: *"),
+        (CACHE_CONTROL_NO_CACHE, "Cache-Control: no-cache"),
+        (CACHE_CONTROL_NO_STORE, "Cache-Control: no-store"),
+        (CONNECTION_CLOSE, "Connection: close"),
+        (CONNECTION_KEEP_ALIVE, "Connection: keep-alive"),
+        (CONNECTION_UPGRADE, "Connection: Upgrade"),
+        (CONTENT_ENCODING_GZIP, "Content-Encoding: gzip"),
+        (CONTENT_LENGTH_ZERO, "Content-Length: 0"),
+        (CONTENT_TYPE_FORM, "Content-Type: application/x-www-form-urlencoded"),
+        (CONTENT_TYPE_HTML, "Content-Type: text/html; charset=utf-8"),
+        (CONTENT_TYPE_JSON, "Content-Type: application/json"),
+        (CONTENT_TYPE_OCTET_STREAM, "Content-Type: application/octet-stream"),
+        (CONTENT_TYPE_TEXT, "Content-Type: text/plain; charset=utf-8"),
+        (EXPECT_CONTINUE, "Expect: 100-continue"),
+        (PRAGMA_NO_CACHE, "Pragma: no-cache"),
+        (
+            STRICT_TRANSPORT_SECURITY_YEAR,
+            "Strict-Transport-Security: max-age=31536000; includeSubDomains",
+        ),
+        (TRANSFER_ENCODING_CHUNKED, "Transfer-Encoding: chunked"),
+        (UPGRADE_WEBSOCKET, "Upgrade: websocket"),
+        (VARY_ACCEPT_ENCODING, "Vary: Accept-Encoding"),
+        (X_CONTENT_TYPE_OPTIONS_NOSNIFF, "X-Content-Type-Options: nosniff"),
+        (X_FRAME_OPTIONS_DENY, "X-Frame-Options: DENY"),
+    ];
+
+    for (header, line) in cases {
+        let field = crate::Field::from(header);
+        field.validate().unwrap();
+
+        let mut buf = Vec::new();
+        unsafe { field.write_unchecked(&mut buf).unwrap() };
+        assert_eq!(buf, format!("{line}\r\n").as_bytes());
+    }
+}
@@ -0,0 +1,243 @@
+//! Cookie headers (RFC 6265).
+
+use crate::Field;
+use crate::consts::names;
+use crate::field::decimal;
+use core::time::Duration;
+
+/// The pairs of every [`Request::cookie`](crate::Request::cookie) and
+/// [`Request::cookies`](crate::Request::cookies) call, joined with `; ` as
+/// they are added, since a request carries a single `Cookie` header.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct CookieJar {
+    value: Vec<u8>,
+    /// Where the value breaks the grammar, once an invalid pair was added.
+    invalid: Option<usize>,
+}
+
+impl CookieJar {
+    /// Appends `name=value`, or marks the jar invalid at its first bad byte.
+    pub(crate) fn push(&mut self, name: &str, value: &str) {
+        if self.invalid.is_some() {
+            return;
+        }
+        let offset = self.separator_len();
+        match check_pair(name, value) {
+            Ok(()) => {
+                if offset > 0 {
+                    self.value.extend_from_slice(b"; ");
+                }
+                self.value.extend_from_slice(name.as_bytes());
+                self.value.push(b'=');
+                self.value.extend_from_slice(value.as_bytes());
+            }
+            Err(pos) => self.invalid = Some(self.value.len() + offset + pos),
+        }
+    }
+
+    fn separator_len(&self) -> usize {
+        if self.value.is_empty() { 0 } else { 2 }
+    }
+
+    /// The `Cookie` header, unless no cookie was added, leaving the jar
+    /// empty.
+    pub(crate) fn take_field(&mut self) -> Option<Field<'static>> {
+        let jar = core::mem::take(self);
+        match jar.invalid {
+            Some(pos) => Some(Field::invalid(names::COOKIE, pos)),
+            None if jar.value.is_empty() => None,
+            None => Some(Field::owned(names::COOKIE, jar.value)),
+        }
+    }
+}
+
+/// The position in `name=value` of the first byte that breaks the grammar:
+/// the name is a token and the value is `cookie-octet`s, optionally quoted.
+pub(crate) fn check_pair(name: &str, value: &str) -> Result<(), usize> {
+    if let Some(pos) = crate::invalid_token_pos(name.as_bytes()) {
+        return Err(pos);
+    }
+    let offset = name.len() + 1;
+    let bytes = value.as_bytes();
+    let inner = match bytes {
+        [b'"', inner @ .., b'"'] => inner,
+        _ => bytes,
+    };
+    let quote = usize::from(inner.len() != bytes.len());
+    match inner.iter().position(|&ch| !is_cookie_octet(ch)) {
+        Some(pos) => Err(offset + quote + pos),
+        None => Ok(()),
+    }
+}
+
+/// US-ASCII without controls, whitespace, `"`, `,`, `;` and `\`.
+fn is_cookie_octet(ch: u8) -> bool {
+    matches!(ch, 0x21 | 0x23..=0x2b | 0x2d..=0x3a | 0x3c..=0x5b | 0x5d..=0x7e)
+}
+
+/// A `Set-Cookie` header, written by
+/// [`Response::set_cookie`](crate::Response::set_cookie).
+///
+/// Attributes are written in the order of RFC 6265, section 4.1.1, with
+/// `SameSite` last, whatever order they were set in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SetCookie<'a> {
+    name: &'a str,
+    value: &'a str,
+    max_age: Option<Duration>,
+    domain: Option<&'a str>,
+    path: Option<&'a str>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+/// The `SameSite` attribute of a [`SetCookie`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    /// Sent on cross-site requests too, which browsers only allow on a
+    /// [`SetCookie::secure`] cookie.
+    None,
+}
+
+impl SameSite {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Strict => "Strict",
+            Self::Lax => "Lax",
+            Self::None => "None",
+        }
+    }
+}
+
+impl<'a> SetCookie<'a> {
+    pub fn new(name: &'a str, value: &'a str) -> Self {
+        Self {
+            name,
+            value,
+            max_age: None,
+            domain: None,
+            path: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    /// Written in whole seconds; zero deletes the cookie.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn domain(mut self, domain: &'a str) -> Self {
+        self.domain = Some(domain);
+        self
+    }
+
+    pub fn path(mut self, path: &'a str) -> Self {
+        self.path = Some(path);
+        self
+    }
+
+    pub fn secure(mut self) -> Self {
+        self.secure = true;
+        self
+    }
+
+    pub fn http_only(mut self) -> Self {
+        self.http_only = true;
+        self
+    }
+
+    pub fn same_site(mut self, same_site: SameSite) -> Self {
+        self.same_site = Some(same_site);
+        self
+    }
+
+    /// The position in the value of the first byte that cannot be written:
+    /// a bad name or value, a control or `;` in `Domain` or `Path`, or
+    /// `SameSite=None` on a cookie that is not `Secure`.
+    pub(crate) fn check(&self) -> Result<(), usize> {
+        check_pair(self.name, self.value)?;
+        let mut offset = self.name.len() + 1 + self.value.len();
+        offset += self.max_age.map_or(0, |age| 10 + decimal(age.as_secs().into()).count());
+        for (attr, value) in [("; Domain=", self.domain), ("; Path=", self.path)] {
+            let Some(value) = value else { continue };
+            offset += attr.len();
+            if let Some(pos) = value.bytes().position(|ch| ch.is_ascii_control() || ch == b';') {
+                return Err(offset + pos);
+            }
+            offset += value.len();
+        }
+        offset += if self.secure { 8 } else { 0 } + if self.http_only { 10 } else { 0 };
+        match self.same_site {
+            Some(SameSite::None) if !self.secure => Err(offset),
+            _ => Ok(()),
+        }
+    }
+
+    /// The header value as the chunks it is written in.
+    pub(crate) fn chunks(&self) -> impl Iterator<Item = &'a [u8]> {
+        let max_age = self
+            .max_age
+            .map(|age| [b"; Max-Age=".as_slice()].into_iter().chain(decimal(age.as_secs().into())));
+        let attr = |name: &'static str, value: Option<&'a str>| value.map(|v| [name.as_bytes(), v.as_bytes()]);
+        let flag = |name: &'static str, set: bool| if set { name.as_bytes() } else { b"" };
+        let same_site = attr("; SameSite=", self.same_site.map(SameSite::as_str));
+        [self.name.as_bytes(), b"=", self.value.as_bytes()]
+            .into_iter()
+            .chain(max_age.into_iter().flatten())
+            .chain(attr("; Domain=", self.domain).into_iter().flatten())
+            .chain(attr("; Path=", self.path).into_iter().flatten())
+            .chain([flag("; Secure", self.secure), flag("; HttpOnly", self.http_only)])
+            .chain(same_site.into_iter().flatten())
+    }
+}
+
+#[test]
+fn pair_validation() {
+    assert_eq!(check_pair("SID", "31d4d96e407aad42"), Ok(()));
+    assert_eq!(check_pair("lang", "\"en-US\""), Ok(()));
+    assert_eq!(check_pair("empty", ""), Ok(()));
+    assert_eq!(check_pair("", "x"), Err(0));
+    assert_eq!(check_pair("a b", "x"), Err(1));
+    assert_eq!(check_pair("a=", "x"), Err(1));
+    assert_eq!(check_pair("a", "x y"), Err(3));
+    assert_eq!(check_pair("a", "x;y"), Err(3));
+    assert_eq!(check_pair("a", "x,y"), Err(3));
+    assert_eq!(check_pair("a", "\"x\"y\""), Err(4));
+    assert_eq!(check_pair("a", "\"x"), Err(2));
+    assert_eq!(check_pair("a", "x\r\n"), Err(3));
+}
+
+#[cfg(test)]
+fn value(cookie: &SetCookie<'_>) -> String {
+    String::from_utf8(cookie.chunks().collect::<Vec<_>>().concat()).unwrap()
+}
+
+#[test]
+fn set_cookie_attributes() {
+    assert_eq!(value(&SetCookie::new("id", "a3fWa")), "id=a3fWa");
+    let cookie = SetCookie::new("id", "a3fWa")
+        .same_site(SameSite::Lax)
+        .http_only()
+        .secure()
+        .path("/app")
+        .domain("example.com")
+        .max_age(Duration::from_secs(2_592_000));
+    assert_eq!(
+        value(&cookie),
+        "id=a3fWa; Max-Age=2592000; Domain=example.com; Path=/app; Secure; HttpOnly; SameSite=Lax"
+    );
+    assert_eq!(cookie.check(), Ok(()));
+    assert_eq!(value(&SetCookie::new("gone", "").max_age(Duration::ZERO)), "gone=; Max-Age=0");
+
+    assert_eq!(SetCookie::new("a b", "x").check(), Err(1));
+    assert_eq!(SetCookie::new("a", "x").max_age(Duration::from_secs(60)).path("/x;y").check(), Err(24));
+    assert_eq!(SetCookie::new("a", "x").domain("ex\nample").check(), Err(14));
+    assert_eq!(SetCookie::new("a", "x").http_only().same_site(SameSite::None).check(), Err(13));
+    assert_eq!(SetCookie::new("a", "x").secure().same_site(SameSite::None).check(), Ok(()));
+}
@@ -0,0 +1,63 @@
+use std::io::{self, IoSlice, Write};
+
+/// Discards everything written to it and counts the bytes, to measure a
+/// message without buffering it, even one whose headers are not `Clone`
+/// for [`Request::estimate_len`](crate::Request::estimate_len).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CountingWriter {
+    count: usize,
+}
+
+impl CountingWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The bytes written so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.count += buf.len();
+        Ok(buf.len())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let n = bufs.iter().map(|buf| buf.len()).sum();
+        self.count += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn counting_writer() {
+    let req = || crate::Request::post().path("/items").query("a=1").v1_1().header("X-Id", b"7").body(b"hello");
+    let mut buf = Vec::new();
+    req().write_to(&mut buf).unwrap();
+
+    let mut counter = CountingWriter::new();
+    let len = req().write_to(&mut counter).unwrap();
+    assert_eq!(len, buf.len());
+    assert_eq!(counter.count(), buf.len());
+
+    let mut counter = CountingWriter::new();
+    let len = req().write_to_vectored(&mut counter).unwrap();
+    assert_eq!((len, counter.count()), (buf.len(), buf.len()));
+}
+
+#[test]
+fn counts_headers_that_are_not_clone() {
+    let mut ids = 0..3;
+    // the closure borrows `ids` mutably, so the headers cannot be cloned
+    let headers = core::iter::from_fn(|| ids.next().map(|_| crate::Header { name: "X-Id", value: b"7" }));
+    let mut counter = CountingWriter::new();
+    let len = crate::Request::get().path("/").v1_1().headers(headers).write_to(&mut counter).unwrap();
+    assert_eq!((len, counter.count()), (16 + 3 * 9 + 2, 16 + 3 * 9 + 2));
+}
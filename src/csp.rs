@@ -0,0 +1,308 @@
+//! `Content-Security-Policy` values.
+
+/// A source expression in a CSP directive.
+///
+/// Keywords, nonces and hashes are quoted when the policy is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source<'a> {
+    /// `'self'`
+    SelfOrigin,
+    /// `'none'`, which cannot be combined with other sources.
+    None,
+    /// `'unsafe-inline'`
+    UnsafeInline,
+    /// `'unsafe-eval'`
+    UnsafeEval,
+    /// `'strict-dynamic'`
+    StrictDynamic,
+    /// `'nonce-…'` with the base64 nonce.
+    Nonce(&'a str),
+    /// `'sha256-…'` with the base64 digest.
+    Sha256(&'a str),
+    /// `'sha384-…'` with the base64 digest.
+    Sha384(&'a str),
+    /// `'sha512-…'` with the base64 digest.
+    Sha512(&'a str),
+    /// A scheme such as `https` or `data`, written with its `:`.
+    Scheme(&'a str),
+    /// A host source such as `*.example.com` or `https://cdn.example.com:443/js/`.
+    Host(&'a str),
+}
+
+impl<'a> Source<'a> {
+    /// `'nonce-…'` for a base64 nonce, as generated per response.
+    pub fn nonce(value: &'a str) -> Self {
+        Self::Nonce(value)
+    }
+
+    /// The source as the chunks it is written in.
+    pub(crate) fn chunks(&self) -> [&'a [u8]; 3] {
+        let quoted = |prefix: &'static [u8], value: &'a str| [prefix, value.as_bytes(), b"'"];
+        match *self {
+            Self::SelfOrigin => [b"'self'", b"", b""],
+            Self::None => [b"'none'", b"", b""],
+            Self::UnsafeInline => [b"'unsafe-inline'", b"", b""],
+            Self::UnsafeEval => [b"'unsafe-eval'", b"", b""],
+            Self::StrictDynamic => [b"'strict-dynamic'", b"", b""],
+            Self::Nonce(v) => quoted(b"'nonce-", v),
+            Self::Sha256(v) => quoted(b"'sha256-", v),
+            Self::Sha384(v) => quoted(b"'sha384-", v),
+            Self::Sha512(v) => quoted(b"'sha512-", v),
+            Self::Scheme(s) => [s.as_bytes(), b":", b""],
+            Self::Host(h) => [h.as_bytes(), b"", b""],
+        }
+    }
+
+    fn check(&self) -> bool {
+        match *self {
+            Self::Nonce(v) | Self::Sha256(v) | Self::Sha384(v) | Self::Sha512(v) => is_base64(v),
+            Self::Scheme(s) => is_scheme(s),
+            Self::Host(h) => is_host_source(h),
+            _ => true,
+        }
+    }
+}
+
+/// A policy rejected by [`Csp::check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CspError {
+    /// The policy has no directives.
+    Empty,
+    /// The directive at this index is not a valid directive name.
+    InvalidDirectiveName(usize),
+    /// A directive of this name was already added; the index is of the
+    /// second one.
+    DuplicateDirective(usize),
+    /// The directive at this index combines `'none'` with other sources.
+    NoneWithOthers(usize),
+    /// The source at `source` of the directive at `directive` is malformed.
+    InvalidSource { directive: usize, source: usize },
+}
+
+impl core::fmt::Display for CspError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("policy has no directives"),
+            Self::InvalidDirectiveName(i) => write!(f, "invalid name for directive {i}"),
+            Self::DuplicateDirective(i) => write!(f, "directive {i} repeats an earlier one"),
+            Self::NoneWithOthers(i) => write!(f, "directive {i} combines 'none' with other sources"),
+            Self::InvalidSource { directive, source } => write!(f, "invalid source {source} in directive {directive}"),
+        }
+    }
+}
+
+impl std::error::Error for CspError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Directive<'a> {
+    name: &'a str,
+    sources: Vec<Source<'a>>,
+    /// `report-to` names a reporting group instead of sources.
+    group: Option<&'a str>,
+}
+
+/// A `Content-Security-Policy`, built directive by directive and written
+/// by [`Response::content_security_policy`](crate::Response::content_security_policy).
+///
+/// Mistakes are recorded rather than panicking: [`Csp::check`] reports the
+/// first, and writing a policy that fails it fails the header.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Csp<'a> {
+    directives: Vec<Directive<'a>>,
+}
+
+impl<'a> Csp<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn default_src(self, sources: &[Source<'a>]) -> Self {
+        self.directive("default-src", sources)
+    }
+
+    pub fn script_src(self, sources: &[Source<'a>]) -> Self {
+        self.directive("script-src", sources)
+    }
+
+    pub fn style_src(self, sources: &[Source<'a>]) -> Self {
+        self.directive("style-src", sources)
+    }
+
+    pub fn img_src(self, sources: &[Source<'a>]) -> Self {
+        self.directive("img-src", sources)
+    }
+
+    pub fn connect_src(self, sources: &[Source<'a>]) -> Self {
+        self.directive("connect-src", sources)
+    }
+
+    pub fn frame_ancestors(self, sources: &[Source<'a>]) -> Self {
+        self.directive("frame-ancestors", sources)
+    }
+
+    /// `report-to` with the name of a `Reporting-Endpoints` group.
+    pub fn report_to(mut self, group: &'a str) -> Self {
+        self.directives.push(Directive {
+            name: "report-to",
+            sources: Vec::new(),
+            group: Some(group),
+        });
+        self
+    }
+
+    /// Any other directive, such as `upgrade-insecure-requests` with no
+    /// sources at all.
+    pub fn directive(mut self, name: &'a str, sources: &[Source<'a>]) -> Self {
+        self.directives.push(Directive {
+            name,
+            sources: sources.to_vec(),
+            group: None,
+        });
+        self
+    }
+
+    /// Finds the first mistake in the policy.
+    pub fn check(&self) -> Result<(), CspError> {
+        if self.directives.is_empty() {
+            return Err(CspError::Empty);
+        }
+        for (i, directive) in self.directives.iter().enumerate() {
+            let name = directive.name;
+            if name.is_empty() || !name.bytes().all(|ch| ch.is_ascii_alphanumeric() || ch == b'-') {
+                return Err(CspError::InvalidDirectiveName(i));
+            }
+            if self.directives[..i].iter().any(|d| d.name.eq_ignore_ascii_case(name)) {
+                return Err(CspError::DuplicateDirective(i));
+            }
+            if directive.group.is_some_and(|g| crate::invalid_token_pos(g.as_bytes()).is_some()) {
+                return Err(CspError::InvalidSource { directive: i, source: 0 });
+            }
+            if directive.sources.len() > 1 && directive.sources.contains(&Source::None) {
+                return Err(CspError::NoneWithOthers(i));
+            }
+            if let Some(source) = directive.sources.iter().position(|s| !s.check()) {
+                return Err(CspError::InvalidSource { directive: i, source });
+            }
+        }
+        Ok(())
+    }
+
+    /// The policy as the chunks it is written in.
+    pub(crate) fn chunks(&self) -> impl Iterator<Item = &[u8]> {
+        self.directives.iter().enumerate().flat_map(|(i, directive)| {
+            let sep: &[u8] = if i > 0 { b"; " } else { b"" };
+            let group = directive.group.map(|g| [b" ".as_slice(), g.as_bytes()]);
+            [sep, directive.name.as_bytes()]
+                .into_iter()
+                .chain(group.into_iter().flatten())
+                .chain(directive.sources.iter().flat_map(|source| {
+                    let [a, b, c] = source.chunks();
+                    [b" ", a, b, c]
+                }))
+        })
+    }
+}
+
+fn is_base64(v: &str) -> bool {
+    let body = v.trim_end_matches('=');
+    !body.is_empty()
+        && v.len() - body.len() <= 2
+        && body.bytes().all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, b'+' | b'/' | b'-' | b'_'))
+}
+
+fn is_scheme(s: &str) -> bool {
+    let mut bytes = s.bytes();
+    bytes.next().is_some_and(|ch| ch.is_ascii_alphabetic())
+        && bytes.all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, b'+' | b'-' | b'.'))
+}
+
+/// `[ scheme "://" ] host [ ":" port ] [ path ]`, loosely: the parts are
+/// checked for the characters they may hold, not for their structure.
+fn is_host_source(h: &str) -> bool {
+    let rest = match h.split_once("://") {
+        Some((scheme, rest)) if is_scheme(scheme) => rest,
+        Some(_) => return false,
+        None => h,
+    };
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let (host, port) = match authority.split_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (authority, None),
+    };
+
+    let host = host.strip_prefix("*.").unwrap_or(host);
+    let host_ok = host == "*"
+        || (!host.is_empty()
+            && host.split('.').all(|label| {
+                !label.is_empty() && label.bytes().all(|ch| ch.is_ascii_alphanumeric() || ch == b'-')
+            }));
+    let port_ok = port.is_none_or(|p| p == "*" || (!p.is_empty() && p.bytes().all(|ch| ch.is_ascii_digit())));
+    host_ok && port_ok && crate::path::validate_encoded(path.as_bytes(), b"/").is_ok() && !path.contains([';', ','])
+}
+
+#[cfg(test)]
+fn policy(csp: &Csp<'_>) -> String {
+    String::from_utf8(csp.chunks().collect::<Vec<_>>().concat()).unwrap()
+}
+
+#[test]
+fn realistic_policy() {
+    let csp = Csp::new()
+        .default_src(&[Source::SelfOrigin])
+        .script_src(&[Source::SelfOrigin, Source::nonce("rAnd0m+/4w=="), Source::StrictDynamic])
+        .style_src(&[Source::SelfOrigin, Source::Host("https://fonts.googleapis.com")])
+        .img_src(&[Source::SelfOrigin, Source::Scheme("data"), Source::Host("*.example-cdn.com:443/img/")])
+        .connect_src(&[Source::SelfOrigin, Source::Host("wss://live.example.com")])
+        .frame_ancestors(&[Source::None])
+        .directive("upgrade-insecure-requests", &[])
+        .report_to("csp-endpoint");
+
+    assert_eq!(csp.check(), Ok(()));
+    assert_eq!(
+        policy(&csp),
+        "default-src 'self'; \
+         script-src 'self' 'nonce-rAnd0m+/4w==' 'strict-dynamic'; \
+         style-src 'self' https://fonts.googleapis.com; \
+         img-src 'self' data: *.example-cdn.com:443/img/; \
+         connect-src 'self' wss://live.example.com; \
+         frame-ancestors 'none'; \
+         upgrade-insecure-requests; \
+         report-to csp-endpoint"
+    );
+}
+
+#[test]
+fn none_is_exclusive() {
+    let csp = Csp::new()
+        .default_src(&[Source::None])
+        .directive("object-src", &[Source::None, Source::SelfOrigin]);
+    assert_eq!(csp.check(), Err(CspError::NoneWithOthers(1)));
+}
+
+#[test]
+fn nonce_quoting() {
+    let csp = Csp::new().script_src(&[Source::nonce("abc123")]);
+    assert_eq!(policy(&csp), "script-src 'nonce-abc123'");
+    assert_eq!(Source::nonce("abc123").chunks().concat(), b"'nonce-abc123'");
+
+    let quoted = Csp::new().script_src(&[Source::nonce("'abc123'")]);
+    assert_eq!(quoted.check(), Err(CspError::InvalidSource { directive: 0, source: 0 }));
+}
+
+#[test]
+fn invalid_policies() {
+    assert_eq!(Csp::new().check(), Err(CspError::Empty));
+    assert_eq!(
+        Csp::new().img_src(&[Source::SelfOrigin]).directive("IMG-SRC", &[]).check(),
+        Err(CspError::DuplicateDirective(1))
+    );
+    assert_eq!(Csp::new().directive("script src", &[]).check(), Err(CspError::InvalidDirectiveName(0)));
+
+    let bad_source = |source| Csp::new().default_src(&[Source::SelfOrigin, source]).check();
+    assert_eq!(bad_source(Source::Host("example.com; script-src *")), Err(CspError::InvalidSource { directive: 0, source: 1 }));
+    assert_eq!(bad_source(Source::Host("")), Err(CspError::InvalidSource { directive: 0, source: 1 }));
+    assert_eq!(bad_source(Source::Scheme("https:")), Err(CspError::InvalidSource { directive: 0, source: 1 }));
+    assert_eq!(bad_source(Source::Sha256("")), Err(CspError::InvalidSource { directive: 0, source: 1 }));
+    assert_eq!(bad_source(Source::Host("*")), Ok(()));
+    assert_eq!(bad_source(Source::Host("https://*.example.com:*")), Ok(()));
+}
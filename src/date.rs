@@ -0,0 +1,202 @@
+//! IMF-fixdate formatting for the `Date` family of headers.
+
+use std::sync::atomic::{AtomicU64, Ordering, fence};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Length of an IMF-fixdate, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`.
+pub const IMF_FIXDATE_LEN: usize = 29;
+
+const DAYS: [&[u8; 3]; 7] = [b"Thu", b"Fri", b"Sat", b"Sun", b"Mon", b"Tue", b"Wed"];
+const MONTHS: [&[u8; 3]; 12] = [
+    b"Jan", b"Feb", b"Mar", b"Apr", b"May", b"Jun", b"Jul", b"Aug", b"Sep", b"Oct", b"Nov", b"Dec",
+];
+
+/// Formats `secs` since the Unix epoch as an IMF-fixdate.
+///
+/// Returns `None` past the year 9999, which does not fit the format.
+pub(crate) fn imf_fixdate(secs: u64) -> Option<[u8; IMF_FIXDATE_LEN]> {
+    let days = secs / 86400;
+    let rem = secs % 86400;
+    let (year, month, day) = civil_from_days(days);
+
+    if year > 9999 {
+        return None;
+    }
+
+    let mut out = *b"Thu, 01 Jan 1970 00:00:00 GMT";
+    out[..3].copy_from_slice(DAYS[(days % 7) as usize]);
+    put2(&mut out[5..7], day);
+    out[8..11].copy_from_slice(MONTHS[month as usize - 1]);
+    put2(&mut out[12..14], year / 100);
+    put2(&mut out[14..16], year % 100);
+    put2(&mut out[17..19], rem / 3600);
+    put2(&mut out[20..22], rem / 60 % 60);
+    put2(&mut out[23..25], rem % 60);
+    Some(out)
+}
+
+/// Formats `time` as an IMF-fixdate, truncated to the second.
+///
+/// Returns `None` before the Unix epoch or past the year 9999.
+pub fn format(time: SystemTime) -> Option<[u8; IMF_FIXDATE_LEN]> {
+    imf_fixdate(time.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Like [`format`], but clamps `time` to the range an IMF-fixdate can hold.
+pub(crate) fn format_clamped(time: SystemTime) -> [u8; IMF_FIXDATE_LEN] {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    imf_fixdate(secs).unwrap_or(LAST_DATE)
+}
+
+const LAST_DATE: [u8; IMF_FIXDATE_LEN] = *b"Fri, 31 Dec 9999 23:59:59 GMT";
+
+fn put2(out: &mut [u8], n: u64) {
+    out[0] = b'0' + (n / 10) as u8;
+    out[1] = b'0' + (n % 10) as u8;
+}
+
+// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub(crate) fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719468;
+    let era = z / 146097;
+    let doe = z % 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+    (year, month, day)
+}
+
+/// A `Date` value that is re-formatted at most once per second.
+///
+/// Readers never block: the value is published through a sequence lock, and
+/// a reader that races a refresh simply formats the date itself.
+#[derive(Debug)]
+pub struct DateCache {
+    seq: AtomicU64,
+    secs: AtomicU64,
+    value: [AtomicU64; 4],
+}
+
+impl DateCache {
+    pub fn new() -> Self {
+        Self {
+            seq: AtomicU64::new(0),
+            secs: AtomicU64::new(u64::MAX),
+            value: [const { AtomicU64::new(0) }; 4],
+        }
+    }
+
+    /// The formatted current time.
+    ///
+    /// The bytes are returned by value rather than borrowed, since another
+    /// thread may refresh the cache while a borrow would still be alive.
+    pub fn now_value(&self) -> [u8; IMF_FIXDATE_LEN] {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.value_at(secs)
+    }
+
+    pub(crate) fn value_at(&self, secs: u64) -> [u8; IMF_FIXDATE_LEN] {
+        if let Some(value) = self.load(secs) {
+            return value;
+        }
+
+        let value = imf_fixdate(secs).unwrap_or(LAST_DATE);
+        self.store(secs, &value);
+        value
+    }
+
+    fn load(&self, secs: u64) -> Option<[u8; IMF_FIXDATE_LEN]> {
+        let seq = self.seq.load(Ordering::Acquire);
+        if seq % 2 == 1 || self.secs.load(Ordering::Relaxed) != secs {
+            return None;
+        }
+
+        let mut buf = [0; 32];
+        for (chunk, word) in buf.chunks_exact_mut(8).zip(&self.value) {
+            chunk.copy_from_slice(&word.load(Ordering::Relaxed).to_ne_bytes());
+        }
+
+        fence(Ordering::Acquire);
+        if self.seq.load(Ordering::Relaxed) != seq {
+            return None;
+        }
+
+        let mut value = [0; IMF_FIXDATE_LEN];
+        value.copy_from_slice(&buf[..IMF_FIXDATE_LEN]);
+        Some(value)
+    }
+
+    fn store(&self, secs: u64, value: &[u8; IMF_FIXDATE_LEN]) {
+        let seq = self.seq.load(Ordering::Relaxed);
+        if seq % 2 == 1
+            || self
+                .seq
+                .compare_exchange(seq, seq + 1, Ordering::Acquire, Ordering::Relaxed)
+                .is_err()
+        {
+            // another thread is refreshing, let it win
+            return;
+        }
+        fence(Ordering::Release);
+
+        let mut buf = [0; 32];
+        buf[..IMF_FIXDATE_LEN].copy_from_slice(value);
+        for (chunk, word) in buf.chunks_exact(8).zip(&self.value) {
+            word.store(u64::from_ne_bytes(chunk.try_into().unwrap()), Ordering::Relaxed);
+        }
+        self.secs.store(secs, Ordering::Relaxed);
+
+        self.seq.store(seq + 2, Ordering::Release);
+    }
+}
+
+impl Default for DateCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[test]
+fn known_date() {
+    assert_eq!(&imf_fixdate(784111777).unwrap(), b"Sun, 06 Nov 1994 08:49:37 GMT");
+    assert_eq!(&imf_fixdate(0).unwrap(), b"Thu, 01 Jan 1970 00:00:00 GMT");
+    assert_eq!(&imf_fixdate(951782400).unwrap(), b"Tue, 29 Feb 2000 00:00:00 GMT");
+}
+
+#[test]
+fn cache_same_second() {
+    let cache = DateCache::new();
+    let a = cache.value_at(784111777);
+    assert_eq!(cache.load(784111777), Some(a));
+    assert_eq!(cache.value_at(784111777), a);
+}
+
+#[test]
+fn cache_next_second() {
+    let cache = DateCache::new();
+    assert_eq!(&cache.value_at(784111777), b"Sun, 06 Nov 1994 08:49:37 GMT");
+    assert_eq!(&cache.value_at(784111778), b"Sun, 06 Nov 1994 08:49:38 GMT");
+    assert_eq!(cache.load(784111777), None);
+}
+
+#[test]
+fn cache_threads() {
+    let cache = DateCache::new();
+
+    std::thread::scope(|s| {
+        for t in 0..8u64 {
+            let cache = &cache;
+            s.spawn(move || {
+                for i in 0..10_000u64 {
+                    let secs = 784111777 + (i + t) % 3;
+                    assert_eq!(Some(cache.value_at(secs)), imf_fixdate(secs));
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,121 @@
+use crate::{
+    Body, Field, OwnedRequest, Query, Request, RequestWriteError, Response, ResponseWriteError, Version,
+};
+use std::io::Write;
+
+/// An object-safe view of anything that serializes into an HTTP message, so
+/// requests and responses of different types can be queued together.
+///
+/// ```
+/// use http_write::{HttpEncode, Request, Response, StatusCode};
+///
+/// let mut pending: Vec<Box<dyn HttpEncode>> = vec![
+///     Box::new(Request::get().v1_1()),
+///     Box::new(Response::new(StatusCode::OK).v1_1()),
+/// ];
+///
+/// let mut buf = Vec::new();
+/// for msg in &mut pending {
+///     msg.encode(&mut buf).unwrap();
+/// }
+/// ```
+pub trait HttpEncode {
+    fn encode(&mut self, w: &mut dyn Write) -> Result<usize, EncodeError>;
+}
+
+#[derive(Debug)]
+pub enum EncodeError {
+    Request(RequestWriteError),
+    Response(ResponseWriteError),
+}
+
+impl core::fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Request(err) => err.fmt(f),
+            Self::Response(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(err) => Some(err),
+            Self::Response(err) => Some(err),
+        }
+    }
+}
+
+impl From<RequestWriteError> for EncodeError {
+    fn from(e: RequestWriteError) -> Self {
+        Self::Request(e)
+    }
+}
+
+impl From<ResponseWriteError> for EncodeError {
+    fn from(e: ResponseWriteError) -> Self {
+        Self::Response(e)
+    }
+}
+
+impl<'a, T, Q, V, B> HttpEncode for Request<'a, T, Q, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    Q: Iterator<Item = Query<'a>>,
+    V: Version<'a>,
+    B: Body,
+{
+    fn encode(&mut self, w: &mut dyn Write) -> Result<usize, EncodeError> {
+        Ok(self.write_to(w)?)
+    }
+}
+
+impl<'a, T, V, B> HttpEncode for Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+    B: Body,
+{
+    fn encode(&mut self, w: &mut dyn Write) -> Result<usize, EncodeError> {
+        Ok(self.write_to(w)?)
+    }
+}
+
+impl HttpEncode for OwnedRequest {
+    fn encode(&mut self, w: &mut dyn Write) -> Result<usize, EncodeError> {
+        Ok(self.write_to(w)?)
+    }
+}
+
+#[test]
+fn encode_boxed() {
+    let mut pending: Vec<Box<dyn HttpEncode>> = vec![
+        Box::new(Request::get().v1_1().path("/a").header("a", b"1")),
+        Box::new(Response::new(http::StatusCode::OK).v1_1().header("b", b"2")),
+    ];
+
+    let mut buf = Vec::new();
+    let mut lens = Vec::new();
+    for msg in &mut pending {
+        lens.push(msg.encode(&mut buf).unwrap());
+    }
+
+    let mut headers = [httparse::EMPTY_HEADER; 4];
+    let mut req = httparse::Request::new(&mut headers);
+    assert_eq!(req.parse(&buf).unwrap(), httparse::Status::Complete(lens[0]));
+    assert_eq!(req.path, Some("/a"));
+
+    let mut headers = [httparse::EMPTY_HEADER; 4];
+    let mut res = httparse::Response::new(&mut headers);
+    assert_eq!(res.parse(&buf[lens[0]..]).unwrap(), httparse::Status::Complete(lens[1]));
+    assert_eq!(res.headers[0].name, "b");
+}
+
+#[test]
+fn write_through_dyn() {
+    let mut buf = Vec::new();
+    let w: &mut dyn Write = &mut buf;
+    let len = Request::get().v1_1().write_to(w).unwrap();
+    assert_eq!(len, buf.len());
+}
@@ -0,0 +1,175 @@
+use crate::Field;
+use crate::consts::names;
+
+/// An entity tag, as sent in `ETag` and matched against `If-None-Match`.
+///
+/// The tag is stored without its surrounding quotes or `W/` prefix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ETag<'a> {
+    tag: &'a str,
+    weak: bool,
+}
+
+/// The entity tag contains a byte that is not allowed, at the given position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidETag(pub usize);
+
+impl core::fmt::Display for InvalidETag {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid byte in entity tag at {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidETag {}
+
+impl<'a> ETag<'a> {
+    pub const fn strong(tag: &'a str) -> Result<Self, InvalidETag> {
+        match validate(tag.as_bytes()) {
+            Ok(()) => Ok(Self { tag, weak: false }),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub const fn weak(tag: &'a str) -> Result<Self, InvalidETag> {
+        match validate(tag.as_bytes()) {
+            Ok(()) => Ok(Self { tag, weak: true }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Parses a quoted entity tag such as `"abc"` or `W/"abc"`.
+    pub fn parse(value: &'a str) -> Result<Self, InvalidETag> {
+        let (weak, rest, offset) = match value.strip_prefix("W/") {
+            Some(rest) => (true, rest, 2),
+            None => (false, value, 0),
+        };
+
+        let Some(inner) = rest.strip_prefix('"') else {
+            return Err(InvalidETag(offset));
+        };
+        let Some(tag) = inner.strip_suffix('"') else {
+            return Err(InvalidETag(value.len()));
+        };
+
+        validate(tag.as_bytes()).map_err(|InvalidETag(pos)| InvalidETag(pos + offset + 1))?;
+        Ok(Self { tag, weak })
+    }
+
+    pub const fn tag(&self) -> &'a str {
+        self.tag
+    }
+
+    pub const fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// Strong comparison: both tags are strong and identical.
+    pub fn strong_eq(&self, other: &Self) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// Weak comparison: the tags are identical, ignoring the `W/` prefix.
+    pub fn weak_eq(&self, other: &Self) -> bool {
+        self.tag == other.tag
+    }
+
+    /// The tag with its quotes and `W/` prefix, as it appears in a header.
+    pub(crate) fn quoted(&self) -> [&'a [u8]; 3] {
+        let open: &[u8] = if self.weak { b"W/\"" } else { b"\"" };
+        [open, self.tag.as_bytes(), b"\""]
+    }
+
+    #[cfg_attr(not(feature = "date"), allow(dead_code))]
+    pub(crate) fn field(self) -> Field<'a> {
+        Field::parts(names::ETAG, self.quoted())
+    }
+}
+
+/// Checks that `tag` only holds `etagc`s.
+const fn validate(tag: &[u8]) -> Result<(), InvalidETag> {
+    let mut i = 0;
+    while i < tag.len() {
+        let ch = tag[i];
+        if !(ch == 0x21 || matches!(ch, 0x23..=0x7e) || ch >= 0x80) {
+            return Err(InvalidETag(i));
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Whether an `If-None-Match` value matches `current` under weak comparison.
+///
+/// `*` matches any current representation. Parsing stops at the first
+/// malformed entry, which never matches.
+#[cfg_attr(not(feature = "date"), allow(dead_code))]
+pub(crate) fn none_match_list(value: &str, current: Option<&ETag<'_>>) -> bool {
+    let value = value.trim_matches(is_ows);
+    if value == "*" {
+        return true;
+    }
+    let Some(current) = current else {
+        return false;
+    };
+
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|ch| ch == ',' || is_ows(ch));
+        if rest.is_empty() {
+            return false;
+        }
+
+        let weak = rest.strip_prefix("W/");
+        let Some(inner) = weak.unwrap_or(rest).strip_prefix('"') else {
+            return false;
+        };
+        let Some(end) = inner.find('"') else {
+            return false;
+        };
+
+        let Ok(tag) = ETag::strong(&inner[..end]) else {
+            return false;
+        };
+        if tag.weak_eq(current) {
+            return true;
+        }
+        rest = &inner[end + 1..];
+    }
+}
+
+#[cfg_attr(not(feature = "date"), allow(dead_code))]
+fn is_ows(ch: char) -> bool {
+    ch == ' ' || ch == '\t'
+}
+
+#[test]
+fn parse_etag() {
+    assert_eq!(ETag::parse("\"abc\""), ETag::strong("abc"));
+    assert_eq!(ETag::parse("W/\"abc\""), ETag::weak("abc"));
+    assert_eq!(ETag::parse("\"\""), ETag::strong(""));
+    assert_eq!(ETag::parse("abc"), Err(InvalidETag(0)));
+    assert_eq!(ETag::parse("W/\"a c\""), Err(InvalidETag(4)));
+    assert_eq!(ETag::parse("\"abc"), Err(InvalidETag(4)));
+}
+
+#[test]
+fn compare_etags() {
+    let strong = ETag::strong("v1").unwrap();
+    let weak = ETag::weak("v1").unwrap();
+    assert!(strong.strong_eq(&strong));
+    assert!(!strong.strong_eq(&weak));
+    assert!(strong.weak_eq(&weak));
+    assert!(!strong.weak_eq(&ETag::strong("v2").unwrap()));
+}
+
+#[test]
+fn none_match() {
+    let current = ETag::strong("v1").unwrap();
+    assert!(none_match_list("\"v1\"", Some(&current)));
+    assert!(none_match_list("W/\"v1\"", Some(&current)));
+    assert!(none_match_list("\"v0\", W/\"v1\"", Some(&current)));
+    assert!(none_match_list(" * ", None));
+    assert!(!none_match_list("\"v0\"", Some(&current)));
+    assert!(!none_match_list("\"v1\"", None));
+    assert!(!none_match_list("v1, \"v1\"", Some(&current)));
+}
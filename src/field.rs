@@ -0,0 +1,730 @@
+use crate::{ETag, Framing, HeaderWriteError};
+use core::iter::Map;
+use httparse::Header;
+
+/// The iterator that [`Request::headers`](crate::Request::headers) and
+/// [`Response::headers`](crate::Response::headers) chain on.
+pub type IntoFields<'a, H> =
+    Map<<H as IntoIterator>::IntoIter, fn(<H as IntoIterator>::Item) -> Field<'a>>;
+
+/// A single header line as it is queued on a [`Request`](crate::Request) or
+/// [`Response`](crate::Response).
+///
+/// Every [`Header`] converts into a `Field`; the crate's own helpers use the
+/// other representations to carry values they format themselves.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field<'a> {
+    name: &'a str,
+    value: Value<'a>,
+    trusted: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(not(feature = "date"), allow(dead_code))]
+enum Value<'a> {
+    /// Written back to back, so helpers can add quoting without copying.
+    Parts([&'a [u8]; 3]),
+    Inline(Inline),
+    /// A comma-separated list of quoted entity tags.
+    ETags(&'a [ETag<'a>]),
+    /// A comma-separated list of items with optional q-values.
+    Weighted(&'a [(&'a str, Option<f32>)]),
+    /// A serialized origin; the host is bracketed when it is an IPv6 address.
+    Origin {
+        scheme: &'a str,
+        host: &'a str,
+        port: Option<u16>,
+    },
+    /// A content security policy, joined with `; `.
+    Csp(&'a crate::Csp<'a>),
+    /// Tokens joined with `,` and written in lowercase.
+    LowercaseTokens(&'a [&'a str]),
+    /// `key=value` pairs joined with the separator.
+    Pairs(&'a [(&'a str, &'a str)], &'static [u8]),
+    /// Metrics joined with `, `.
+    ServerTiming(&'a crate::ServerTiming<'a>),
+    /// A cookie and its attributes.
+    SetCookie(&'a crate::SetCookie<'a>),
+    /// `prefix`, then the bytes of `raw` base64-encoded as they are written.
+    #[cfg(feature = "base64")]
+    Base64 {
+        prefix: &'static str,
+        raw: [&'a [u8]; 3],
+        alphabet: crate::Base64Alphabet,
+    },
+    /// A value the field owns, checked like `Parts` when written.
+    Owned(Vec<u8>),
+    /// A complete header line written verbatim, and its trimmed value.
+    Raw { line: &'a [u8], value: &'a [u8] },
+    /// A value a helper already rejected.
+    Invalid(HeaderWriteError),
+}
+
+/// A short value stored in the field itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Inline {
+    buf: [u8; Inline::CAP],
+    len: u8,
+}
+
+#[cfg_attr(not(feature = "date"), allow(dead_code))]
+impl Inline {
+    pub(crate) const CAP: usize = 64;
+
+    pub(crate) fn new(value: &[u8]) -> Self {
+        let mut buf = [0; Self::CAP];
+        buf[..value.len()].copy_from_slice(value);
+        Self {
+            buf,
+            len: value.len() as u8,
+        }
+    }
+
+    /// `n` in decimal.
+    pub(crate) fn decimal(n: u64) -> Self {
+        let mut digits = [0; 20];
+        let mut start = digits.len();
+        let mut rest = n;
+        loop {
+            start -= 1;
+            digits[start] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+        Self::new(&digits[start..])
+    }
+
+    /// A structured field date, `@` and the seconds since the Unix epoch.
+    pub(crate) fn sf_date(secs: u64) -> Self {
+        Self::prefixed(b"@", secs)
+    }
+
+    /// `prefix` followed by `n` in decimal; `prefix` is at most 44 bytes.
+    pub(crate) fn prefixed(prefix: &[u8], n: u64) -> Self {
+        let digits = Self::decimal(n);
+        let mut out = Self::new(prefix);
+        let start = prefix.len();
+        out.buf[start..start + digits.len as usize].copy_from_slice(digits.as_bytes());
+        out.len += digits.len;
+        out
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len as usize]
+    }
+}
+
+impl<'a> Field<'a> {
+    /// A header that is validated when it is written.
+    pub fn new(name: &'a str, value: &'a [u8]) -> Self {
+        Self {
+            name,
+            value: Value::Parts([value, b"", b""]),
+            trusted: false,
+        }
+    }
+
+    /// Like [`Field::new`], but the field keeps `value` itself, so it can be a
+    /// value computed on the spot. This costs an allocation that borrowing
+    /// avoids.
+    pub fn owned(name: &'a str, value: Vec<u8>) -> Self {
+        Self {
+            name,
+            value: Value::Owned(value),
+            trusted: false,
+        }
+    }
+
+    /// A header that skips validation when written.
+    pub(crate) const fn trusted(name: &'a str, value: &'a [u8]) -> Self {
+        Self::parts(name, [value, b"", b""])
+    }
+
+    /// A value written as the concatenation of `parts`, which the caller has
+    /// already validated.
+    pub(crate) const fn parts(name: &'a str, parts: [&'a [u8]; 3]) -> Self {
+        Self {
+            name,
+            value: Value::Parts(parts),
+            trusted: true,
+        }
+    }
+
+    /// A value the crate formatted itself, which is always valid.
+    #[cfg_attr(not(feature = "date"), allow(dead_code))]
+    pub(crate) fn inline(name: &'a str, value: Inline) -> Self {
+        Self {
+            name,
+            value: Value::Inline(value),
+            trusted: true,
+        }
+    }
+
+    /// `value` in decimal, under a name that is checked when written.
+    pub(crate) fn int(name: &'a str, value: u64) -> Self {
+        Self {
+            name,
+            value: Value::Inline(Inline::decimal(value)),
+            trusted: false,
+        }
+    }
+
+    /// An entity-tag list, which is only invalid when empty.
+    pub(crate) fn etags(name: &'a str, tags: &'a [ETag<'a>]) -> Self {
+        Self {
+            name,
+            value: Value::ETags(tags),
+            trusted: false,
+        }
+    }
+
+    /// A list of language ranges, which is checked when written.
+    pub(crate) fn languages(name: &'a str, items: &'a [(&'a str, Option<f32>)]) -> Self {
+        Self {
+            name,
+            value: Value::Weighted(items),
+            trusted: false,
+        }
+    }
+
+    /// An origin whose scheme and host were validated by the caller.
+    pub(crate) fn origin(name: &'a str, scheme: &'a str, host: &'a str, port: Option<u16>) -> Self {
+        Self {
+            name,
+            value: Value::Origin { scheme, host, port },
+            trusted: true,
+        }
+    }
+
+    /// A policy that is checked when written.
+    pub(crate) fn csp(name: &'a str, csp: &'a crate::Csp<'a>) -> Self {
+        match csp.check() {
+            Ok(()) => Self {
+                name,
+                value: Value::Csp(csp),
+                trusted: true,
+            },
+            Err(_) => Self::invalid(name, 0),
+        }
+    }
+
+    /// `tokens` joined with `,` in lowercase, checked up front like
+    /// [`Field::csp`].
+    pub(crate) fn lowercase_tokens(name: &'a str, tokens: &'a [&'a str]) -> Self {
+        if tokens.is_empty() {
+            return Self::invalid(name, 0);
+        }
+        let mut offset = 0;
+        for (i, token) in tokens.iter().enumerate() {
+            offset += usize::from(i > 0);
+            if let Some(pos) = crate::invalid_token_pos(token.as_bytes()) {
+                return Self::invalid(name, offset + pos);
+            }
+            offset += token.len();
+        }
+        Self {
+            name,
+            value: Value::LowercaseTokens(tokens),
+            trusted: true,
+        }
+    }
+
+    /// `pairs` as `key=value` joined with `separator`, which the caller has
+    /// checked.
+    pub(crate) fn pairs(name: &'a str, pairs: &'a [(&'a str, &'a str)], separator: &'static [u8]) -> Self {
+        Self {
+            name,
+            value: Value::Pairs(pairs, separator),
+            trusted: true,
+        }
+    }
+
+    /// Server timing metrics, checked up front like [`Field::csp`].
+    pub(crate) fn server_timing(name: &'a str, timing: &'a crate::ServerTiming<'a>) -> Self {
+        match timing.check() {
+            Ok(()) => Self {
+                name,
+                value: Value::ServerTiming(timing),
+                trusted: true,
+            },
+            Err(pos) => Self::invalid(name, pos),
+        }
+    }
+
+    /// A `Set-Cookie`, checked up front like [`Field::csp`].
+    pub(crate) fn set_cookie(cookie: &'a crate::SetCookie<'a>) -> Self {
+        let name = crate::consts::names::SET_COOKIE;
+        match cookie.check() {
+            Ok(()) => Self {
+                name,
+                value: Value::SetCookie(cookie),
+                trusted: true,
+            },
+            Err(pos) => Self::invalid(name, pos),
+        }
+    }
+
+    /// A header whose value is `raw` in base64; the name is checked when it is
+    /// written.
+    #[cfg(feature = "base64")]
+    pub(crate) fn base64(name: &'a str, raw: &'a [u8], alphabet: crate::Base64Alphabet) -> Self {
+        Self {
+            name,
+            value: Value::Base64 {
+                prefix: "",
+                raw: [raw, b"", b""],
+                alphabet,
+            },
+            trusted: false,
+        }
+    }
+
+    /// `Authorization: Basic` with `user:password` in base64, or an invalid
+    /// header if `user` holds a `:`.
+    #[cfg(feature = "base64")]
+    pub(crate) fn basic_auth(user: &'a str, password: &'a str) -> Self {
+        let name = crate::consts::names::AUTHORIZATION;
+        if user.contains(':') {
+            return Self::invalid(name, "Basic ".len());
+        }
+        Self {
+            name,
+            value: Value::Base64 {
+                prefix: "Basic ",
+                raw: [user.as_bytes(), b":", password.as_bytes()],
+                alphabet: crate::Base64Alphabet::Standard,
+            },
+            trusted: true,
+        }
+    }
+
+    /// A header that fails to write with [`HeaderWriteError::InvalidValue`].
+    pub(crate) fn invalid(name: &'a str, pos: usize) -> Self {
+        Self {
+            name,
+            value: Value::Invalid(HeaderWriteError::InvalidValue(pos)),
+            trusted: false,
+        }
+    }
+
+    /// An already serialized header line, optionally ending in CRLF.
+    ///
+    /// The name runs up to the first `:` and is checked like any other; the
+    /// rest of the line is kept byte for byte.
+    pub(crate) fn raw(line: &'a [u8]) -> Self {
+        let body = line.strip_suffix(b"\r\n").unwrap_or(line);
+        let invalid = |pos| Self {
+            name: "",
+            value: Value::Invalid(HeaderWriteError::InvalidRawLine(pos)),
+            trusted: false,
+        };
+
+        if let Some(pos) = body.iter().position(|&ch| matches!(ch, b'\r' | b'\n' | b'\0')) {
+            return invalid(pos);
+        }
+        let Some(colon) = body.iter().position(|&ch| ch == b':') else {
+            return invalid(body.len());
+        };
+        let raw_name = &body[..colon];
+        let name = match crate::check_header(raw_name, b"") {
+            Ok(()) if !raw_name.is_empty() => core::str::from_utf8(raw_name).expect("names are ASCII"),
+            Ok(()) => return invalid(0),
+            Err(HeaderWriteError::InvalidName(pos)) => return invalid(pos),
+            Err(_) => unreachable!("the value is empty"),
+        };
+
+        Self {
+            name,
+            value: Value::Raw {
+                line,
+                value: body[colon + 1..].trim_ascii(),
+            },
+            trusted: true,
+        }
+    }
+
+    /// A `Content-Length` for a body of `len` bytes.
+    pub(crate) fn content_length(len: u64) -> Self {
+        Self::inline(crate::consts::names::CONTENT_LENGTH, Inline::decimal(len))
+    }
+
+    /// A `Priority` of `urgency` (0 to 7) written without the defaults,
+    /// `u=3` and no `i`, or `None` when nothing is left to write.
+    pub(crate) fn priority(urgency: u8, incremental: bool) -> Option<Self> {
+        let name = crate::consts::names::PRIORITY;
+        let i: &[u8] = if incremental { b", i" } else { b"" };
+        match (urgency, incremental) {
+            (8.., _) => Some(Self::invalid(name, 2)),
+            (3, false) => None,
+            (3, true) => Some(Self::parts(name, [b"i", b"", b""])),
+            (u, _) => Some(Self::parts(name, [b"u=", digit(u.into()), i])),
+        }
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    /// Whether this header frames the body, so no `Content-Length` is added.
+    pub(crate) fn is_framing(&self) -> bool {
+        self.name.eq_ignore_ascii_case("content-length") || self.name.eq_ignore_ascii_case("transfer-encoding")
+    }
+
+    pub(crate) fn is_host(&self) -> bool {
+        self.name.eq_ignore_ascii_case("host")
+    }
+
+    /// The value as the chunks it is written in.
+    pub(crate) fn value_chunks(&self) -> impl Iterator<Item = &[u8]> {
+        let mut head: [&[u8]; 11] = [b""; 11];
+        let (mut tags, mut weighted, mut csp, mut timing, mut cookie) = ([].as_slice(), [].as_slice(), None, None, None);
+        let (mut tokens, mut pairs, mut pair_separator) = ([].as_slice(), [].as_slice(), b"".as_slice());
+        match &self.value {
+            Value::Parts(parts) => head[..3].copy_from_slice(parts),
+            Value::Inline(v) => head[0] = v.as_bytes(),
+            Value::ETags(t) => tags = *t,
+            Value::Weighted(items) => weighted = *items,
+            Value::Origin { scheme, host, port } => {
+                let bracket = host.contains(':') && !host.starts_with('[');
+                head[..3].copy_from_slice(&[scheme.as_bytes(), b"://", host.as_bytes()]);
+                if bracket {
+                    head[1..5].copy_from_slice(&[b"://[", host.as_bytes(), b"]", b""]);
+                }
+                if let Some(port) = port {
+                    head[5] = b":";
+                    let mut rest = *port;
+                    let len = port.checked_ilog10().unwrap_or(0) as usize + 1;
+                    for slot in head[6..6 + len].iter_mut().rev() {
+                        *slot = digit(rest % 10);
+                        rest /= 10;
+                    }
+                }
+            }
+            Value::Csp(policy) => csp = Some(*policy),
+            Value::LowercaseTokens(t) => tokens = *t,
+            Value::Pairs(p, sep) => (pairs, pair_separator) = (*p, *sep),
+            Value::ServerTiming(metrics) => timing = Some(*metrics),
+            Value::SetCookie(c) => cookie = Some(*c),
+            #[cfg(feature = "base64")]
+            Value::Base64 { prefix, .. } => head[0] = prefix.as_bytes(),
+            Value::Owned(value) => head[0] = value,
+            Value::Raw { value, .. } => head[0] = value,
+            Value::Invalid(_) => {}
+        }
+
+        let tags = tags.iter().enumerate().flat_map(|(i, tag)| {
+            let [open, tag, close] = tag.quoted();
+            [separator(i), open, tag, close]
+        });
+        let weighted = weighted.iter().enumerate().flat_map(|(i, (item, q))| {
+            let [a, b, c, d, e, f] = crate::accept::qvalue_chunks(*q);
+            [separator(i), item.as_bytes(), a, b, c, d, e, f]
+        });
+        let tokens = tokens.iter().enumerate().flat_map(|(i, token)| {
+            let sep: &[u8] = if i > 0 { b"," } else { b"" };
+            let lower = token.as_bytes().split_inclusive(u8::is_ascii_uppercase).flat_map(|part| {
+                match part.split_last() {
+                    Some((&ch, text)) if ch.is_ascii_uppercase() => [text, lowercase(ch)],
+                    _ => [part, b""],
+                }
+            });
+            [sep].into_iter().chain(lower)
+        });
+        let pairs = pairs.iter().enumerate().flat_map(move |(i, (key, value))| {
+            let sep: &[u8] = if i > 0 { pair_separator } else { b"" };
+            [sep, key.as_bytes(), b"=", value.as_bytes()]
+        });
+        let csp = csp.into_iter().flat_map(crate::Csp::chunks);
+        let timing = timing.into_iter().flat_map(crate::ServerTiming::chunks);
+        let cookie = cookie.into_iter().flat_map(crate::SetCookie::chunks);
+        head.into_iter()
+            .chain(tags)
+            .chain(weighted)
+            .chain(tokens)
+            .chain(pairs)
+            .chain(csp)
+            .chain(timing)
+            .chain(cookie)
+            .chain(self.base64_chunks())
+    }
+
+    #[cfg(feature = "base64")]
+    fn base64_chunks(&self) -> impl Iterator<Item = &[u8]> {
+        let raw = match self.value {
+            Value::Base64 { raw, alphabet, .. } => Some(crate::base64::encode_chunks(raw, alphabet)),
+            _ => None,
+        };
+        raw.into_iter().flatten()
+    }
+
+    #[cfg(not(feature = "base64"))]
+    fn base64_chunks(&self) -> core::iter::Empty<&[u8]> {
+        core::iter::empty()
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), HeaderWriteError> {
+        if self.trusted {
+            return Ok(());
+        }
+        #[cfg(test)]
+        CHECKS.with(|c| c.set(c.get() + 1));
+        match &self.value {
+            Value::Parts([value, ..]) => crate::check_header(self.name.as_bytes(), value),
+            Value::Owned(value) => crate::check_header(self.name.as_bytes(), value),
+            Value::ETags([]) | Value::Weighted([]) => Err(HeaderWriteError::InvalidValue(0)),
+            Value::Invalid(err) => Err(*err),
+            Value::Weighted(items) => {
+                crate::check_header(self.name.as_bytes(), b"")?;
+                let mut offset = 0;
+                for (i, (range, q)) in items.iter().enumerate() {
+                    offset += separator(i).len();
+                    crate::accept::check_language_range(range)
+                        .map_err(|pos| HeaderWriteError::InvalidValue(offset + pos))?;
+                    offset += range.len();
+                    if q.is_some_and(|q| crate::accept::qvalue_millis(q).is_none()) {
+                        return Err(HeaderWriteError::InvalidValue(offset));
+                    }
+                    offset += crate::accept::qvalue_chunks(*q).iter().map(|c| c.len()).sum::<usize>();
+                }
+                Ok(())
+            }
+            _ => crate::check_header(self.name.as_bytes(), b""),
+        }
+    }
+
+    /// The whole line as the chunks [`Field::write_unchecked`] writes, for
+    /// a vectored write.
+    pub(crate) fn line_chunks(&self) -> impl Iterator<Item = &[u8]> {
+        let (raw, named): (&[u8], bool) = match self.value {
+            Value::Raw { line, .. } => (line, false),
+            _ => (b"", true),
+        };
+        let crlf: &[u8] = if raw.ends_with(b"\r\n") { b"" } else { b"\r\n" };
+        let name: [&[u8]; 2] = if named { [self.name.as_bytes(), b": "] } else { [b"", b""] };
+        let value = named.then(|| self.value_chunks()).into_iter().flatten();
+        [raw].into_iter().chain(name).chain(value).chain([crlf])
+    }
+
+    pub(crate) unsafe fn write_unchecked<W: std::io::Write + ?Sized>(
+        &self,
+        w: &mut W,
+    ) -> std::io::Result<usize> {
+        if let Value::Raw { line, .. } = self.value {
+            w.write_all(line)?;
+            if line.ends_with(b"\r\n") {
+                return Ok(line.len());
+            }
+            w.write_all(b"\r\n")?;
+            return Ok(line.len() + 2);
+        }
+        unsafe { crate::write_header_unchecked(w, self.name, self.value_chunks()) }
+    }
+}
+
+/// The framing headers seen among a message's headers.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct FramingHeaders {
+    /// The last `Content-Length`, `None` inside if it is not a number.
+    length: Option<Option<u64>>,
+    /// Whether the last `Transfer-Encoding` ends in `chunked`, if one was
+    /// seen.
+    encoding: Option<bool>,
+    /// Whether two `Content-Length`s gave different or invalid values.
+    lengths_differ: bool,
+}
+
+impl FramingHeaders {
+    /// Records `header`, returning whether the message now has both a
+    /// `Content-Length` and a `Transfer-Encoding`, which must not be sent
+    /// together (RFC 9112, section 6.2), or two `Content-Length`s that do
+    /// not agree on a length (section 6.3).
+    pub(crate) fn conflicts(&mut self, header: &Field<'_>) -> bool {
+        if header.name.eq_ignore_ascii_case("content-length") {
+            let len = parse_length(header.value_chunks());
+            self.lengths_differ |= self.length.is_some_and(|seen| seen != len || len.is_none());
+            self.length = Some(len);
+        } else if header.name.eq_ignore_ascii_case("transfer-encoding") {
+            self.encoding = Some(ends_chunked(header.value_chunks()));
+        }
+        self.conflicting()
+    }
+
+    /// Whether both were seen, or the lengths disagree.
+    pub(crate) fn conflicting(&self) -> bool {
+        self.lengths_differ || (self.length.is_some() && self.encoding.is_some())
+    }
+
+    /// Whether either was seen, so no framing header is added.
+    pub(crate) fn any(&self) -> bool {
+        self.length.is_some() || self.encoding.is_some()
+    }
+
+    /// Whether the headers frame the body other than its `framing`, so it
+    /// would be read back wrong. A message without a body may frame itself.
+    pub(crate) fn disagrees(&self, framing: Framing) -> bool {
+        match framing {
+            Framing::None => false,
+            Framing::Length(len) => self.encoding.is_some() || self.length.is_some_and(|seen| seen != Some(len)),
+            Framing::Chunked => self.length.is_some() || self.encoding == Some(false),
+        }
+    }
+}
+
+/// The value of a `Content-Length`, if it is a number.
+fn parse_length<'v>(value: impl Iterator<Item = &'v [u8]>) -> Option<u64> {
+    let mut len: Option<u64> = None;
+    let mut done = false;
+    for &ch in value.flatten() {
+        match ch {
+            b'0'..=b'9' if !done => len = Some(len.unwrap_or(0).checked_mul(10)?.checked_add(u64::from(ch - b'0'))?),
+            b' ' | b'\t' => done = len.is_some(),
+            _ => return None,
+        }
+    }
+    len
+}
+
+/// Whether the last coding of a `Transfer-Encoding` is `chunked`.
+fn ends_chunked<'v>(value: impl Iterator<Item = &'v [u8]>) -> bool {
+    let (mut len, mut matches, mut done) = (0, true, false);
+    for &ch in value.flatten() {
+        match ch {
+            b',' => (len, matches, done) = (0, true, false),
+            b' ' | b'\t' => done = len > 0,
+            _ => {
+                matches &= !done && b"chunked".get(len) == Some(&ch.to_ascii_lowercase());
+                len += 1;
+            }
+        }
+    }
+    matches && len == 7
+}
+
+/// The most names [`UniqueHeaders`] tracks, one bit each.
+pub(crate) const MAX_UNIQUE_HEADERS: usize = 64;
+
+/// Which of the headers a message may hold once were seen.
+pub(crate) struct UniqueHeaders {
+    names: &'static [&'static str],
+    seen: u64,
+}
+
+impl UniqueHeaders {
+    pub(crate) fn new(names: &'static [&'static str]) -> Self {
+        debug_assert!(names.len() <= MAX_UNIQUE_HEADERS);
+        Self { names, seen: 0 }
+    }
+
+    /// Records `header`, returning whether one of the names was already seen.
+    pub(crate) fn repeats(&mut self, header: &Field<'_>) -> bool {
+        match self.names.iter().position(|name| name.eq_ignore_ascii_case(header.name)) {
+            Some(i) => {
+                let bit = 1 << i;
+                let seen = self.seen & bit != 0;
+                self.seen |= bit;
+                seen
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    pub(crate) static CHECKS: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+/// The decimal digit `d` as a one-byte slice.
+pub(crate) fn digit(d: u16) -> &'static [u8] {
+    const DIGITS: &[u8; 10] = b"0123456789";
+    let d = d as usize;
+    &DIGITS[d..d + 1]
+}
+
+/// The digits of `n`, as one-byte chunks.
+pub(crate) fn decimal<'s>(n: u128) -> impl Iterator<Item = &'s [u8]> {
+    let len = n.checked_ilog10().unwrap_or(0) + 1;
+    (0..len).rev().map(move |i| digit((n / 10u128.pow(i) % 10) as u16))
+}
+
+/// The uppercase ASCII letter `ch` in lowercase, as a one-byte slice.
+fn lowercase<'s>(ch: u8) -> &'s [u8] {
+    const LETTERS: &[u8; 26] = b"abcdefghijklmnopqrstuvwxyz";
+    let i = (ch - b'A') as usize;
+    &LETTERS[i..i + 1]
+}
+
+fn separator(i: usize) -> &'static [u8] {
+    if i > 0 { b", " } else { b"" }
+}
+
+impl<'a> From<Header<'a>> for Field<'a> {
+    fn from(h: Header<'a>) -> Self {
+        Self::new(h.name, h.value)
+    }
+}
+
+/// A `(name, value)` text header, checked when it is written, so
+/// [`Request::headers`](crate::Request::headers) takes string tuples.
+impl<'a> From<(&'a str, &'a str)> for Field<'a> {
+    fn from((name, value): (&'a str, &'a str)) -> Self {
+        Self::new(name, value.as_bytes())
+    }
+}
+
+/// A header that is known to be valid, so it is written through the
+/// unchecked path.
+///
+/// Build one with [`ValidatedHeader::new`] in a `const` to have the check
+/// happen at compile time; see [`consts`](crate::consts) for common ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidatedHeader<'a> {
+    name: &'a str,
+    value: &'a [u8],
+}
+
+impl<'a> ValidatedHeader<'a> {
+    pub const fn new(name: &'a str, value: &'a [u8]) -> Result<Self, HeaderWriteError> {
+        match crate::check_header(name.as_bytes(), value) {
+            Ok(()) => Ok(Self { name, value }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Caller must guarantee that `name` and `value` are a valid header.
+    pub const unsafe fn new_unchecked(name: &'a str, value: &'a [u8]) -> Self {
+        Self { name, value }
+    }
+
+    pub const fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub const fn value(&self) -> &'a [u8] {
+        self.value
+    }
+}
+
+impl<'a> From<ValidatedHeader<'a>> for Field<'a> {
+    fn from(h: ValidatedHeader<'a>) -> Self {
+        Self::trusted(h.name, h.value)
+    }
+}
+
+#[test]
+fn unique_headers_bits() {
+    static NAMES: [&str; MAX_UNIQUE_HEADERS] = {
+        let mut names = ["x"; MAX_UNIQUE_HEADERS];
+        names[MAX_UNIQUE_HEADERS - 1] = "last";
+        names
+    };
+    let mut unique = UniqueHeaders::new(&NAMES);
+    let last = Field::new("LAST", b"1");
+    assert!(!unique.repeats(&last));
+    assert!(unique.repeats(&last));
+    assert!(!unique.repeats(&Field::new("other", b"1")));
+}
@@ -0,0 +1,251 @@
+//! Headers for serving a static file from its metadata.
+
+use crate::consts::{self, names};
+use crate::field::Inline;
+use crate::{ETag, Field, ResourceMeta, Response};
+use core::iter::{Chain, Flatten};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What [`Response::file`] describes a file with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FileMeta<'a> {
+    pub len: u64,
+    pub modified: Option<SystemTime>,
+    /// See [`FileETag`] for one derived from the length and `modified`.
+    pub etag: Option<ETag<'a>>,
+    /// See [`guess_content_type`] for one derived from the extension.
+    pub content_type: Option<&'a str>,
+}
+
+impl<'a> FileMeta<'a> {
+    /// The validators [`Response::conditional`] checks requests against.
+    pub fn resource_meta(&self) -> ResourceMeta<'a> {
+        ResourceMeta {
+            etag: self.etag,
+            last_modified: self.modified,
+        }
+    }
+}
+
+/// The headers attached by [`Response::file`] and [`Response::file_entity`].
+pub type FileHeaders<'a> = Flatten<core::array::IntoIter<Option<Field<'a>>, 5>>;
+
+/// An entity tag derived from a file's length and modification time, as
+/// `<len>-<mtime>` in hex.
+///
+/// Modification times only have a resolution of a second once they are
+/// sent, so a file rewritten twice within one second at the same length
+/// keeps its tag; [`FileETag::weak`] admits as much.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FileETag {
+    buf: [u8; 33],
+    len: u8,
+    weak: bool,
+}
+
+impl FileETag {
+    pub fn strong(len: u64, modified: SystemTime) -> Self {
+        Self::new(len, modified, false)
+    }
+
+    pub fn weak(len: u64, modified: SystemTime) -> Self {
+        Self::new(len, modified, true)
+    }
+
+    fn new(len: u64, modified: SystemTime, weak: bool) -> Self {
+        let secs = modified.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let mut buf = [0; 33];
+        let mut at = hex(&mut buf, len);
+        buf[at] = b'-';
+        at += 1;
+        at += hex(&mut buf[at..], secs);
+        Self {
+            buf,
+            len: at as u8,
+            weak,
+        }
+    }
+
+    pub fn etag(&self) -> ETag<'_> {
+        let tag = core::str::from_utf8(&self.buf[..self.len as usize]).expect("hex digits are ASCII");
+        match self.weak {
+            true => ETag::weak(tag),
+            false => ETag::strong(tag),
+        }
+        .expect("hex digits are etagc")
+    }
+}
+
+/// Writes `n` in lowercase hex at the start of `out`, returning its length.
+fn hex(out: &mut [u8], n: u64) -> usize {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let len = (n.checked_ilog2().unwrap_or(0) / 4 + 1) as usize;
+    for (i, slot) in out[..len].iter_mut().rev().enumerate() {
+        *slot = DIGITS[(n >> (4 * i) & 0xf) as usize];
+    }
+    len
+}
+
+/// The media type commonly served for the extension of `path`, matched
+/// without regard to case.
+pub fn guess_content_type(path: &str) -> Option<&'static str> {
+    let (_, ext) = path.rsplit_once('.')?;
+    const TYPES: &[(&str, &str)] = &[
+        ("css", "text/css; charset=utf-8"),
+        ("gif", "image/gif"),
+        ("htm", "text/html; charset=utf-8"),
+        ("html", "text/html; charset=utf-8"),
+        ("ico", "image/x-icon"),
+        ("jpeg", "image/jpeg"),
+        ("jpg", "image/jpeg"),
+        ("js", "text/javascript; charset=utf-8"),
+        ("json", "application/json"),
+        ("mjs", "text/javascript; charset=utf-8"),
+        ("pdf", "application/pdf"),
+        ("png", "image/png"),
+        ("svg", "image/svg+xml"),
+        ("txt", "text/plain; charset=utf-8"),
+        ("wasm", "application/wasm"),
+        ("webp", "image/webp"),
+        ("woff", "font/woff"),
+        ("woff2", "font/woff2"),
+        ("xml", "application/xml"),
+    ];
+    TYPES.iter().find(|(e, _)| e.eq_ignore_ascii_case(ext)).map(|&(_, ty)| ty)
+}
+
+impl<'a, T, V, B> Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Adds `Content-Length`, `Content-Type`, `Accept-Ranges: bytes`, `ETag`
+    /// and `Last-Modified` for the file, leaving out those `meta` has no
+    /// value for.
+    ///
+    /// The `Content-Length` frames the body, so a file body written after
+    /// the head must be exactly `meta.len` bytes long.
+    pub fn file(self, meta: &FileMeta<'a>) -> Response<'a, Chain<T, FileHeaders<'a>>, V, B> {
+        let etag = meta.etag.map(ETag::field);
+        let last_modified = meta
+            .modified
+            .and_then(crate::date::format)
+            .map(|date| Field::inline(names::LAST_MODIFIED, Inline::new(&date)));
+        self.file_headers(meta, etag, last_modified)
+    }
+
+    /// Like [`Response::file`], without the validators, for a response from
+    /// [`Response::conditional`] that already carries them.
+    ///
+    /// A `304 Not Modified` still describes the file it stands for, so its
+    /// `Content-Length` is that of the file.
+    pub fn file_entity(self, meta: &FileMeta<'a>) -> Response<'a, Chain<T, FileHeaders<'a>>, V, B> {
+        self.file_headers(meta, None, None)
+    }
+
+    fn file_headers(
+        self,
+        meta: &FileMeta<'a>,
+        etag: Option<Field<'a>>,
+        last_modified: Option<Field<'a>>,
+    ) -> Response<'a, Chain<T, FileHeaders<'a>>, V, B> {
+        let content_type = meta.content_type.map(|ty| Field::new(names::CONTENT_TYPE, ty.as_bytes()));
+        let headers = [
+            Some(Field::content_length(meta.len)),
+            content_type,
+            Some(consts::ACCEPT_RANGES_BYTES.into()),
+            etag,
+            last_modified,
+        ];
+        self.fields(headers.into_iter().flatten())
+    }
+}
+
+#[cfg(test)]
+const MODIFIED: u64 = 784111777;
+
+#[cfg(test)]
+fn written<'a, T: Iterator<Item = Field<'a>>>(mut res: Response<'a, T, crate::version::V1_1>) -> String {
+    let mut buf = Vec::new();
+    res.write_to(&mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn full_metadata() {
+    let modified = UNIX_EPOCH + std::time::Duration::from_secs(MODIFIED);
+    let tag = FileETag::weak(1234, modified);
+    let meta = FileMeta {
+        len: 1234,
+        modified: Some(modified),
+        etag: Some(tag.etag()),
+        content_type: guess_content_type("static/site.CSS"),
+    };
+    assert_eq!(
+        written(Response::new(http::StatusCode::OK).v1_1().file(&meta)),
+        "HTTP/1.1 200 OK\r\n\
+         Content-Length: 1234\r\n\
+         Content-Type: text/css; charset=utf-8\r\n\
+         Accept-Ranges: bytes\r\n\
+         ETag: W/\"4d2-2ebc98a1\"\r\n\
+         Last-Modified: Sun, 06 Nov 1994 08:49:37 GMT\r\n\r\n"
+    );
+}
+
+#[test]
+fn minimal_metadata() {
+    let meta = FileMeta {
+        len: 0,
+        ..FileMeta::default()
+    };
+    assert_eq!(
+        written(Response::new(http::StatusCode::OK).v1_1().file(&meta)),
+        "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nAccept-Ranges: bytes\r\n\r\n"
+    );
+
+    let bad = FileMeta {
+        content_type: Some("text/plain\r\nX-Injected: 1"),
+        ..meta
+    };
+    let err = Response::new(http::StatusCode::OK).v1_1().file(&bad).write_to(&mut Vec::new());
+    assert!(matches!(err, Err(crate::ResponseWriteError::InvalidHeader { .. })));
+}
+
+#[test]
+fn not_modified_keeps_entity_headers() {
+    let modified = UNIX_EPOCH + std::time::Duration::from_secs(MODIFIED);
+    let tag = FileETag::strong(1234, modified);
+    let meta = FileMeta {
+        len: 1234,
+        modified: Some(modified),
+        etag: Some(tag.etag()),
+        content_type: Some("image/png"),
+    };
+    assert_eq!(tag.etag(), ETag::strong("4d2-2ebc98a1").unwrap());
+
+    let crate::ConditionalOutcome::NotModified(res) =
+        Response::conditional(&meta.resource_meta(), Some("\"4d2-2ebc98a1\""), None)
+    else {
+        panic!("the tags match");
+    };
+    let mut buf = Vec::new();
+    res.file_entity(&meta).v1_1().write_to(&mut buf).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "HTTP/1.1 304 Not Modified\r\n\
+         ETag: \"4d2-2ebc98a1\"\r\n\
+         Last-Modified: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
+         Content-Length: 1234\r\n\
+         Content-Type: image/png\r\n\
+         Accept-Ranges: bytes\r\n\r\n"
+    );
+}
+
+#[test]
+fn derived_etags() {
+    assert_eq!(FileETag::weak(0, UNIX_EPOCH).etag(), ETag::weak("0-0").unwrap());
+    let modified = UNIX_EPOCH + std::time::Duration::from_secs(0xffff_ffff);
+    assert_eq!(FileETag::strong(u64::MAX, modified).etag().tag(), "ffffffffffffffff-ffffffff");
+    assert_eq!(guess_content_type("archive.tar.gz"), None);
+    assert_eq!(guess_content_type("noext"), None);
+    assert_eq!(guess_content_type("app.wasm"), Some("application/wasm"));
+}
@@ -0,0 +1,166 @@
+//! Writing messages to a [`core::fmt::Write`] sink.
+
+use std::io;
+
+/// Shows a serialized message for `Display`, with bytes that are not UTF-8
+/// replaced by `U+FFFD`, or the error that kept it from being written.
+pub(crate) fn display_lossy<E: core::fmt::Display>(
+    f: &mut core::fmt::Formatter<'_>,
+    message: Result<Vec<u8>, E>,
+) -> core::fmt::Result {
+    match message {
+        Ok(bytes) => f.write_str(&String::from_utf8_lossy(&bytes)),
+        Err(err) => write!(f, "<{err}>"),
+    }
+}
+
+/// Passes UTF-8 on to a [`core::fmt::Write`], holding back a character split
+/// across writes until the rest of it arrives.
+///
+/// Bytes that are not UTF-8 fail with [`io::ErrorKind::InvalidData`].
+pub(crate) struct Utf8Writer<'w, F: ?Sized> {
+    inner: &'w mut F,
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl<'w, F: core::fmt::Write + ?Sized> Utf8Writer<'w, F> {
+    pub(crate) fn new(inner: &'w mut F) -> Self {
+        Self {
+            inner,
+            pending: [0; 4],
+            pending_len: 0,
+        }
+    }
+
+    /// Fails if the last write ended partway through a character.
+    pub(crate) fn finish(self) -> io::Result<()> {
+        match self.pending_len {
+            0 => Ok(()),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        self.inner.write_str(s).map_err(|_| io::Error::other("formatter error"))
+    }
+
+    /// Completes the held back character with the start of `buf`, returning
+    /// how many bytes of `buf` that took.
+    fn complete_pending(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let held = self.pending_len;
+        let take = buf.len().min(4 - held);
+        let mut joined = self.pending;
+        joined[held..held + take].copy_from_slice(&buf[..take]);
+        match core::str::from_utf8(&joined[..held + take]) {
+            Ok(s) => {
+                self.pending_len = 0;
+                self.write_str(s)?;
+                Ok(take)
+            }
+            Err(e) if e.valid_up_to() > 0 => {
+                self.pending_len = 0;
+                // SAFETY: checked by `from_utf8`
+                self.write_str(unsafe { core::str::from_utf8_unchecked(&joined[..e.valid_up_to()]) })?;
+                Ok(e.valid_up_to() - held)
+            }
+            Err(e) if e.error_len().is_none() => {
+                self.pending = joined;
+                self.pending_len = held + take;
+                Ok(take)
+            }
+            Err(_) => Err(invalid()),
+        }
+    }
+}
+
+impl<F: core::fmt::Write + ?Sized> io::Write for Utf8Writer<'_, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        if self.pending_len > 0 {
+            let taken = self.complete_pending(rest)?;
+            rest = &rest[taken..];
+            if self.pending_len > 0 {
+                // all of `buf` went into the character, which is still short
+                return Ok(buf.len());
+            }
+        }
+        let (valid, tail) = match core::str::from_utf8(rest) {
+            Ok(s) => (s, &[][..]),
+            Err(e) if e.error_len().is_none() => {
+                let (valid, tail) = rest.split_at(e.valid_up_to());
+                // SAFETY: checked by `from_utf8`
+                (unsafe { core::str::from_utf8_unchecked(valid) }, tail)
+            }
+            Err(_) => return Err(invalid()),
+        };
+        self.write_str(valid)?;
+        self.pending[..tail.len()].copy_from_slice(tail);
+        self.pending_len = tail.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "message is not valid UTF-8")
+}
+
+#[test]
+fn split_characters() {
+    use std::io::Write;
+
+    let text = "naïve – 日本 🦀";
+    for split in 1..text.len() {
+        let mut out = String::new();
+        let mut w = Utf8Writer::new(&mut out);
+        for part in text.as_bytes().chunks(split) {
+            w.write_all(part).unwrap();
+        }
+        w.finish().unwrap();
+        assert_eq!(out, text, "split {split}");
+    }
+
+    let mut out = String::new();
+    let mut w = Utf8Writer::new(&mut out);
+    w.write_all(&"🦀".as_bytes()[..2]).unwrap();
+    assert_eq!(w.finish().unwrap_err().kind(), io::ErrorKind::InvalidData);
+
+    let mut w = Utf8Writer::new(&mut out);
+    w.write_all(&"é".as_bytes()[..1]).unwrap();
+    assert_eq!(w.write_all(b"x").unwrap_err().kind(), io::ErrorKind::InvalidData);
+    assert_eq!(Utf8Writer::new(&mut out).write_all(b"ok \xff").unwrap_err().kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn write_to_fmt() {
+    let mut out = String::from("> ");
+    let len = crate::Request::post()
+        .path("/caf%C3%A9")
+        .v1_1()
+        .header("X-Name", "Zoë".as_bytes())
+        .body("日本".as_bytes())
+        .write_to_fmt(&mut out)
+        .unwrap();
+    assert_eq!(out, "> POST /caf%C3%A9 HTTP/1.1\r\nX-Name: Zoë\r\nContent-Length: 6\r\n\r\n日本");
+    assert_eq!(len, out.len() - 2);
+
+    let mut out = String::new();
+    let err = crate::Request::get().path("/").v1_1().header("X-Bin", b"\xc3(").write_to_fmt(&mut out);
+    assert!(matches!(
+        err,
+        Err(crate::RequestWriteError::Io { ref source, .. }) if source.kind() == io::ErrorKind::InvalidData
+    ));
+
+    let mut out = String::new();
+    crate::Response::new(http::StatusCode::OK).v1_1().text("héllo").write_to_fmt(&mut out).unwrap();
+    assert_eq!(out, "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 6\r\n\r\nhéllo");
+    let err = crate::Response::new(http::StatusCode::OK).v1_1().body(b"\x80").write_to_fmt(&mut String::new());
+    assert!(matches!(
+        err,
+        Err(crate::ResponseWriteError::Io { ref source, .. }) if source.kind() == io::ErrorKind::InvalidData
+    ));
+}
@@ -0,0 +1,80 @@
+//! `application/x-www-form-urlencoded` bodies.
+
+use crate::{Body, BodyError, Framing};
+use std::io::{self, Write};
+
+/// Percent-encodes `bytes` the way HTML forms do, with `+` for spaces.
+pub(crate) fn encode(out: &mut String, bytes: &[u8]) {
+    for &ch in bytes {
+        match ch {
+            b' ' => out.push('+'),
+            b'*' | b'-' | b'.' | b'_' => out.push(ch as char),
+            _ if ch.is_ascii_alphanumeric() => out.push(ch as char),
+            _ => {
+                const HEX: &[u8; 16] = b"0123456789ABCDEF";
+                out.push('%');
+                out.push(HEX[usize::from(ch >> 4)] as char);
+                out.push(HEX[usize::from(ch & 0xf)] as char);
+            }
+        }
+    }
+}
+
+/// A form body built pair by pair, sent with
+/// [`Request::form`](crate::Request::form).
+///
+/// Each key and value is encoded as it is added, with `+` for spaces and
+/// everything but alphanumerics and `*-._` percent-encoded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FormBody {
+    encoded: String,
+}
+
+impl FormBody {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pair(mut self, key: &str, value: &str) -> Self {
+        if !self.encoded.is_empty() {
+            self.encoded.push('&');
+        }
+        encode(&mut self.encoded, key.as_bytes());
+        self.encoded.push('=');
+        encode(&mut self.encoded, value.as_bytes());
+        self
+    }
+
+    /// The body as it is written.
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+}
+
+impl<K: AsRef<str>, V: AsRef<str>> FromIterator<(K, V)> for FormBody {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(pairs: I) -> Self {
+        pairs.into_iter().fold(Self::new(), |form, (key, value)| form.pair(key.as_ref(), value.as_ref()))
+    }
+}
+
+impl Body for FormBody {
+    fn framing(&mut self) -> Result<Framing, BodyError> {
+        self.encoded.as_bytes().framing()
+    }
+
+    fn write_body<W: Write + ?Sized>(&mut self, w: &mut W) -> io::Result<usize> {
+        self.encoded.as_bytes().write_body(w)
+    }
+}
+
+#[test]
+fn form_body() {
+    let form = FormBody::new().pair("name", "John Doe").pair("city", "NYC");
+    assert_eq!(form.as_str(), "name=John+Doe&city=NYC");
+    let decoded: Vec<(String, String)> = serde_urlencoded::from_str(form.as_str()).unwrap();
+    assert_eq!(decoded, [("name".into(), "John Doe".into()), ("city".into(), "NYC".into())]);
+
+    let form: FormBody = [("q", "a&b=c"), ("emoji", "\u{e9}+/")].into_iter().collect();
+    assert_eq!(form.as_str(), "q=a%26b%3Dc&emoji=%C3%A9%2B%2F");
+    assert_eq!(FormBody::new().as_str(), "");
+}
@@ -0,0 +1,235 @@
+//! Headers and queries built apart from the message they end up in.
+
+use crate::query::{IntoQueries, Query};
+use crate::{EmptyHeaders, EmptyQueries, Field, IntoFields, Request, Response};
+use core::iter::{self, Chain, Once};
+use core::marker::PhantomData;
+
+/// Headers and queries without a method or path, for a layer to contribute
+/// to a request it does not build; see [`Request::extend_from`].
+///
+/// A fragment whose iterators are `Clone` is `Clone`, so it can be applied to
+/// any number of requests.
+#[derive(Clone)]
+pub struct RequestFragment<'a, T = EmptyHeaders<'a>, Q = EmptyQueries<'a>> {
+    headers: T,
+    queries: Q,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> RequestFragment<'a> {
+    pub fn new() -> Self {
+        Self {
+            headers: EmptyHeaders::new(),
+            queries: EmptyQueries::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl Default for RequestFragment<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T, Q> RequestFragment<'a, T, Q>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    pub fn header(self, name: &'a str, value: &'a [u8]) -> RequestFragment<'a, Chain<T, Once<Field<'a>>>, Q> {
+        self.fields(iter::once(Field::new(name, value)))
+    }
+
+    pub fn headers<H>(self, h: H) -> RequestFragment<'a, Chain<T, IntoFields<'a, H>>, Q>
+    where
+        H: IntoIterator,
+        H::Item: Into<Field<'a>>,
+    {
+        self.fields(h.into_iter().map(Into::into as fn(H::Item) -> Field<'a>))
+    }
+
+    fn fields<H: Iterator<Item = Field<'a>>>(self, h: H) -> RequestFragment<'a, Chain<T, H>, Q> {
+        RequestFragment {
+            headers: self.headers.chain(h),
+            queries: self.queries,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, Q> RequestFragment<'a, T, Q>
+where
+    Q: Iterator<Item = Query<'a>>,
+{
+    pub fn query(self, q: &'a str) -> RequestFragment<'a, T, Chain<Q, Once<Query<'a>>>> {
+        self.query_items(iter::once(Query::new(q)))
+    }
+
+    pub fn queries<Qs>(self, qs: Qs) -> RequestFragment<'a, T, Chain<Q, IntoQueries<'a, Qs>>>
+    where
+        Qs: IntoIterator,
+        Qs::Item: Into<Query<'a>>,
+    {
+        self.query_items(qs.into_iter().map(Into::into as fn(Qs::Item) -> Query<'a>))
+    }
+
+    fn query_items<Qs: Iterator<Item = Query<'a>>>(self, qs: Qs) -> RequestFragment<'a, T, Chain<Q, Qs>> {
+        RequestFragment {
+            headers: self.headers,
+            queries: self.queries.chain(qs),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, Q, V, B> Request<'a, T, Q, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    Q: Iterator<Item = Query<'a>>,
+{
+    /// Appends the headers and queries of `other` after those already added.
+    pub fn extend_from<T2, Q2>(
+        self,
+        other: RequestFragment<'a, T2, Q2>,
+    ) -> Request<'a, Chain<T, T2>, Chain<Q, Q2>, V, B>
+    where
+        T2: Iterator<Item = Field<'a>>,
+        Q2: Iterator<Item = Query<'a>>,
+    {
+        self.fields(other.headers).query_items(other.queries)
+    }
+}
+
+/// Headers without a status, for a layer to contribute to a response it
+/// does not build; see [`Response::extend_from`].
+///
+/// Like [`RequestFragment`], it is `Clone` when its iterator is.
+#[derive(Clone)]
+pub struct HeaderFragment<'a, T = EmptyHeaders<'a>> {
+    headers: T,
+    _marker: PhantomData<&'a ()>,
+}
+
+impl<'a> HeaderFragment<'a> {
+    pub fn new() -> Self {
+        Self {
+            headers: EmptyHeaders::new(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl Default for HeaderFragment<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, T> HeaderFragment<'a, T>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    pub fn header(self, name: &'a str, value: &'a [u8]) -> HeaderFragment<'a, Chain<T, Once<Field<'a>>>> {
+        self.fields(iter::once(Field::new(name, value)))
+    }
+
+    pub fn headers<H>(self, h: H) -> HeaderFragment<'a, Chain<T, IntoFields<'a, H>>>
+    where
+        H: IntoIterator,
+        H::Item: Into<Field<'a>>,
+    {
+        self.fields(h.into_iter().map(Into::into as fn(H::Item) -> Field<'a>))
+    }
+
+    fn fields<H: Iterator<Item = Field<'a>>>(self, h: H) -> HeaderFragment<'a, Chain<T, H>> {
+        HeaderFragment {
+            headers: self.headers.chain(h),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, T, V, B> Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Appends the headers of `other` after those already added.
+    pub fn extend_from<T2>(self, other: HeaderFragment<'a, T2>) -> Response<'a, Chain<T, T2>, V, B>
+    where
+        T2: Iterator<Item = Field<'a>>,
+    {
+        self.fields(other.headers)
+    }
+}
+
+#[cfg(test)]
+mod tracing_layer {
+    use super::*;
+
+    pub(super) fn fragment<'a>(
+        trace_id: &'a [u8],
+    ) -> RequestFragment<'a, impl Iterator<Item = Field<'a>> + Clone, impl Iterator<Item = Query<'a>> + Clone> {
+        RequestFragment::new().header("X-Trace-Id", trace_id).query("trace=1")
+    }
+}
+
+#[cfg(test)]
+mod auth_layer {
+    use super::*;
+
+    pub(super) fn fragment<'a>()
+    -> RequestFragment<'a, impl Iterator<Item = Field<'a>>, impl Iterator<Item = Query<'a>>> {
+        RequestFragment::new().header("Authorization", b"Bearer t0ken").query("key=abc").query("v=2")
+    }
+}
+
+#[cfg(test)]
+fn written<'a>(
+    mut req: Request<'a, impl Iterator<Item = Field<'a>>, impl Iterator<Item = Query<'a>>, crate::version::V1_1>,
+) -> String {
+    let mut buf = Vec::new();
+    req.write_to(&mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn fragments_merge_in_order() {
+    let req = Request::get()
+        .path("/items")
+        .query("page=2")
+        .v1_1()
+        .header("Host", b"example.com")
+        .extend_from(tracing_layer::fragment(b"abc123"))
+        .extend_from(auth_layer::fragment())
+        .header("Accept", b"*/*");
+    assert_eq!(
+        written(req),
+        "GET /items?page=2&trace=1&key=abc&v=2 HTTP/1.1\r\n\
+         Host: example.com\r\n\
+         X-Trace-Id: abc123\r\n\
+         Authorization: Bearer t0ken\r\n\
+         Accept: */*\r\n\r\n"
+    );
+}
+
+#[test]
+fn cloned_fragment_applies_twice() {
+    let fragment = tracing_layer::fragment(b"abc123");
+    let get = Request::get().path("/a").v1_1().extend_from(fragment.clone());
+    let post = Request::post().path("/b").v1_1().extend_from(fragment);
+    assert_eq!(written(get), "GET /a?trace=1 HTTP/1.1\r\nX-Trace-Id: abc123\r\n\r\n");
+    assert_eq!(written(post), "POST /b?trace=1 HTTP/1.1\r\nX-Trace-Id: abc123\r\n\r\n");
+
+    let vary = crate::Header { name: "Vary", value: b"Accept" };
+    let headers = HeaderFragment::new().header("Cache-Control", b"no-store").headers([vary]);
+    for code in [http::StatusCode::OK, http::StatusCode::NOT_FOUND] {
+        let mut buf = Vec::new();
+        Response::new(code).v1_1().extend_from(headers.clone()).write_to(&mut buf).unwrap();
+        let reason = code.canonical_reason().unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            format!("HTTP/1.1 {} {reason}\r\nCache-Control: no-store\r\nVary: Accept\r\n\r\n", code.as_str())
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! [`Arbitrary`] implementations and the generators behind them, for fuzzing
+//! code that builds or parses HTTP messages.
+//!
+//! The generators lean towards values `write_to` accepts, but mix in empty
+//! and very long values and the bytes at the edges of each character class.
+//! What holds for every generated value:
+//!
+//! - methods are valid tokens, since `write_to` writes them unchecked,
+//! - versions are `1.0` or `1.1`, or too long or short to be accepted.
+//!
+//! Paths, queries, header names and header values carry no such guarantee:
+//! they are untrusted, so `write_to` may reject them. Whatever it does
+//! accept is a well-formed message head.
+
+use crate::{Method, MethodProperties, OwnedRequest, Query};
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+const TCHAR_SPECIALS: &[u8] = b"!#$%&'*+-.^_`|~";
+const PCHAR_SPECIALS: &[u8] = b"-._~!$&'()*+,;=:@";
+/// Value bytes at the edges of the ranges a field value may hold.
+const VALUE_EDGES: &[u8] = b"\t \x21\x7e\x80\xff";
+
+/// How a generated string is shaped before its bytes are picked.
+fn len(u: &mut Unstructured<'_>) -> Result<usize> {
+    Ok(match u.int_in_range(0..=15u8)? {
+        0 => 0,
+        1 => u.int_in_range(1024..=4096)?,
+        _ => u.int_in_range(1..=24)?,
+    })
+}
+
+fn pick(u: &mut Unstructured<'_>, specials: &[u8]) -> Result<u8> {
+    Ok(match u.int_in_range(0..=3u8)? {
+        0 => *u.choose(specials)?,
+        1 => u.int_in_range(b'0'..=b'9')?,
+        2 => u.int_in_range(b'A'..=b'Z')?,
+        _ => u.int_in_range(b'a'..=b'z')?,
+    })
+}
+
+/// A `tchar` token of 1 to 4096 bytes.
+pub fn token(u: &mut Unstructured<'_>) -> Result<String> {
+    let len = len(u)?.max(1);
+    (0..len).map(|_| pick(u, TCHAR_SPECIALS).map(char::from)).collect()
+}
+
+/// Mostly `pchar`s and `%XX` escapes, and sometimes any string at all.
+fn pchars(u: &mut Unstructured<'_>, extra: &[u8]) -> Result<String> {
+    if u.ratio(1, 16)? {
+        return String::arbitrary(u);
+    }
+    let len = len(u)?;
+    let mut out = String::with_capacity(len);
+    for _ in 0..len {
+        match u.int_in_range(0..=15u8)? {
+            0 => out.push_str(&format!("%{:02X}", u8::arbitrary(u)?)),
+            1 if !extra.is_empty() => out.push(char::from(*u.choose(extra)?)),
+            _ => out.push(char::from(pick(u, PCHAR_SPECIALS)?)),
+        }
+    }
+    Ok(out)
+}
+
+/// An origin-form path: `/` and mostly valid segments.
+pub fn path(u: &mut Unstructured<'_>) -> Result<String> {
+    let mut path = String::from("/");
+    path.push_str(&pchars(u, b"/")?);
+    Ok(path)
+}
+
+/// A query segment, with the same bias as [`path`].
+pub fn query(u: &mut Unstructured<'_>) -> Result<String> {
+    pchars(u, b"/?=")
+}
+
+/// A header name, mostly of letters, digits, `-` and `_`, sometimes with
+/// other `tchar`s, and rarely any string at all.
+pub fn field_name(u: &mut Unstructured<'_>) -> Result<String> {
+    match u.int_in_range(0..=31u8)? {
+        0 => String::arbitrary(u),
+        1..=3 => token(u),
+        _ => {
+            let len = len(u)?.max(1);
+            (0..len).map(|_| pick(u, b"-_").map(char::from)).collect()
+        }
+    }
+}
+
+/// A header value of visible bytes, spaces and tabs, with a few obs-text
+/// bytes and, rarely, a CR, LF or NUL.
+pub fn field_value(u: &mut Unstructured<'_>) -> Result<Vec<u8>> {
+    let len = len(u)?;
+    let mut value = (0..len)
+        .map(|_| match u.int_in_range(0..=15u8)? {
+            0 => Ok(*u.choose(VALUE_EDGES)?),
+            _ => u.int_in_range(0x20..=0x7e),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if u.ratio(1, 32)? {
+        let pos = u.choose_index(value.len() + 1)?;
+        value.insert(pos, *u.choose(b"\r\n\0")?);
+    }
+    Ok(value)
+}
+
+impl<'a> Arbitrary<'a> for Method<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        const KNOWN: &[Method<'static>] = &[
+            Method::Get,
+            Method::Head,
+            Method::Post,
+            Method::Put,
+            Method::Delete,
+            Method::Connect,
+            Method::Options,
+            Method::Trace,
+            Method::Patch,
+            Method::Propfind,
+            Method::Proppatch,
+            Method::Mkcol,
+            Method::Copy,
+            Method::Move,
+            Method::Lock,
+            Method::Unlock,
+            Method::Report,
+            Method::Purge,
+            Method::Query,
+        ];
+        if u.ratio(3, 4)? {
+            return Ok(u.choose(KNOWN)?.clone());
+        }
+
+        // custom names borrow the longest run of tchars from the input
+        let bytes = u.peek_bytes(u.len().min(16)).unwrap_or_default();
+        let len = bytes.iter().take_while(|&&ch| crate::is_tchar(ch)).count();
+        let name = match len {
+            0 => return Ok(u.choose(KNOWN)?.clone()),
+            len => u.bytes(len)?,
+        };
+        // SAFETY: tchars are all ASCII
+        let name = unsafe { core::str::from_utf8_unchecked(name) };
+        Ok(match bool::arbitrary(u)? {
+            true => Method::Custom(name),
+            false => Method::custom_with_properties(
+                name,
+                MethodProperties {
+                    safe: bool::arbitrary(u)?,
+                    idempotent: bool::arbitrary(u)?,
+                    allows_body: bool::arbitrary(u)?,
+                },
+            ),
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for Query<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Query::owned(query(u)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for OwnedRequest {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let version = match u.ratio(1, 16)? {
+            true => *u.choose(&["", "1.10"])?,
+            false => *u.choose(&["1.1", "1.0"])?,
+        };
+        let queries = (0..u.int_in_range(0..=4)?).map(|_| query(u)).collect::<Result<_>>()?;
+        let headers = (0..u.int_in_range(0..=8)?)
+            .map(|_| Ok((field_name(u)?, field_value(u)?)))
+            .collect::<Result<_>>()?;
+
+        Ok(OwnedRequest {
+            method: Method::arbitrary(u)?.as_str().to_owned(),
+            path: path(u)?,
+            queries,
+            headers,
+            version: version.to_owned(),
+            body: Vec::arbitrary(u)?,
+        })
+    }
+}
+
+/// Fills `n` inputs of `len` bytes from a fixed xorshift seed.
+#[cfg(test)]
+fn inputs(n: usize, len: usize) -> impl Iterator<Item = Vec<u8>> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0..n).map(move |_| {
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+fn parses(buf: &[u8]) -> bool {
+    let mut headers = [httparse::EMPTY_HEADER; 64];
+    let mut req = httparse::Request::new(&mut headers);
+    matches!(req.parse(buf), Ok(httparse::Status::Complete(_)))
+}
+
+#[test]
+fn generated_requests_parse() {
+    let mut written = 0;
+    for data in inputs(1000, 4096) {
+        let mut u = Unstructured::new(&data);
+        let req = OwnedRequest::arbitrary(&mut u).unwrap();
+
+        let mut buf = Vec::new();
+        if req.write_to(&mut buf).is_ok() {
+            assert!(parses(&buf), "{req:?}");
+            written += 1;
+        }
+    }
+    // the bias towards valid values has to show
+    assert!(written > 250, "{written}");
+}
+
+#[test]
+fn generated_methods_and_queries_parse() {
+    for data in inputs(1000, 512) {
+        let mut u = Unstructured::new(&data);
+        let method = Method::arbitrary(&mut u).unwrap();
+        let queries = Vec::<Query<'_>>::arbitrary(&mut u).unwrap();
+
+        let mut buf = Vec::new();
+        let mut req = crate::Request::new(method).path("/").queries(queries).v1_1();
+        if req.write_to(&mut buf).is_ok() {
+            assert!(parses(&buf), "{}", String::from_utf8_lossy(&buf));
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Components that contribute headers without knowing the message type.
+
+use crate::{Field, Header, IntoFields, Request, Response};
+use core::iter::{Chain, Copied, Map};
+
+/// Something that has headers to add, such as an auth provider or a
+/// tracing layer; see [`Request::headers_from`].
+///
+/// The iterator is only driven once the message is written, so a source can
+/// compute its headers lazily.
+pub trait HeaderSource<'a> {
+    type Iter: Iterator<Item = Header<'a>>;
+
+    fn headers(&'a self) -> Self::Iter;
+}
+
+/// The object-safe form of [`HeaderSource`], implemented for every source.
+///
+/// `&dyn DynHeaderSource` is a [`HeaderSource`] itself; its headers are a
+/// boxed iterator, so adding them allocates once.
+pub trait DynHeaderSource<'a> {
+    fn dyn_headers(&'a self) -> Box<dyn Iterator<Item = Header<'a>> + 'a>;
+}
+
+impl<'a, S: HeaderSource<'a>> DynHeaderSource<'a> for S
+where
+    S::Iter: 'a,
+{
+    fn dyn_headers(&'a self) -> Box<dyn Iterator<Item = Header<'a>> + 'a> {
+        Box::new(self.headers())
+    }
+}
+
+impl<'a> HeaderSource<'a> for dyn DynHeaderSource<'a> + 'a {
+    type Iter = Box<dyn Iterator<Item = Header<'a>> + 'a>;
+
+    fn headers(&'a self) -> Self::Iter {
+        self.dyn_headers()
+    }
+}
+
+impl<'a> HeaderSource<'a> for [Header<'a>] {
+    type Iter = Copied<core::slice::Iter<'a, Header<'a>>>;
+
+    fn headers(&'a self) -> Self::Iter {
+        self.iter().copied()
+    }
+}
+
+impl<'a, const N: usize> HeaderSource<'a> for [Header<'a>; N] {
+    type Iter = Copied<core::slice::Iter<'a, Header<'a>>>;
+
+    fn headers(&'a self) -> Self::Iter {
+        self.iter().copied()
+    }
+}
+
+type MapEntry<'a> = (&'a http::HeaderName, &'a http::HeaderValue);
+
+// names are written as `http` stores them, in lowercase
+impl<'a> HeaderSource<'a> for http::HeaderMap {
+    type Iter = Map<http::header::Iter<'a, http::HeaderValue>, fn(MapEntry<'a>) -> Header<'a>>;
+
+    fn headers(&'a self) -> Self::Iter {
+        self.iter().map(|(name, value)| Header {
+            name: name.as_str(),
+            value: value.as_bytes(),
+        })
+    }
+}
+
+impl<'a, T, Q, V, B> Request<'a, T, Q, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Adds the headers of `source`, which are validated like any other when
+    /// the request is written.
+    pub fn headers_from<S: HeaderSource<'a> + ?Sized>(
+        self,
+        source: &'a S,
+    ) -> Request<'a, Chain<T, IntoFields<'a, S::Iter>>, Q, V, B> {
+        self.headers(source.headers())
+    }
+}
+
+impl<'a, T, V, B> Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Like [`Request::headers_from`].
+    pub fn headers_from<S: HeaderSource<'a> + ?Sized>(
+        self,
+        source: &'a S,
+    ) -> Response<'a, Chain<T, IntoFields<'a, S::Iter>>, V, B> {
+        self.headers(source.headers())
+    }
+}
+
+#[cfg(test)]
+struct Tracing {
+    trace_id: u32,
+    hex: core::cell::OnceCell<String>,
+}
+
+#[cfg(test)]
+impl<'a> HeaderSource<'a> for Tracing {
+    type Iter = core::iter::FromFn<Box<dyn FnMut() -> Option<Header<'a>> + 'a>>;
+
+    fn headers(&'a self) -> Self::Iter {
+        let mut done = false;
+        core::iter::from_fn(Box::new(move || {
+            if core::mem::replace(&mut done, true) {
+                return None;
+            }
+            let hex = self.hex.get_or_init(|| format!("{:08x}", self.trace_id));
+            Some(Header { name: "X-Trace-Id", value: hex.as_bytes() })
+        }))
+    }
+}
+
+#[cfg(test)]
+fn written<'a, T: Iterator<Item = Field<'a>>>(
+    mut req: Request<'a, T, crate::EmptyQueries<'a>, crate::version::V1_1>,
+) -> String {
+    let mut buf = Vec::new();
+    req.write_to(&mut buf).unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn lazy_source() {
+    let tracing = Tracing { trace_id: 0xbeef, hex: Default::default() };
+    let req = Request::get().path("/").v1_1().headers_from(&tracing);
+    assert!(tracing.hex.get().is_none(), "computed before the write");
+    assert_eq!(written(req), "GET / HTTP/1.1\r\nX-Trace-Id: 0000beef\r\n\r\n");
+}
+
+#[test]
+fn combined_sources() {
+    let auth = [Header { name: "Authorization", value: b"Bearer t0ken" }];
+    let mut map = http::HeaderMap::new();
+    map.insert(http::header::ACCEPT, http::HeaderValue::from_static("*/*"));
+    let req = Request::get().path("/").v1_1().headers_from(auth.as_slice()).headers_from(&map);
+    assert_eq!(written(req), "GET / HTTP/1.1\r\nAuthorization: Bearer t0ken\r\naccept: */*\r\n\r\n");
+
+    map.insert(http::header::CONTENT_LENGTH, http::HeaderValue::from_static("1"));
+    map.insert(http::header::TRANSFER_ENCODING, http::HeaderValue::from_static("chunked"));
+    let mut buf = Vec::new();
+    let err = Request::get().path("/").v1_1().headers_from(&map).write_to(&mut buf);
+    assert!(matches!(err, Err(crate::RequestWriteError::ConflictingFraming { buffer_offset: 0 })));
+    assert!(buf.is_empty());
+
+    let bad = [Header { name: "X-Bad", value: b"a\r\nb" }];
+    let err = Request::get().path("/").v1_1().headers_from(&bad).write_to(&mut Vec::new());
+    assert!(matches!(err, Err(crate::RequestWriteError::InvalidHeader { .. })));
+}
+
+#[test]
+fn dyn_source() {
+    let tracing = Tracing { trace_id: 1, hex: Default::default() };
+    let auth = [Header { name: "Authorization", value: b"Bearer t0ken" }];
+    let sources: [&dyn DynHeaderSource<'_>; 2] = [&tracing, &auth];
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::OK)
+        .v1_1()
+        .headers_from(sources[0])
+        .headers_from(sources[1])
+        .write_to(&mut buf)
+        .unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nX-Trace-Id: 00000001\r\nAuthorization: Bearer t0ken\r\n\r\n");
+}
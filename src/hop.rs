@@ -0,0 +1,147 @@
+//! Dropping the headers that only apply to one connection, for forwarding
+//! a message to the next hop.
+
+use crate::consts::names;
+use crate::{Field, Header, IntoFields, Request, Response};
+use core::iter::Chain;
+
+/// Removed on every hop, whether `Connection` names them or not.
+const HOP_BY_HOP: &[&str] = &[
+    names::CONNECTION,
+    "Keep-Alive",
+    "Proxy-Authenticate",
+    "Proxy-Authorization",
+    "Proxy-Connection",
+    "TE",
+    "Trailer",
+    names::TRANSFER_ENCODING,
+    names::UPGRADE,
+];
+
+/// The headers of a message minus those that are hop-by-hop, as returned
+/// by [`strip_hop_by_hop`].
+#[derive(Clone, Debug)]
+pub struct ForwardedHeaders<'a> {
+    all: &'a [Header<'a>],
+    rest: core::slice::Iter<'a, Header<'a>>,
+}
+
+/// `headers` without the standard hop-by-hop headers and those listed in
+/// any `Connection` header, in their original order.
+///
+/// Names are matched without regard to case, and the `Connection` tokens
+/// may be padded with whitespace. Nothing is allocated; each header is
+/// checked against the `Connection` values as it is reached.
+pub fn strip_hop_by_hop<'a>(headers: &'a [Header<'a>]) -> ForwardedHeaders<'a> {
+    ForwardedHeaders {
+        all: headers,
+        rest: headers.iter(),
+    }
+}
+
+impl<'a> ForwardedHeaders<'a> {
+    fn is_hop_by_hop(&self, name: &str) -> bool {
+        HOP_BY_HOP.iter().any(|h| h.eq_ignore_ascii_case(name))
+            || self
+                .all
+                .iter()
+                .filter(|h| h.name.eq_ignore_ascii_case(names::CONNECTION))
+                .flat_map(|h| h.value.split(|&ch| ch == b','))
+                .any(|token| token.trim_ascii().eq_ignore_ascii_case(name.as_bytes()))
+    }
+}
+
+impl<'a> Iterator for ForwardedHeaders<'a> {
+    type Item = Header<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let header = *self.rest.next()?;
+            if !self.is_hop_by_hop(header.name) {
+                return Some(header);
+            }
+        }
+    }
+}
+
+impl<'a, T, Q, V, B> Request<'a, T, Q, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Adds the headers of a received message that are meant for the next
+    /// hop, per [`strip_hop_by_hop`].
+    pub fn forwarded_headers(
+        self,
+        headers: &'a [Header<'a>],
+    ) -> Request<'a, Chain<T, IntoFields<'a, ForwardedHeaders<'a>>>, Q, V, B> {
+        self.headers(strip_hop_by_hop(headers))
+    }
+}
+
+impl<'a, T, V, B> Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Like [`Request::forwarded_headers`].
+    pub fn forwarded_headers(
+        self,
+        headers: &'a [Header<'a>],
+    ) -> Response<'a, Chain<T, IntoFields<'a, ForwardedHeaders<'a>>>, V, B> {
+        self.headers(strip_hop_by_hop(headers))
+    }
+}
+
+#[cfg(test)]
+fn names<'a>(headers: impl Iterator<Item = Header<'a>>) -> Vec<&'a str> {
+    headers.map(|h| h.name).collect()
+}
+
+#[cfg(test)]
+const fn header<'a>(name: &'a str, value: &'a [u8]) -> Header<'a> {
+    Header { name, value }
+}
+
+#[test]
+fn connection_names_headers() {
+    let headers = [
+        header("Host", b"example.com"),
+        header("connection", b" close ,X-Session-Hint,, x-debug "),
+        header("X-Session-Hint", b"1"),
+        header("Accept", b"*/*"),
+        header("X-DEBUG", b"on"),
+        header("Connection", b"Foo"),
+        header("foo", b"bar"),
+    ];
+    assert_eq!(names(strip_hop_by_hop(&headers)), ["Host", "Accept"]);
+}
+
+#[test]
+fn standard_set() {
+    let headers = [
+        header("Keep-Alive", b"timeout=5"),
+        header("Accept", b"*/*"),
+        header("proxy-connection", b"keep-alive"),
+        header("TE", b"trailers"),
+        header("Trailer", b"Expires"),
+        header("Transfer-Encoding", b"chunked"),
+        header("Upgrade", b"websocket"),
+        header("Proxy-Authorization", b"Basic abc"),
+        header("Proxy-Authenticate", b"Basic"),
+        header("Content-Type", b"text/plain"),
+        header("X-Forwarded-For", b"10.0.0.1"),
+        header("Authorization", b"Bearer t0ken"),
+    ];
+    assert_eq!(names(strip_hop_by_hop(&headers)), ["Accept", "Content-Type", "X-Forwarded-For", "Authorization"]);
+
+    let mut buf = Vec::new();
+    Request::get().path("/").v1_1().header("Via", b"1.1 proxy").forwarded_headers(&headers).write_to(&mut buf).unwrap();
+    assert_eq!(
+        String::from_utf8(buf).unwrap(),
+        "GET / HTTP/1.1\r\n\
+         Via: 1.1 proxy\r\n\
+         Accept: */*\r\n\
+         Content-Type: text/plain\r\n\
+         X-Forwarded-For: 10.0.0.1\r\n\
+         Authorization: Bearer t0ken\r\n\r\n"
+    );
+}
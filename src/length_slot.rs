@@ -0,0 +1,142 @@
+//! A `Content-Length` written before the body size is known, and patched
+//! in place once it is.
+
+use crate::{Field, Response, ResponseWriteError, Version};
+use core::ops::Range;
+
+/// The placeholder `Content-Length` value left by
+/// [`Response::write_to_vec_with_length_slot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthSlot {
+    start: usize,
+    width: u8,
+}
+
+/// A length [`LengthSlot::fill`] could not write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthSlotError {
+    /// The length has more digits than the slot holds.
+    TooLong,
+    /// The buffer no longer reaches the end of the slot.
+    OutOfBounds,
+}
+
+impl core::fmt::Display for LengthSlotError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TooLong => f.write_str("length does not fit in the slot"),
+            Self::OutOfBounds => f.write_str("buffer does not reach the end of the slot"),
+        }
+    }
+}
+
+impl std::error::Error for LengthSlotError {}
+
+impl LengthSlot {
+    /// The widest slot, enough for any `u64`.
+    pub const MAX_WIDTH: u8 = 20;
+
+    /// Where the digits of the value are in the buffer.
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.start + self.width as usize
+    }
+
+    /// Writes `len` into the slot, right-aligned with leading zeros, which
+    /// parsers read as the same number. On error the slot is left as it was.
+    pub fn fill(&self, buf: &mut [u8], len: u64) -> Result<(), LengthSlotError> {
+        let slot = buf.get_mut(self.range()).ok_or(LengthSlotError::OutOfBounds)?;
+        if 10u64.checked_pow(self.width.into()).is_some_and(|limit| len >= limit) {
+            return Err(LengthSlotError::TooLong);
+        }
+        let mut n = len;
+        for digit in slot.iter_mut().rev() {
+            *digit = b'0' + (n % 10) as u8;
+            n /= 10;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T, V> Response<'a, T, V>
+where
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+{
+    /// Appends the head to `buf` with a `Content-Length` of zeros to
+    /// [`LengthSlot::fill`] once the body that follows it has been generated.
+    ///
+    /// The headers must not frame the body themselves.
+    pub fn write_to_vec_with_length_slot(&mut self, buf: &mut Vec<u8>) -> Result<LengthSlot, ResponseWriteError> {
+        self.write_to_vec_with_length_slot_width(buf, LengthSlot::MAX_WIDTH)
+    }
+
+    /// Like [`Response::write_to_vec_with_length_slot`], with room for
+    /// `width` digits, clamped to `1..=20`.
+    pub fn write_to_vec_with_length_slot_width(
+        &mut self,
+        buf: &mut Vec<u8>,
+        width: u8,
+    ) -> Result<LengthSlot, ResponseWriteError> {
+        let width = width.clamp(1, LengthSlot::MAX_WIDTH);
+        let head = buf.len();
+        if let Err(err) = self.write_to(buf) {
+            buf.truncate(head);
+            return Err(err);
+        }
+        // the head ends in the empty line, which goes after the slot
+        buf.truncate(buf.len() - 2);
+        buf.extend_from_slice(b"Content-Length: ");
+        let start = buf.len();
+        buf.resize(start + width as usize, b'0');
+        buf.extend_from_slice(b"\r\n\r\n");
+        Ok(LengthSlot { start, width })
+    }
+}
+
+#[cfg(test)]
+fn response() -> Response<'static, impl Iterator<Item = Field<'static>>, crate::version::V1_1> {
+    Response::new(http::StatusCode::OK).v1_1().header("Content-Type", b"text/plain")
+}
+
+#[test]
+fn fill_then_parse() {
+    for body in [b"".as_slice(), b"hello", &[b'x'; 100_000]] {
+        let mut buf = Vec::new();
+        let slot = response().write_to_vec_with_length_slot(&mut buf).unwrap();
+        let head_len = buf.len();
+        buf.extend_from_slice(body);
+        slot.fill(&mut buf, body.len() as u64).unwrap();
+
+        let mut headers = [httparse::EMPTY_HEADER; 4];
+        let mut res = httparse::Response::new(&mut headers);
+        assert_eq!(res.parse(&buf).unwrap(), httparse::Status::Complete(head_len));
+        let len = res.headers.iter().find(|h| h.name == "Content-Length").unwrap().value;
+        assert_eq!(len.len(), 20);
+        assert_eq!(core::str::from_utf8(len).unwrap().parse::<u64>().unwrap(), body.len() as u64);
+        assert_eq!(&buf[head_len..], body);
+    }
+
+    let mut buf = b"earlier message".to_vec();
+    let slot = response().write_to_vec_with_length_slot(&mut buf).unwrap();
+    slot.fill(&mut buf, u64::MAX).unwrap();
+    assert_eq!(&buf[slot.range()], b"18446744073709551615");
+    assert!(buf.starts_with(b"earlier messageHTTP/1.1 200 OK\r\n"));
+    assert!(buf.ends_with(b"Content-Length: 18446744073709551615\r\n\r\n"));
+}
+
+#[test]
+fn fill_errors() {
+    let mut buf = Vec::new();
+    let slot = response().write_to_vec_with_length_slot_width(&mut buf, 4).unwrap();
+    assert_eq!(slot.fill(&mut buf, 9999), Ok(()));
+    assert!(buf.ends_with(b"Content-Length: 9999\r\n\r\n"));
+    assert_eq!(slot.fill(&mut buf, 10_000), Err(LengthSlotError::TooLong));
+    assert_eq!(&buf[slot.range()], b"9999");
+    assert_eq!(slot.fill(&mut buf[..slot.range().end - 1], 1), Err(LengthSlotError::OutOfBounds));
+
+    let mut buf = b"kept".to_vec();
+    let mut bad = Response::new(http::StatusCode::OK).v1_1().header("Bad Name", b"x");
+    let err = bad.write_to_vec_with_length_slot(&mut buf);
+    assert!(matches!(err, Err(ResponseWriteError::InvalidHeader { .. })));
+    assert_eq!(buf, b"kept");
+}
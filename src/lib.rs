@@ -1,62 +1,293 @@
+// `request_with_query` finds its '?' with a closure
+#![cfg_attr(test, allow(clippy::manual_pattern_char_comparison))]
+
+mod accept;
+pub use accept::choose_encoding;
+#[cfg(feature = "tokio")]
+mod async_io;
 mod response;
 pub use http::StatusCode;
-pub use response::{Response, ResponseWriteError};
+pub use response::{BasicResponse, InvalidStatusCode, Response, ResponseWriteError};
+mod body;
+pub use body::{Body, BodyError, ChunkedBody, Framing, NoBody, ReaderBody};
+#[cfg(feature = "json")]
+pub use body::{Json, JsonStream};
+#[cfg(feature = "bytes")]
+mod bytes_body;
+#[cfg(feature = "bytes")]
+pub use bytes_body::BytesBody;
+mod cache;
+pub use cache::{CacheHeaders, CachePolicy};
+mod canonical;
+pub use canonical::CANONICAL_VERSION;
+#[cfg(feature = "date")]
+mod conditional;
+#[cfg(feature = "date")]
+pub use conditional::{ConditionalOutcome, ResourceMeta, Validators};
+#[cfg(feature = "date")]
+pub mod date;
+#[cfg(feature = "date")]
+pub use date::DateCache;
+#[cfg(any(feature = "serde", feature = "base64"))]
+mod base64;
+#[cfg(feature = "base64")]
+pub use base64::Base64Alphabet;
+pub mod consts;
+mod cookie;
+pub use cookie::{SameSite, SetCookie};
+mod counting;
+pub use counting::CountingWriter;
+mod csp;
+pub use csp::{Csp, CspError, Source};
+mod encode;
+pub use encode::{EncodeError, HttpEncode};
+mod etag;
+pub use etag::{ETag, InvalidETag};
+mod field;
+mod fmt_write;
+mod form;
+pub use form::FormBody;
+mod fragment;
+pub use fragment::{HeaderFragment, RequestFragment};
+mod header_source;
+pub use header_source::{DynHeaderSource, HeaderSource};
+mod hop;
+pub use hop::{ForwardedHeaders, strip_hop_by_hop};
+#[cfg(feature = "date")]
+mod file;
+#[cfg(feature = "date")]
+pub use file::{FileETag, FileHeaders, FileMeta, guess_content_type};
+pub use field::{Field, IntoFields, ValidatedHeader};
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+mod length_slot;
+pub use length_slot::{LengthSlot, LengthSlotError};
+mod method;
+#[cfg(all(feature = "net", unix))]
+pub mod net;
+pub use method::{InvalidMethod, Method, MethodProperties};
+#[cfg(test)]
+mod no_alloc;
+mod origin;
+pub use origin::{Origin, RefererFragment};
+mod owned;
+pub use owned::OwnedRequest;
+mod path;
+pub use path::{EncodedPath, InvalidPath};
+mod query;
+pub use query::{EncodedQuery, InvalidQuery, IntoQueries, Query, QueryPairs, QuerySeparator};
+mod redirect;
+pub use redirect::{RedirectError, RedirectPolicy, follow_redirect, follow_redirect_with};
 mod request;
-pub use request::{Method, Request, RequestWriteError};
+mod respond;
+pub use respond::ResponseContext;
+#[cfg(feature = "sigv4")]
+pub mod sigv4;
+mod server_timing;
+pub use server_timing::{Metric, ServerTiming};
+mod static_response;
+pub use request::{BasicRequest, PreflightHeaders, Request, RequestWriteError};
+#[cfg(feature = "serde")]
+pub use request::SerializedQueries;
+#[cfg(feature = "serde")]
+mod template;
+#[cfg(feature = "serde")]
+pub use template::{RequestTemplate, TemplateBody, TemplateError};
+mod target;
+mod tee;
+mod trace;
+/// Why a value could not be urlencoded.
+#[cfg(feature = "serde")]
+pub use serde_urlencoded::ser::Error as UrlencodedError;
+pub use tee::TeeWriter;
+mod vectored;
 pub mod version;
 use core::marker::PhantomData;
 pub use httparse::Header;
 pub use version::Version;
 
-#[derive(Debug, PartialEq, Eq)]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::static_response::{static_bytes, static_len};
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum HeaderWriteError {
     InvalidName(usize),
     InvalidValue(usize),
+    /// A raw header line has no `name:` prefix or holds a stray CR, LF or
+    /// NUL, at the given position in the line.
+    InvalidRawLine(usize),
     Io,
 }
 
+impl core::fmt::Display for HeaderWriteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidName(pos) => write!(f, "invalid header name at byte {pos}"),
+            Self::InvalidValue(pos) => write!(f, "invalid header value at byte {pos}"),
+            Self::InvalidRawLine(pos) => write!(f, "malformed raw header line at byte {pos}"),
+            Self::Io => f.write_str("I/O error while writing a header"),
+        }
+    }
+}
+
+impl std::error::Error for HeaderWriteError {}
+
 impl From<std::io::Error> for HeaderWriteError {
     fn from(_: std::io::Error) -> HeaderWriteError {
         HeaderWriteError::Io
     }
 }
 
-pub(crate) fn write_header<W: std::io::Write>(
-    w: &mut W,
-    header: Header<'_>,
-) -> Result<usize, HeaderWriteError> {
-    if let Some(pos) = header
-        .name
-        .as_bytes()
-        .iter()
-        .position(|ch| !(ch.is_ascii_alphanumeric() || matches!(ch, b'-' | b'_')))
-    {
-        return Err(HeaderWriteError::InvalidName(pos));
-    } else if let Some(pos) = header
-        .value
-        .iter()
-        .position(|ch| matches!(ch, b'\r' | b'\n' | b'\0'))
-    {
-        return Err(HeaderWriteError::InvalidValue(pos));
-    }
-    // SAFETY: header is valid
-    Ok(unsafe { write_header_unchecked(w, header)? })
-}
-
-pub(crate) unsafe fn write_header_unchecked<W: std::io::Write>(
+pub(crate) const fn check_header(name: &[u8], value: &[u8]) -> Result<(), HeaderWriteError> {
+    if name.is_empty() {
+        return Err(HeaderWriteError::InvalidName(0));
+    }
+
+    let mut i = 0;
+    while i < name.len() {
+        let ch = name[i];
+        if !is_tchar(ch) {
+            return Err(HeaderWriteError::InvalidName(i));
+        }
+        i += 1;
+    }
+
+    let mut i = 0;
+    while i < value.len() {
+        if matches!(value[i], b'\r' | b'\n' | b'\0') {
+            return Err(HeaderWriteError::InvalidValue(i));
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+/// Returns the position of the first byte that is not a `tchar` (RFC 9110,
+/// section 5.6.2), or of the end if `token` is empty.
+pub(crate) fn invalid_token_pos(token: &[u8]) -> Option<usize> {
+    if token.is_empty() {
+        return Some(0);
+    }
+    token.iter().position(|&ch| !is_tchar(ch))
+}
+
+/// Returns the position of the first byte that breaks `token68` (RFC 9110,
+/// section 11.2): `ALPHA / DIGIT / "-" / "." / "_" / "~" / "+" / "/"`, then
+/// only `=` padding. An empty `token` fails at 0.
+pub(crate) fn invalid_token68_pos(token: &[u8]) -> Option<usize> {
+    let is_body = |ch: &u8| ch.is_ascii_alphanumeric() || matches!(ch, b'-' | b'.' | b'_' | b'~' | b'+' | b'/');
+    let end = token.iter().position(|ch| !is_body(ch)).unwrap_or(token.len());
+    if end == 0 {
+        return Some(0);
+    }
+    token[end..].iter().position(|&ch| ch != b'=').map(|pos| end + pos)
+}
+
+pub(crate) const fn is_tchar(ch: u8) -> bool {
+    ch.is_ascii_alphanumeric()
+        || matches!(
+            ch,
+            b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+        )
+}
+
+pub(crate) unsafe fn write_header_unchecked<'v, W: std::io::Write + ?Sized>(
     w: &mut W,
-    header: Header<'_>,
+    name: &str,
+    value: impl IntoIterator<Item = &'v [u8]>,
 ) -> std::io::Result<usize> {
-    let mut len = 0;
-    len += w.write(header.name.as_bytes())?;
-    write!(w, ": ")?;
-    len += 2;
-    len += w.write(header.value)?;
-    write!(w, "\r\n")?;
-    len += 2;
-    Ok(len)
+    w.write_all(name.as_bytes())?;
+    w.write_all(b": ")?;
+    let mut len = name.len() + 2;
+    for part in value {
+        w.write_all(part)?;
+        len += part.len();
+    }
+    w.write_all(b"\r\n")?;
+    Ok(len + 2)
+}
+
+/// Counts the bytes a writer accepts, so errors can report them.
+pub(crate) struct Counter<'w, W: ?Sized> {
+    inner: &'w mut W,
+    pub(crate) written: usize,
+}
+
+impl<'w, W: ?Sized> Counter<'w, W> {
+    pub(crate) fn new(inner: &'w mut W) -> Self {
+        Self { inner, written: 0 }
+    }
+}
+
+impl<W: std::io::Write + ?Sized> std::io::Write for Counter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.written += n;
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[std::io::IoSlice<'_>]) -> std::io::Result<usize> {
+        let n = self.inner.write_vectored(bufs)?;
+        self.written += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The longest head [`Stage`] holds on the stack.
+const STAGE_LEN: usize = 4096;
+
+/// Holds a head until every check of it passed, so a message that fails one
+/// writes nothing. Past [`STAGE_LEN`] bytes it moves to the heap.
+pub(crate) struct Stage {
+    buf: [u8; STAGE_LEN],
+    len: usize,
+    spilled: Vec<u8>,
+}
+
+impl Stage {
+    pub(crate) fn new() -> Self {
+        Self {
+            buf: [0; STAGE_LEN],
+            len: 0,
+            spilled: Vec::new(),
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        if self.spilled.is_empty() {
+            &self.buf[..self.len]
+        } else {
+            &self.spilled
+        }
+    }
+}
+
+impl std::io::Write for Stage {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.spilled.is_empty() {
+            if let Some(room) = self.buf.get_mut(self.len..self.len + data.len()) {
+                room.copy_from_slice(data);
+                self.len += data.len();
+                return Ok(data.len());
+            }
+            self.spilled.extend_from_slice(&self.buf[..self.len]);
+        }
+        self.spilled.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
 }
 
+#[derive(Clone)]
 pub struct EmptyHeaders<'a>(PhantomData<&'a ()>);
 
 impl<'a> EmptyHeaders<'a> {
@@ -66,13 +297,14 @@ impl<'a> EmptyHeaders<'a> {
 }
 
 impl<'a> Iterator for EmptyHeaders<'a> {
-    type Item = Header<'a>;
+    type Item = Field<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         None
     }
 }
 
+#[derive(Clone)]
 pub struct EmptyQueries<'a>(PhantomData<&'a ()>);
 
 impl<'a> EmptyQueries<'a> {
@@ -82,7 +314,7 @@ impl<'a> EmptyQueries<'a> {
 }
 
 impl<'a> Iterator for EmptyQueries<'a> {
-    type Item = crate::request::Query<'a>;
+    type Item = Query<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         None
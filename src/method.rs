@@ -0,0 +1,295 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Method<'a> {
+    Get,
+    Head,
+    Post,
+    Put,
+    Delete,
+    Connect,
+    Options,
+    Trace,
+    Patch,
+    // WebDAV (RFC 4918) and its extensions
+    Propfind,
+    Proppatch,
+    Mkcol,
+    Copy,
+    Move,
+    Lock,
+    Unlock,
+    Report,
+    /// Cache invalidation, as understood by Varnish, Squid and most CDNs.
+    Purge,
+    /// The safe method with a body from draft-ietf-httpbis-safe-method-w-body.
+    Query,
+    Custom(&'a str),
+    /// A custom method whose semantics are known to the caller.
+    Extension {
+        name: &'a str,
+        properties: MethodProperties,
+    },
+}
+
+impl<'a> Method<'a> {
+    /// The method token as it appears in the request line.
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            Self::Get => "GET",
+            Self::Head => "HEAD",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Delete => "DELETE",
+            Self::Connect => "CONNECT",
+            Self::Options => "OPTIONS",
+            Self::Trace => "TRACE",
+            Self::Patch => "PATCH",
+            Self::Propfind => "PROPFIND",
+            Self::Proppatch => "PROPPATCH",
+            Self::Mkcol => "MKCOL",
+            Self::Copy => "COPY",
+            Self::Move => "MOVE",
+            Self::Lock => "LOCK",
+            Self::Unlock => "UNLOCK",
+            Self::Report => "REPORT",
+            Self::Purge => "PURGE",
+            Self::Query => "QUERY",
+            Self::Custom(c) => c,
+            Self::Extension { name, .. } => name,
+        }
+    }
+
+    /// Maps a request-line method token onto its variant.
+    ///
+    /// Known methods are matched case-sensitively, as method names are; any
+    /// other valid token becomes a [`Method::Custom`].
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, InvalidMethod> {
+        let method = match bytes {
+            b"GET" => Self::Get,
+            b"HEAD" => Self::Head,
+            b"POST" => Self::Post,
+            b"PUT" => Self::Put,
+            b"DELETE" => Self::Delete,
+            b"CONNECT" => Self::Connect,
+            b"OPTIONS" => Self::Options,
+            b"TRACE" => Self::Trace,
+            b"PATCH" => Self::Patch,
+            b"PROPFIND" => Self::Propfind,
+            b"PROPPATCH" => Self::Proppatch,
+            b"MKCOL" => Self::Mkcol,
+            b"COPY" => Self::Copy,
+            b"MOVE" => Self::Move,
+            b"LOCK" => Self::Lock,
+            b"UNLOCK" => Self::Unlock,
+            b"REPORT" => Self::Report,
+            b"PURGE" => Self::Purge,
+            b"QUERY" => Self::Query,
+            _ => {
+                if let Some(pos) = crate::invalid_token_pos(bytes) {
+                    return Err(InvalidMethod(pos));
+                }
+                // SAFETY: tchars are all ASCII
+                Self::Custom(unsafe { core::str::from_utf8_unchecked(bytes) })
+            }
+        };
+        Ok(method)
+    }
+
+    /// A custom method that answers the semantics queries with `properties`.
+    pub fn custom_with_properties(name: &'a str, properties: MethodProperties) -> Self {
+        Self::Extension { name, properties }
+    }
+
+    /// The request semantics of this method.
+    ///
+    /// [`Method::Custom`] gets the conservative answer: neither safe nor
+    /// idempotent, and allowed to carry a body.
+    pub fn properties(&self) -> MethodProperties {
+        const fn props(safe: bool, idempotent: bool, allows_body: bool) -> MethodProperties {
+            MethodProperties {
+                safe,
+                idempotent,
+                allows_body,
+            }
+        }
+
+        match self {
+            Self::Get | Self::Head => props(true, true, false),
+            Self::Options => props(true, true, true),
+            Self::Trace => props(true, true, false),
+            Self::Put => props(false, true, true),
+            Self::Delete => props(false, true, false),
+            Self::Post | Self::Patch => props(false, false, true),
+            Self::Connect => props(false, false, false),
+            Self::Propfind | Self::Report | Self::Query => props(true, true, true),
+            Self::Proppatch | Self::Mkcol | Self::Copy | Self::Move => props(false, true, true),
+            Self::Lock => props(false, false, true),
+            Self::Unlock | Self::Purge => props(false, true, false),
+            Self::Custom(_) => props(false, false, true),
+            Self::Extension { properties, .. } => *properties,
+        }
+    }
+
+    /// Whether the method is read-only (RFC 9110, section 9.2.1).
+    pub fn is_safe(&self) -> bool {
+        self.properties().safe
+    }
+
+    /// Whether repeating the request has the same effect as sending it once
+    /// (RFC 9110, section 9.2.2), which makes it safe to retry.
+    pub fn is_idempotent(&self) -> bool {
+        self.properties().idempotent
+    }
+
+    /// Whether a body has defined semantics for this method.
+    pub fn allows_body(&self) -> bool {
+        self.properties().allows_body
+    }
+}
+
+impl core::fmt::Display for Method<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for Method<'_> {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Method<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl<'a> TryFrom<&'a [u8]> for Method<'a> {
+    type Error = InvalidMethod;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, InvalidMethod> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl<'a> TryFrom<&'a str> for Method<'a> {
+    type Error = InvalidMethod;
+
+    fn try_from(s: &'a str) -> Result<Self, InvalidMethod> {
+        Self::from_bytes(s.as_bytes())
+    }
+}
+
+/// The method is not a valid token; the byte at the given position is not a
+/// `tchar`, or the method is empty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidMethod(pub usize);
+
+impl core::fmt::Display for InvalidMethod {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid method at byte {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidMethod {}
+
+/// What a method promises about its requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MethodProperties {
+    pub safe: bool,
+    pub idempotent: bool,
+    pub allows_body: bool,
+}
+
+#[test]
+fn builtin_properties() {
+    let cases = [
+        (Method::Get, true, true, false),
+        (Method::Head, true, true, false),
+        (Method::Post, false, false, true),
+        (Method::Put, false, true, true),
+        (Method::Delete, false, true, false),
+        (Method::Connect, false, false, false),
+        (Method::Options, true, true, true),
+        (Method::Trace, true, true, false),
+        (Method::Patch, false, false, true),
+        (Method::Custom("FOO"), false, false, true),
+    ];
+
+    for (method, safe, idempotent, allows_body) in cases {
+        assert_eq!(method.is_safe(), safe, "{method:?}");
+        assert_eq!(method.is_idempotent(), idempotent, "{method:?}");
+        assert_eq!(method.allows_body(), allows_body, "{method:?}");
+    }
+}
+
+#[test]
+fn webdav_properties() {
+    let cases = [
+        (Method::Propfind, "PROPFIND", true, true, true),
+        (Method::Proppatch, "PROPPATCH", false, true, true),
+        (Method::Mkcol, "MKCOL", false, true, true),
+        (Method::Copy, "COPY", false, true, true),
+        (Method::Move, "MOVE", false, true, true),
+        (Method::Lock, "LOCK", false, false, true),
+        (Method::Unlock, "UNLOCK", false, true, false),
+        (Method::Report, "REPORT", true, true, true),
+        (Method::Purge, "PURGE", false, true, false),
+        (Method::Query, "QUERY", true, true, true),
+    ];
+
+    for (method, name, safe, idempotent, allows_body) in cases {
+        assert_eq!(method.as_str(), name);
+        assert_eq!(Method::from_bytes(name.as_bytes()), Ok(method.clone()));
+        assert_eq!(method.is_safe(), safe, "{method:?}");
+        assert_eq!(method.is_idempotent(), idempotent, "{method:?}");
+        assert_eq!(method.allows_body(), allows_body, "{method:?}");
+    }
+}
+
+#[test]
+fn from_bytes() {
+    assert_eq!(Method::from_bytes(b"GET"), Ok(Method::Get));
+    assert_eq!(Method::from_bytes(b"get"), Ok(Method::Custom("get")));
+    assert_eq!(Method::from_bytes(b"BREW"), Ok(Method::Custom("BREW")));
+    assert_eq!(Method::from_bytes(b""), Err(InvalidMethod(0)));
+    assert_eq!(Method::from_bytes(b"GE T"), Err(InvalidMethod(2)));
+    assert_eq!(Method::from_bytes(b"GET\r\n"), Err(InvalidMethod(3)));
+}
+
+#[test]
+fn try_from() {
+    assert_eq!(Method::try_from("DELETE"), Ok(Method::Delete));
+    assert_eq!(Method::try_from("M-SEARCH"), Ok(Method::Custom("M-SEARCH")));
+    assert_eq!(Method::try_from(&b"PATCH"[..]), Ok(Method::Patch));
+    assert_eq!(Method::try_from("GET /"), Err(InvalidMethod(3)));
+    assert_eq!(Method::try_from("P\u{f6}ST"), Err(InvalidMethod(1)));
+}
+
+#[test]
+fn display() {
+    assert_eq!(Method::Get.to_string(), "GET");
+    assert_eq!(format!("{} /", Method::Custom("BREW")), "BREW /");
+    assert_eq!(Method::custom_with_properties("SYNC", MethodProperties { safe: true, idempotent: true, allows_body: false }).as_str(), "SYNC");
+    assert!(Method::Options == "OPTIONS");
+    assert!(Method::Options == *"OPTIONS");
+    assert!(Method::Get != "get");
+}
+
+#[test]
+fn custom_with_properties() {
+    let purge = Method::custom_with_properties(
+        "PURGE",
+        MethodProperties {
+            safe: false,
+            idempotent: true,
+            allows_body: false,
+        },
+    );
+
+    assert_eq!(purge.as_str(), "PURGE");
+    assert!(!purge.is_safe());
+    assert!(purge.is_idempotent());
+    assert!(!purge.allows_body());
+}
+
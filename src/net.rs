@@ -0,0 +1,524 @@
+//! Writing to sockets with the help of the operating system: corking and
+//! `sendfile`.
+
+use crate::consts::names;
+use crate::field::Inline;
+use crate::{Field, Response, Version};
+use core::ops::Range;
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::{AsRawFd, RawFd};
+
+/// Writes `res` with `body` and its `Content-Length` with the socket
+/// corked, so that the head leaves in the same segment as the start of the
+/// body instead of in one of its own. A response that sends no body only
+/// announces it, like [`Response::to_head_request`].
+///
+/// On Linux, `TCP_CORK` is set for the writes and put back to what it was
+/// afterwards, on error too. A socket that cannot be corked, such as a Unix
+/// socket, and every other target are written to without it.
+pub fn write_corked<'a, S, T, V>(sock: &mut S, res: Response<'a, T, V>, body: &[u8]) -> io::Result<usize>
+where
+    S: AsRawFd + Write,
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+{
+    #[cfg(target_os = "linux")]
+    let mut cork = Cork(sock.as_raw_fd());
+    #[cfg(not(target_os = "linux"))]
+    let mut cork = NoCork;
+    corked(&mut cork, || Ok(res.set_body(body).write_to(sock)?))
+}
+
+/// The `TCP_CORK` option of a socket.
+trait CorkOpt {
+    fn get(&mut self) -> io::Result<bool>;
+    fn set(&mut self, on: bool) -> io::Result<()>;
+}
+
+/// Runs `f` with `opt` set, restoring it once `f` returns. An option that
+/// cannot be read is left alone.
+fn corked<C: CorkOpt, R>(opt: &mut C, f: impl FnOnce() -> io::Result<R>) -> io::Result<R> {
+    let Ok(was_on) = opt.get() else {
+        return f();
+    };
+    if !was_on {
+        opt.set(true)?;
+    }
+    let out = f();
+    if !was_on {
+        let restored = opt.set(false);
+        // an error of the writes matters more than one of the restoring
+        let out = out?;
+        restored?;
+        return Ok(out);
+    }
+    out
+}
+
+#[cfg(target_os = "linux")]
+struct Cork(RawFd);
+
+#[cfg(target_os = "linux")]
+impl CorkOpt for Cork {
+    fn get(&mut self) -> io::Result<bool> {
+        let mut on: libc::c_int = 0;
+        let mut len = size_of::<libc::c_int>() as libc::socklen_t;
+        // SAFETY: `on` and `len` outlive the call, and `len` is the size of `on`
+        let rc = unsafe { libc::getsockopt(self.0, libc::IPPROTO_TCP, libc::TCP_CORK, (&raw mut on).cast(), &mut len) };
+        match rc {
+            0 => Ok(on != 0),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    fn set(&mut self, on: bool) -> io::Result<()> {
+        let on = libc::c_int::from(on);
+        let len = size_of::<libc::c_int>() as libc::socklen_t;
+        // SAFETY: `on` outlives the call, and `len` is its size
+        let rc = unsafe { libc::setsockopt(self.0, libc::IPPROTO_TCP, libc::TCP_CORK, (&raw const on).cast(), len) };
+        match rc {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+struct NoCork;
+
+#[cfg(not(target_os = "linux"))]
+impl CorkOpt for NoCork {
+    fn get(&mut self) -> io::Result<bool> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    fn set(&mut self, _: bool) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
+
+/// What [`Response::write_with_file_with`] does when a non-blocking socket
+/// is not ready for more.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OnWouldBlock {
+    /// Waits in `poll(2)` until it is.
+    #[default]
+    Poll,
+    /// Fails with [`io::ErrorKind::WouldBlock`], leaving the message partly
+    /// sent.
+    Fail,
+}
+
+/// How much of a message [`Response::write_with_file`] sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WriteReport {
+    pub head: usize,
+    pub body: u64,
+}
+
+impl<'a, T, V> Response<'a, T, V>
+where
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+{
+    /// Like [`Response::write_with_file_with`], waiting on a socket that is
+    /// not ready.
+    pub fn write_with_file<S: AsRawFd>(
+        self,
+        sock: &mut S,
+        file: &File,
+        range: Option<Range<u64>>,
+    ) -> io::Result<WriteReport> {
+        self.write_with_file_with(sock, file, range, OnWouldBlock::Poll)
+    }
+
+    /// Writes the head with a `Content-Length` for `file`, or for `range` of
+    /// it with a `Content-Range` as well, and then sends that much of the
+    /// file.
+    ///
+    /// On Linux the file goes out with `sendfile(2)`, without a copy through
+    /// userspace; elsewhere, or for a file `sendfile` does not take, it is
+    /// read and written in chunks. A ranged response is a
+    /// `206 Partial Content` the caller sets the status of; a range that is
+    /// empty or reaches past the end of the file is
+    /// [`io::ErrorKind::InvalidInput`]. The headers must not frame the body
+    /// themselves. A response that sends no body, such as one to `HEAD`,
+    /// only announces the file and sends none of it.
+    pub fn write_with_file_with<S: AsRawFd>(
+        self,
+        sock: &mut S,
+        file: &File,
+        range: Option<Range<u64>>,
+        on_would_block: OnWouldBlock,
+    ) -> io::Result<WriteReport> {
+        let total = file.metadata()?.len();
+        let (range, content_range) = match range {
+            None => (0..total, None),
+            Some(range) if range.start < range.end && range.end <= total => {
+                let content_range = content_range(&range, total);
+                (range, Some(content_range))
+            }
+            Some(_) => return Err(io::Error::new(io::ErrorKind::InvalidInput, "range outside the file")),
+        };
+
+        let mut out = Fd(sock.as_raw_fd(), on_would_block);
+        let sends_body = self.sends_body();
+        let mut res = self.field(Field::content_length(range.end - range.start)).fields(content_range.into_iter());
+        let head = res.write_to(&mut out)?;
+        if !sends_body {
+            return Ok(WriteReport { head, body: 0 });
+        }
+        let body = send_file(&mut out, file, range)?;
+        Ok(WriteReport { head, body })
+    }
+}
+
+/// `Content-Range: bytes <first>-<last>/<total>`.
+fn content_range(range: &Range<u64>, total: u64) -> Field<'static> {
+    let mut buf = [0; Inline::CAP];
+    let mut rest = buf.as_mut_slice();
+    match write!(rest, "bytes {}-{}/{total}", range.start, range.end - 1) {
+        Ok(()) => {
+            let len = Inline::CAP - rest.len();
+            Field::inline(names::CONTENT_RANGE, Inline::new(&buf[..len]))
+        }
+        // three 20 digit numbers do not fit
+        Err(_) => Field::invalid(names::CONTENT_RANGE, 0),
+    }
+}
+
+/// A socket written to with `write(2)`.
+struct Fd(RawFd, OnWouldBlock);
+
+impl Fd {
+    /// Whether to try again after `err`, having waited for the socket if it
+    /// was not ready.
+    fn retry(&self, err: &io::Error) -> io::Result<bool> {
+        match (err.kind(), self.1) {
+            (io::ErrorKind::Interrupted, _) => Ok(true),
+            (io::ErrorKind::WouldBlock, OnWouldBlock::Poll) => {
+                let mut pollfd = libc::pollfd {
+                    fd: self.0,
+                    events: libc::POLLOUT,
+                    revents: 0,
+                };
+                // SAFETY: `pollfd` outlives the call, which is given one entry
+                if unsafe { libc::poll(&mut pollfd, 1, -1) } == -1 {
+                    let err = io::Error::last_os_error();
+                    return if err.kind() == io::ErrorKind::Interrupted { Ok(true) } else { Err(err) };
+                }
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+}
+
+impl Write for Fd {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        loop {
+            // SAFETY: `buf` is valid for reads of its length
+            let n = unsafe { libc::write(self.0, buf.as_ptr().cast(), buf.len()) };
+            if n >= 0 {
+                return Ok(n as usize);
+            }
+            let err = io::Error::last_os_error();
+            if !self.retry(&err)? {
+                return Err(err);
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Sends `range` of `file`, returning how many bytes that was.
+fn send_file(out: &mut Fd, file: &File, range: Range<u64>) -> io::Result<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut offset = range.start;
+        while offset < range.end {
+            // sendfile moves at most this much at once anyway
+            let chunk = (range.end - offset).min(0x7fff_f000) as usize;
+            let mut off = offset as libc::off_t;
+            // SAFETY: both descriptors are open, and `off` outlives the call
+            let n = unsafe { libc::sendfile(out.0, file.as_raw_fd(), &mut off, chunk) };
+            match n {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                1.. => offset += n as u64,
+                _ => {
+                    let err = io::Error::last_os_error();
+                    let unsupported = matches!(err.raw_os_error(), Some(libc::EINVAL | libc::ENOSYS));
+                    if unsupported && offset == range.start {
+                        return copy_file(out, file, range);
+                    }
+                    if !out.retry(&err)? {
+                        return Err(err);
+                    }
+                }
+            }
+        }
+        Ok(range.end - range.start)
+    }
+    #[cfg(not(target_os = "linux"))]
+    copy_file(out, file, range)
+}
+
+/// Sends `range` of `file` through a buffer.
+fn copy_file(out: &mut Fd, file: &File, range: Range<u64>) -> io::Result<u64> {
+    use std::os::unix::fs::FileExt;
+
+    let mut buf = [0; 16 * 1024];
+    let mut offset = range.start;
+    while offset < range.end {
+        let want = (range.end - offset).min(buf.len() as u64) as usize;
+        let n = match file.read_at(&mut buf[..want], offset) {
+            Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+        out.write_all(&buf[..n])?;
+        offset += n as u64;
+    }
+    Ok(range.end - range.start)
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct MockCork {
+    on: Option<bool>,
+    fail_set: bool,
+    calls: Vec<&'static str>,
+}
+
+#[cfg(test)]
+impl CorkOpt for MockCork {
+    fn get(&mut self) -> io::Result<bool> {
+        self.calls.push("get");
+        self.on.ok_or_else(|| io::ErrorKind::Unsupported.into())
+    }
+
+    fn set(&mut self, on: bool) -> io::Result<()> {
+        self.calls.push(if on { "cork" } else { "uncork" });
+        if self.fail_set {
+            return Err(io::ErrorKind::PermissionDenied.into());
+        }
+        self.on = Some(on);
+        Ok(())
+    }
+}
+
+#[test]
+fn cork_is_restored() {
+    let mut opt = MockCork { on: Some(false), ..Default::default() };
+    assert_eq!(corked(&mut opt, || Ok(1)).unwrap(), 1);
+    assert_eq!((opt.on, &opt.calls[..]), (Some(false), &["get", "cork", "uncork"][..]));
+
+    let mut opt = MockCork { on: Some(false), ..Default::default() };
+    let err = corked(&mut opt, || -> io::Result<()> { Err(io::ErrorKind::BrokenPipe.into()) }).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    assert_eq!((opt.on, &opt.calls[..]), (Some(false), &["get", "cork", "uncork"][..]));
+
+    // a socket corked by the caller stays corked
+    let mut opt = MockCork { on: Some(true), ..Default::default() };
+    corked(&mut opt, || Ok(())).unwrap();
+    assert_eq!((opt.on, &opt.calls[..]), (Some(true), &["get"][..]));
+
+    let mut opt = MockCork::default();
+    corked(&mut opt, || Ok(())).unwrap();
+    assert_eq!(&opt.calls[..], ["get"]);
+
+    let mut opt = MockCork { on: Some(false), fail_set: true, ..Default::default() };
+    let mut ran = false;
+    let err = corked(&mut opt, || {
+        ran = true;
+        Ok(())
+    })
+    .unwrap_err();
+    assert_eq!((err.kind(), ran), (io::ErrorKind::PermissionDenied, false));
+}
+
+#[cfg(test)]
+fn response() -> Response<'static, impl Iterator<Item = Field<'static>>, crate::version::V1_1> {
+    Response::new(http::StatusCode::OK).v1_1().header("Content-Type", b"text/plain")
+}
+
+#[test]
+fn corked_tcp_write() {
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut server, _) = listener.accept().unwrap();
+
+    let len = write_corked(&mut server, response(), b"hello").unwrap();
+    #[cfg(target_os = "linux")]
+    assert!(!Cork(server.as_raw_fd()).get().unwrap());
+    drop(server);
+
+    let mut received = Vec::new();
+    client.read_to_end(&mut received).unwrap();
+    let expected = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\nhello";
+    assert_eq!((len, &received[..]), (expected.len(), &expected[..]));
+}
+
+/// What [`write_corked`] sends of `res` with a body of `hello`.
+#[cfg(test)]
+fn corked_hello<'a, T: Iterator<Item = Field<'a>>, V: Version<'a>>(res: Response<'a, T, V>) -> Vec<u8> {
+    use std::io::Read;
+
+    let (mut a, mut b) = std::os::unix::net::UnixStream::pair().unwrap();
+    let len = write_corked(&mut a, res, b"hello").unwrap();
+    drop(a);
+    let mut received = Vec::new();
+    b.read_to_end(&mut received).unwrap();
+    assert_eq!(len, received.len());
+    received
+}
+
+#[test]
+fn corked_without_body() {
+    let received = corked_hello(response().to_head_request());
+    assert_eq!(received, b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n\r\n");
+    let received = corked_hello(Response::new(http::StatusCode::NO_CONTENT).v1_1().header("Content-Type", b"text/plain"));
+    assert_eq!(received, b"HTTP/1.1 204 No Content\r\nContent-Type: text/plain\r\n\r\n");
+}
+
+#[test]
+fn uncorkable_socket() {
+    let received = corked_hello(response());
+    assert!(received.ends_with(b"\r\n\r\nhello"));
+}
+
+#[cfg(test)]
+struct TempFile(std::path::PathBuf);
+
+#[cfg(test)]
+impl TempFile {
+    fn new(name: &str, contents: &[u8]) -> Self {
+        let path = std::env::temp_dir().join(format!("http_write-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        Self(path)
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Reads everything `b` receives on another thread, slowly.
+#[cfg(test)]
+fn receive(mut b: std::os::unix::net::UnixStream) -> std::thread::JoinHandle<Vec<u8>> {
+    use std::io::Read;
+
+    std::thread::spawn(move || {
+        let mut received = Vec::new();
+        let mut buf = [0; 4096];
+        loop {
+            match b.read(&mut buf).unwrap() {
+                0 => return received,
+                n => received.extend_from_slice(&buf[..n]),
+            }
+            std::thread::sleep(std::time::Duration::from_micros(50));
+        }
+    })
+}
+
+#[cfg(test)]
+fn contents() -> Vec<u8> {
+    (0..300_000u32).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn file_over_socket() {
+    use std::os::unix::net::UnixStream;
+
+    let contents = contents();
+    let tmp = TempFile::new("file_over_socket", &contents);
+    let file = File::open(&tmp.0).unwrap();
+
+    let (mut a, b) = UnixStream::pair().unwrap();
+    // a full socket makes the send wait for the slow reader
+    a.set_nonblocking(true).unwrap();
+    let reader = receive(b);
+    let res = Response::new(http::StatusCode::OK).v1_1().header("Content-Type", b"application/octet-stream");
+    let report = res.write_with_file(&mut a, &file, None).unwrap();
+    drop(a);
+    let received = reader.join().unwrap();
+
+    let head = b"HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: 300000\r\n\r\n";
+    assert_eq!(report, WriteReport { head: head.len(), body: 300_000 });
+    assert_eq!(&received[..head.len()], head);
+    assert!(received[head.len()..] == contents);
+}
+
+#[test]
+fn ranged_file() {
+    use std::os::unix::net::UnixStream;
+
+    let contents = contents();
+    let tmp = TempFile::new("ranged_file", &contents);
+    let file = File::open(&tmp.0).unwrap();
+
+    let (mut a, b) = UnixStream::pair().unwrap();
+    let reader = receive(b);
+    let res = Response::new(http::StatusCode::PARTIAL_CONTENT).v1_1();
+    let report = res.write_with_file(&mut a, &file, Some(1000..1500)).unwrap();
+    drop(a);
+    let received = reader.join().unwrap();
+
+    let head = b"HTTP/1.1 206 Partial Content\r\nContent-Length: 500\r\nContent-Range: bytes 1000-1499/300000\r\n\r\n";
+    assert_eq!(report, WriteReport { head: head.len(), body: 500 });
+    assert_eq!(&received[..head.len()], head);
+    assert_eq!(&received[head.len()..], &contents[1000..1500]);
+
+    let (mut a, b) = UnixStream::pair().unwrap();
+    let reader = receive(b);
+    let res = Response::new(http::StatusCode::PARTIAL_CONTENT).v1_1().to_head_request();
+    let report = res.write_with_file(&mut a, &file, Some(1000..1500)).unwrap();
+    drop(a);
+    assert_eq!(report, WriteReport { head: head.len(), body: 0 });
+    assert_eq!(reader.join().unwrap(), head);
+
+    let (mut a, _b) = UnixStream::pair().unwrap();
+    for range in [0..0, 299_999..300_001] {
+        let res = Response::new(http::StatusCode::PARTIAL_CONTENT).v1_1();
+        let err = res.write_with_file(&mut a, &file, Some(range)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn copy_fallback() {
+    use std::os::unix::net::UnixStream;
+
+    let contents = contents();
+    let tmp = TempFile::new("copy_fallback", &contents);
+    let file = File::open(&tmp.0).unwrap();
+
+    let (a, b) = UnixStream::pair().unwrap();
+    a.set_nonblocking(true).unwrap();
+    let reader = receive(b);
+    let sent = copy_file(&mut Fd(a.as_raw_fd(), OnWouldBlock::Poll), &file, 10..200_000).unwrap();
+    drop(a);
+    assert_eq!(sent, 199_990);
+    assert!(reader.join().unwrap() == contents[10..200_000]);
+
+    // a file that shrank under the send
+    let (a, _b) = UnixStream::pair().unwrap();
+    let err = copy_file(&mut Fd(a.as_raw_fd(), OnWouldBlock::Poll), &file, 299_000..300_010).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    let (a, _b) = UnixStream::pair().unwrap();
+    a.set_nonblocking(true).unwrap();
+    let err = copy_file(&mut Fd(a.as_raw_fd(), OnWouldBlock::Fail), &file, 0..300_000).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+}
@@ -0,0 +1,197 @@
+//! A counting global allocator for the test build, and the checks that the
+//! write path never allocates.
+//!
+//! Allocations are counted per thread, so tests running in parallel do not
+//! see each other's. Wrap the code under test in [`assert_no_alloc`] to
+//! hold a new feature to the same guarantee.
+//!
+//! The APIs documented as allocating, such as
+//! [`Request::to_owned_request`](crate::Request::to_owned_request),
+//! `write_to_captured` or `write_to_vectored`, are not held to it, and
+//! neither is a head longer than the 4 KiB `write_to` stages on the stack.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io;
+
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    // a thread being torn down has nothing left to assert
+    let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+}
+
+// SAFETY: every call is forwarded to the system allocator as is
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Runs `f`, returning how many allocations it made on this thread.
+pub(crate) fn allocations<R>(f: impl FnOnce() -> R) -> (usize, R) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let out = f();
+    (ALLOCATIONS.with(Cell::get) - before, out)
+}
+
+/// Runs `f`, panicking if it allocated.
+pub(crate) fn assert_no_alloc<R>(what: &str, f: impl FnOnce() -> R) -> R {
+    let (n, out) = allocations(f);
+    assert_eq!(n, 0, "{what} allocated {n} times");
+    out
+}
+
+use crate::{Request, Response};
+
+#[test]
+fn counts_allocations() {
+    let (n, _) = allocations(|| format!("{}", 12345));
+    assert!(n > 0);
+    let (n, _) = allocations(|| 1 + 1);
+    assert_eq!(n, 0);
+    // the documented allocating APIs do allocate
+    let (n, _) = allocations(|| Request::get().path("/").v1_1().to_owned_request().unwrap());
+    assert!(n > 0);
+    let (n, _) = allocations(|| Request::get().path("/").v1_1().write_to_vectored(&mut io::sink()).unwrap());
+    assert!(n > 0);
+}
+
+// the messages are built inside the checks, so their builders are held to
+// the guarantee too
+#[test]
+fn request_write_does_not_allocate() {
+    let body = b"{\"name\":\"value\"}".as_slice();
+    let mut vec = Vec::with_capacity(4096);
+    let mut slice = [0; 4096];
+
+    let req = || {
+        Request::post()
+            .path("/api/./items/../things")
+            .normalize_path()
+            .query("a=1")
+            .query("b=%20")
+            .v1_1()
+            .header("Host", b"example.com")
+            .header("Content-Type", b"application/json")
+            .accept_language(&[("en-US", None), ("de", Some(0.5))])
+            .origin("https", "example.com", Some(8443))
+            .traceparent([0xab; 16], [0xcd; 8], true)
+            .tracestate(&[("congo", "t61rcWkgMzE")])
+            .set_body(body)
+    };
+    let len = assert_no_alloc("write_to a Vec", || req().write_to(&mut vec).unwrap());
+    assert_eq!(len, vec.len());
+
+    let len = assert_no_alloc("write_to a slice", || req().write_to(&mut slice.as_mut_slice()).unwrap());
+    assert_eq!(slice[..len], vec[..]);
+
+    let mut unchecked = Vec::with_capacity(4096);
+    // SAFETY: the same request was written checked above
+    let len = assert_no_alloc("write_to_unchecked", || unsafe { req().write_to_unchecked(&mut unchecked).unwrap() });
+    assert_eq!(len, unchecked.len());
+}
+
+#[test]
+fn response_write_does_not_allocate() {
+    let mut vec = Vec::with_capacity(4096);
+    let mut slice = [0; 4096];
+    let csp = crate::Csp::new().default_src(&[crate::Source::SelfOrigin]);
+
+    let res = || {
+        Response::new(http::StatusCode::NOT_FOUND)
+            .v1_1()
+            .header("Content-Type", b"text/plain")
+            .header("Cache-Control", b"no-store")
+            .content_security_policy(&csp)
+            .set_body(b"not found".as_slice())
+    };
+    let len = assert_no_alloc("write_to a Vec", || res().write_to(&mut vec).unwrap());
+    assert_eq!(len, vec.len());
+
+    let len = assert_no_alloc("write_to a slice", || res().write_to(&mut slice.as_mut_slice()).unwrap());
+    assert_eq!(slice[..len], vec[..]);
+
+    let mut unchecked = Vec::with_capacity(4096);
+    // SAFETY: the same response was written checked above
+    let len = assert_no_alloc("write_to_unchecked", || unsafe { res().write_to_unchecked(&mut unchecked).unwrap() });
+    assert_eq!(unchecked[..len], vec[..]);
+}
+
+#[test]
+fn unique_headers_do_not_allocate() {
+    let mut vec = Vec::with_capacity(4096);
+    let req = || {
+        Request::get()
+            .path("/")
+            .v1_1()
+            .unique_headers(crate::consts::UNIQUE_HEADERS)
+            .header("Host", b"example.com")
+            .header("Accept", b"*/*")
+    };
+    let len = assert_no_alloc("write_to with unique headers", || req().write_to(&mut vec).unwrap());
+    assert_eq!(len, vec.len());
+    let mut slice = [0; 4096];
+    let err = assert_no_alloc("a repeated unique header", || req().host("b").write_to(&mut slice.as_mut_slice()));
+    assert!(matches!(err, Err(crate::RequestWriteError::DuplicateHeader { .. })));
+
+    let mut vec = Vec::with_capacity(4096);
+    let res = || {
+        Response::new(http::StatusCode::OK)
+            .v1_1()
+            .unique_headers(crate::consts::UNIQUE_HEADERS)
+            .header("Content-Type", b"text/plain")
+    };
+    let len = assert_no_alloc("Response::write_to with unique headers", || res().write_to(&mut vec).unwrap());
+    assert_eq!(len, vec.len());
+}
+
+// headers that own their values allocate while the message is built, so it
+// is built outside the check and only the write is measured
+#[test]
+fn owned_headers_do_not_allocate() {
+    let mut vec = Vec::with_capacity(4096);
+    let fields = vec![crate::Field::new("Host", b"example.com"), crate::Field::new("Accept", b"*/*")];
+    let mut req = Request::get().path("/").v1_1().headers(fields).header_owned("X-Id", format!("req-{}", 7));
+    let len = assert_no_alloc("write_to with Vec-backed and owned headers", || req.write_to(&mut vec).unwrap());
+    assert_eq!(vec, b"GET / HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\nX-Id: req-7\r\n\r\n");
+    assert_eq!(len, vec.len());
+
+    let mut vec = Vec::with_capacity(4096);
+    let mut res = Response::ok().v1_1().headers(vec![crate::Field::new("Content-Type", b"text/plain")]).header_owned("X-Id", "req-7");
+    let len = assert_no_alloc("Response::write_to with Vec-backed and owned headers", || res.write_to(&mut vec).unwrap());
+    assert_eq!(vec, b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nX-Id: req-7\r\n\r\n");
+    assert_eq!(len, vec.len());
+}
+
+#[test]
+fn chunked_body_does_not_allocate() {
+    let mut vec = Vec::with_capacity(4096);
+    let chunks = [b"id,name\n".as_slice(), b"1,ada\n", b"2,grace\n"];
+    let res = || Response::new(http::StatusCode::OK).v1_1().header("Content-Type", b"text/csv").chunked(chunks);
+    let len = assert_no_alloc("write_to with a chunked body", || res().write_to(&mut vec).unwrap());
+    assert_eq!(len, vec.len());
+    assert!(vec.ends_with(b"8\r\nid,name\n\r\n6\r\n1,ada\n\r\n8\r\n2,grace\n\r\n0\r\n\r\n"));
+}
@@ -0,0 +1,149 @@
+use crate::Field;
+use std::net::Ipv6Addr;
+
+/// What [`Request::referer_with`](crate::Request::referer_with) does with a
+/// `#fragment`, which a `Referer` must not carry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RefererFragment {
+    /// Write the reference without its fragment.
+    #[default]
+    Strip,
+    /// Fail the write at the position of the `#`.
+    Reject,
+}
+
+/// The origin of a request, as [`Request::cors_preflight`](crate::Request::cors_preflight)
+/// takes it; written like [`Request::origin`](crate::Request::origin) writes
+/// its parts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Origin<'a> {
+    pub scheme: &'a str,
+    pub host: &'a str,
+    pub port: Option<u16>,
+}
+
+impl<'a> Origin<'a> {
+    pub fn new(scheme: &'a str, host: &'a str, port: Option<u16>) -> Self {
+        Self { scheme, host, port }
+    }
+}
+
+/// Builds an `Origin` value, leaving out the scheme's default port.
+///
+/// An IPv6 host may be given with or without its brackets.
+pub(crate) fn origin<'a>(name: &'a str, scheme: &'a str, host: &'a str, port: Option<u16>) -> Field<'a> {
+    if let Err(pos) = check_scheme(scheme) {
+        return Field::invalid(name, pos);
+    }
+    if let Err(pos) = check_host(host) {
+        return Field::invalid(name, scheme.len() + 3 + pos);
+    }
+
+    let default = [("http", 80), ("ws", 80), ("https", 443), ("wss", 443)]
+        .into_iter()
+        .find(|(s, _)| s.eq_ignore_ascii_case(scheme))
+        .map(|(_, port)| port);
+    let port = port.filter(|&p| Some(p) != default);
+    Field::origin(name, scheme, host, port)
+}
+
+/// Builds a `Referer` value from a URI reference.
+pub(crate) fn referer<'a>(name: &'a str, uri: &'a str, fragment: RefererFragment) -> Field<'a> {
+    if fluent_uri::UriRef::parse(uri).is_err() {
+        let pos = uri.bytes().position(|ch| !ch.is_ascii_graphic()).unwrap_or(0);
+        return Field::invalid(name, pos);
+    }
+
+    match (uri.find('#'), fragment) {
+        (None, _) => Field::trusted(name, uri.as_bytes()),
+        (Some(pos), RefererFragment::Strip) => Field::trusted(name, &uri.as_bytes()[..pos]),
+        (Some(pos), RefererFragment::Reject) => Field::invalid(name, pos),
+    }
+}
+
+/// Builds a `Link` value, `<uri>` followed by `params` such as
+/// `; rel="deprecation"`.
+#[cfg(feature = "date")]
+pub(crate) fn link<'a>(name: &'a str, uri: &'a str, params: &'static [u8]) -> Field<'a> {
+    if fluent_uri::UriRef::parse(uri).is_err() {
+        let pos = uri.bytes().position(|ch| !ch.is_ascii_graphic()).unwrap_or(0);
+        return Field::invalid(name, 1 + pos);
+    }
+    Field::parts(name, [b"<", uri.as_bytes(), params])
+}
+
+/// `ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )`
+fn check_scheme(scheme: &str) -> Result<(), usize> {
+    let bad = scheme.bytes().enumerate().position(|(i, ch)| match i {
+        0 => !ch.is_ascii_alphabetic(),
+        _ => !(ch.is_ascii_alphanumeric() || matches!(ch, b'+' | b'-' | b'.')),
+    });
+    match (scheme.is_empty(), bad) {
+        (true, _) => Err(0),
+        (false, Some(pos)) => Err(pos),
+        (false, None) => Ok(()),
+    }
+}
+
+/// A registered name, IPv4 address, or IPv6 address.
+fn check_host(host: &str) -> Result<(), usize> {
+    if host.is_empty() {
+        return Err(0);
+    }
+
+    let ipv6 = match host.strip_prefix('[') {
+        Some(inner) => inner.strip_suffix(']').ok_or(host.len())?,
+        None if host.contains(':') => host,
+        None => {
+            crate::path::validate_encoded(host.as_bytes(), b"")?;
+            return match host.find('@') {
+                Some(pos) => Err(pos),
+                None => Ok(()),
+            };
+        }
+    };
+
+    ipv6.parse::<Ipv6Addr>().map(|_| ()).map_err(|_| 0)
+}
+
+#[cfg(test)]
+fn value(field: Field<'_>) -> Result<String, crate::HeaderWriteError> {
+    field.validate()?;
+    Ok(String::from_utf8(field.value_chunks().collect::<Vec<_>>().concat()).unwrap())
+}
+
+#[test]
+fn default_port() {
+    let origin = |scheme, port| value(origin("Origin", scheme, "example.com", port));
+    assert_eq!(origin("https", Some(443)).unwrap(), "https://example.com");
+    assert_eq!(origin("https", None).unwrap(), "https://example.com");
+    assert_eq!(origin("http", Some(80)).unwrap(), "http://example.com");
+    assert_eq!(origin("http", Some(8080)).unwrap(), "http://example.com:8080");
+    assert_eq!(origin("https", Some(80)).unwrap(), "https://example.com:80");
+    assert_eq!(origin("http", Some(0)).unwrap(), "http://example.com:0");
+    assert_eq!(origin("h ttp", None), Err(crate::HeaderWriteError::InvalidValue(1)));
+}
+
+#[test]
+fn ipv6_origin() {
+    let origin = |host, port| value(origin("Origin", "https", host, port));
+    assert_eq!(origin("::1", Some(8443)).unwrap(), "https://[::1]:8443");
+    assert_eq!(origin("[2001:db8::1]", None).unwrap(), "https://[2001:db8::1]");
+    assert_eq!(origin("[::1", None), Err(crate::HeaderWriteError::InvalidValue(12)));
+    assert_eq!(origin("example.com/x", None), Err(crate::HeaderWriteError::InvalidValue(19)));
+}
+
+#[test]
+fn referer_fragment() {
+    let uri = "https://example.com/a?b=c#section";
+    assert_eq!(value(referer("Referer", uri, RefererFragment::Strip)).unwrap(), "https://example.com/a?b=c");
+    assert_eq!(
+        value(referer("Referer", uri, RefererFragment::Reject)),
+        Err(crate::HeaderWriteError::InvalidValue(25))
+    );
+    assert_eq!(value(referer("Referer", "/partial", RefererFragment::Reject)).unwrap(), "/partial");
+    assert_eq!(
+        value(referer("Referer", "/a b", RefererFragment::Strip)),
+        Err(crate::HeaderWriteError::InvalidValue(2))
+    );
+}
@@ -0,0 +1,222 @@
+use crate::consts::names;
+use crate::version::Dynamic;
+use std::io::Write;
+use crate::{Body, Field, Framing, Method, Query, Request, RequestWriteError, Version};
+
+/// A request that owns all of its parts, so it can be kept around and
+/// written any number of times.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) queries: Vec<String>,
+    pub(crate) headers: Vec<(String, Vec<u8>)>,
+    pub(crate) version: String,
+    pub(crate) body: Vec<u8>,
+}
+
+impl OwnedRequest {
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The request target up to the query, which includes the scheme and
+    /// authority of an absolute-form target.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn queries(&self) -> impl Iterator<Item = &str> {
+        self.queries.iter().map(String::as_str)
+    }
+
+    pub fn headers(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.headers.iter().map(|(n, v)| (n.as_str(), v.as_slice()))
+    }
+
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Replaces the body, which is written after the head as-is, and sets
+    /// `Content-Length` to its length.
+    ///
+    /// Fails with [`RequestWriteError::ConflictingFraming`] if the request
+    /// has a `Transfer-Encoding`, which the new body would not follow.
+    pub fn with_body(mut self, body: Vec<u8>) -> Result<Self, RequestWriteError> {
+        let is = |name: &str, target: &str| name.eq_ignore_ascii_case(target);
+        if self.headers.iter().any(|(n, _)| is(n, names::TRANSFER_ENCODING)) {
+            return Err(RequestWriteError::ConflictingFraming { buffer_offset: 0 });
+        }
+        self.headers.retain(|(n, _)| !is(n, names::CONTENT_LENGTH));
+        self.headers.push((names::CONTENT_LENGTH.to_owned(), body.len().to_string().into_bytes()));
+        self.body = body;
+        Ok(self)
+    }
+
+    /// Writes the head followed by the body.
+    pub fn write_to<W: std::io::Write + ?Sized>(&self, w: &mut W) -> Result<usize, RequestWriteError> {
+        let mut req = Request::new(Method::Custom(&self.method))
+            .path(&self.path)
+            .version(Dynamic::new(&self.version))
+            .queries(self.queries.iter().map(|q| Query::new(q)))
+            .headers(self.headers.iter().map(|(n, v)| Field::new(n, v)));
+
+        let len = req.write_to(w)?;
+        let mut w = crate::Counter::new(w);
+        w.write_all(&self.body).map_err(|source| RequestWriteError::Io {
+            source,
+            written: len + w.written,
+        })?;
+        Ok(len + self.body.len())
+    }
+}
+
+impl<'a, T, Q, V, B> Request<'a, T, Q, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    Q: Iterator<Item = Query<'a>>,
+    V: Version<'a>,
+    B: Body,
+{
+    /// Runs the checks of [`Request::write_to`] and copies the request into
+    /// an [`OwnedRequest`], so it can be inspected or signed before it is
+    /// written.
+    ///
+    /// The `Content-Length` or `Transfer-Encoding` that `write_to` would add
+    /// is made explicit, and the body is kept in its encoded form.
+    ///
+    /// Like `write_to`, this drains the header and query iterators.
+    pub fn to_owned_request(&mut self) -> Result<OwnedRequest, RequestWriteError> {
+        let version = self.checked_version()?;
+        let path = self.checked_path()?;
+        let framing = self.checked_framing()?;
+
+        let mut normalized = Vec::new();
+        self.target.write_prefix(&mut normalized)?;
+        if self.target.has_path() {
+            self.write_path(&mut normalized, path)?;
+        }
+
+        let mut queries = Vec::new();
+        for q in &mut self.queries {
+            queries.push(q.checked().map_err(|_| RequestWriteError::InvalidQuery)?.into_owned());
+        }
+        if !self.target.has_path() && !queries.is_empty() {
+            return Err(RequestWriteError::QueryNotAllowed);
+        }
+        // an owned request joins with `&`, so other separators make one segment
+        if self.query_separator != crate::QuerySeparator::Ampersand && queries.len() > 1 {
+            queries = vec![queries.join(self.query_separator.as_str())];
+        }
+
+        let mut headers = Vec::new();
+        let mut framed = crate::field::FramingHeaders::default();
+        let mut unique = crate::field::UniqueHeaders::new(self.unique_headers);
+        let mut host = false;
+        for header in self.drain_headers() {
+            header.validate().map_err(|e| (0, e))?;
+            if framed.conflicts(&header) {
+                return Err(RequestWriteError::ConflictingFraming { buffer_offset: 0 });
+            }
+            if unique.repeats(&header) {
+                return Err(RequestWriteError::DuplicateHeader { buffer_offset: 0 });
+            }
+            host |= header.is_host();
+            headers.push((header.name().to_owned(), header.value_chunks().collect::<Vec<_>>().concat()));
+        }
+        if !host && self.host_required() {
+            return Err(RequestWriteError::MissingHost { buffer_offset: 0 });
+        }
+        if framed.disagrees(framing) {
+            return Err(RequestWriteError::ConflictingFraming { buffer_offset: 0 });
+        }
+
+        match (framing, framed.any()) {
+            (Framing::Length(len), false) => {
+                headers.push((names::CONTENT_LENGTH.to_owned(), len.to_string().into_bytes()))
+            }
+            (Framing::Chunked, false) => headers.push((names::TRANSFER_ENCODING.to_owned(), b"chunked".to_vec())),
+            _ => {}
+        }
+        let mut body = Vec::new();
+        self.body.write_body(&mut body)?;
+
+        Ok(OwnedRequest {
+            method: self.method.as_str().to_owned(),
+            // the target was validated, and normalizing only drops segments
+            path: String::from_utf8(normalized).expect("paths are ASCII"),
+            queries,
+            headers,
+            version: version.to_owned(),
+            body,
+        })
+    }
+}
+
+#[test]
+fn to_owned_request() {
+    let owned = Request::put()
+        .path("/a/../b")
+        .normalize_path()
+        .query("x=1")
+        .v1_1()
+        .header("Host", b"example.com")
+        .to_owned_request()
+        .unwrap()
+        .with_body(b"hi".to_vec())
+        .unwrap();
+
+    assert_eq!(owned.method(), "PUT");
+    assert_eq!(owned.path(), "/b");
+    assert_eq!(owned.queries().collect::<Vec<_>>(), ["x=1"]);
+    assert_eq!(
+        owned.headers().collect::<Vec<_>>(),
+        [("Host", &b"example.com"[..]), ("Content-Length", b"2")]
+    );
+
+    let mut buf = Vec::new();
+    owned.write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"PUT /b?x=1 HTTP/1.1\r\nHost: example.com\r\nContent-Length: 2\r\n\r\nhi");
+
+    let owned = owned.with_body(b"hello".to_vec()).unwrap();
+    let lengths = owned.headers().filter(|(n, _)| *n == "Content-Length");
+    assert_eq!(lengths.collect::<Vec<_>>(), [("Content-Length", &b"5"[..])]);
+}
+
+#[test]
+fn with_body_chunked() {
+    let owned = Request::post()
+        .path("/")
+        .v1_1()
+        .header("Host", b"example.com")
+        .header("Transfer-Encoding", b"chunked")
+        .to_owned_request()
+        .unwrap();
+    assert!(matches!(
+        owned.with_body(b"hi".to_vec()),
+        Err(RequestWriteError::ConflictingFraming { buffer_offset: 0 })
+    ));
+}
+
+#[test]
+fn to_owned_request_framing_disagrees() {
+    let mut req = Request::post().path("/").v1_1().header("Host", b"a").header("Content-Length", b"3").body(b"hello");
+    assert!(matches!(req.to_owned_request(), Err(RequestWriteError::ConflictingFraming { buffer_offset: 0 })));
+}
+
+#[test]
+fn to_owned_request_empty_body() {
+    let owned = Request::get().path("/").v1_1().header("Host", b"a").body(b"").to_owned_request().unwrap();
+    assert_eq!(owned.headers().collect::<Vec<_>>(), [("Host", &b"a"[..])]);
+
+    let mut buf = Vec::new();
+    Request::get().path("/").v1_1().header("Host", b"a").body(b"").write_to(&mut buf).unwrap();
+    let mut owned_buf = Vec::new();
+    owned.write_to(&mut owned_buf).unwrap();
+    assert_eq!(owned_buf, buf);
+}
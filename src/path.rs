@@ -0,0 +1,263 @@
+/// A request path that has already been checked for valid percent-encoding.
+///
+/// Validating once, e.g. for a static route, lets
+/// [`Request::write_to`](crate::Request::write_to) skip the check on every
+/// write.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EncodedPath<'a>(&'a str);
+
+impl<'a> EncodedPath<'a> {
+    pub const fn new(path: &'a str) -> Result<Self, InvalidPath> {
+        match validate(path) {
+            Ok(()) => Ok(Self(path)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Caller must guarantee that `path` is a non-empty, percent-encoded path.
+    pub const unsafe fn new_unchecked(path: &'a str) -> Self {
+        Self(path)
+    }
+
+    pub const fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+/// The path contains a byte that is not allowed, at the given position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidPath(pub usize);
+
+impl core::fmt::Display for InvalidPath {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid byte in path at {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidPath {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum RequestPath<'a> {
+    Raw(&'a str),
+    Encoded(EncodedPath<'a>),
+}
+
+impl<'a> RequestPath<'a> {
+    pub(crate) fn checked(self) -> Result<&'a str, InvalidPath> {
+        match self {
+            Self::Raw(path) => {
+                #[cfg(test)]
+                CHECKS.with(|c| c.set(c.get() + 1));
+                validate(path).map(|()| path)
+            }
+            Self::Encoded(path) => Ok(path.0),
+        }
+    }
+
+    pub(crate) fn as_str(self) -> &'a str {
+        match self {
+            Self::Raw(path) => path,
+            Self::Encoded(path) => path.0,
+        }
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    pub(crate) static CHECKS: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
+
+/// Checks that `path` is non-empty and only holds `pchar`s and `/`.
+pub(crate) const fn validate(path: &str) -> Result<(), InvalidPath> {
+    let bytes = path.as_bytes();
+    if bytes.is_empty() {
+        return Err(InvalidPath(0));
+    }
+
+    match validate_encoded(bytes, b"/") {
+        Ok(()) => Ok(()),
+        Err(pos) => Err(InvalidPath(pos)),
+    }
+}
+
+/// Checks for `pchar`s, the bytes in `extra`, and well-formed `%XX` escapes.
+pub(crate) const fn validate_encoded(bytes: &[u8], extra: &[u8]) -> Result<(), usize> {
+    let mut i = 0;
+    while i < bytes.len() {
+        let ch = bytes[i];
+        if ch == b'%' {
+            if i + 2 >= bytes.len() {
+                return Err(i);
+            }
+            if !bytes[i + 1].is_ascii_hexdigit() || !bytes[i + 2].is_ascii_hexdigit() {
+                return Err(i);
+            }
+            i += 3;
+            continue;
+        }
+
+        if !(is_pchar(ch) || contains(extra, ch)) {
+            return Err(i);
+        }
+        i += 1;
+    }
+    Ok(())
+}
+
+const fn is_pchar(ch: u8) -> bool {
+    ch.is_ascii_alphanumeric()
+        || matches!(
+            ch,
+            b'-' | b'.'
+                | b'_'
+                | b'~'
+                | b'!'
+                | b'$'
+                | b'&'
+                | b'\''
+                | b'('
+                | b')'
+                | b'*'
+                | b'+'
+                | b','
+                | b';'
+                | b'='
+                | b':'
+                | b'@'
+        )
+}
+
+const fn contains(set: &[u8], ch: u8) -> bool {
+    let mut i = 0;
+    while i < set.len() {
+        if set[i] == ch {
+            return true;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// How the request path is rewritten before it hits the wire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Normalize {
+    /// The path is written exactly as given.
+    Off,
+    /// RFC 3986 `remove_dot_segments`, where `..` above the root is dropped.
+    DotSegments,
+    /// Like [`Normalize::DotSegments`], but `..` above the root is an error.
+    NoTraversal,
+}
+
+/// Returns `true` if a `..` segment would climb above the root of `path`.
+pub(crate) fn escapes_root(path: &str) -> bool {
+    let mut depth = 0usize;
+    for seg in segments(path) {
+        match seg {
+            "." => {}
+            ".." => match depth.checked_sub(1) {
+                Some(d) => depth = d,
+                None => return true,
+            },
+            _ => depth += 1,
+        }
+    }
+    false
+}
+
+/// Writes `path` with its dot segments removed, always starting with `/`.
+///
+/// This works on the percent-encoded form, so `%2E` is not treated as a dot.
+/// Nothing is buffered: a segment is emitted only if no later `..` pops it.
+pub(crate) fn write_normalized<W: std::io::Write + ?Sized>(
+    w: &mut W,
+    path: &str,
+) -> std::io::Result<usize> {
+    let mut segs = segments(path);
+    let mut trailing = false;
+    let mut wrote_any = false;
+    let mut len = 0;
+
+    w.write_all(b"/")?;
+    len += 1;
+
+    while let Some(seg) = segs.next() {
+        trailing = matches!(seg, "." | "..");
+        if trailing || popped_later(segs.clone()) {
+            continue;
+        }
+
+        if wrote_any {
+            w.write_all(b"/")?;
+            len += 1;
+        }
+        w.write_all(seg.as_bytes())?;
+        len += seg.len();
+        wrote_any = true;
+    }
+
+    if trailing && wrote_any {
+        w.write_all(b"/")?;
+        len += 1;
+    }
+
+    Ok(len)
+}
+
+fn segments(path: &str) -> core::str::Split<'_, char> {
+    path.strip_prefix('/').unwrap_or(path).split('/')
+}
+
+/// Whether a segment followed by `rest` is removed by a later `..`.
+fn popped_later<'a>(rest: impl Iterator<Item = &'a str>) -> bool {
+    let mut above = 0usize;
+    for seg in rest {
+        match seg {
+            "." => {}
+            ".." => match above.checked_sub(1) {
+                Some(a) => above = a,
+                None => return true,
+            },
+            _ => above += 1,
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+fn normalized(path: &str) -> String {
+    let mut buf = Vec::new();
+    let len = write_normalized(&mut buf, path).unwrap();
+    assert_eq!(len, buf.len());
+    String::from_utf8(buf).unwrap()
+}
+
+#[test]
+fn rfc_examples() {
+    assert_eq!(normalized("/a/b/c/./../../g"), "/a/g");
+    assert_eq!(normalized("mid/content=5/../6"), "/mid/6");
+    assert_eq!(normalized("/a/./b/../c"), "/a/c");
+    assert_eq!(normalized("/./a"), "/a");
+    assert_eq!(normalized("/a/%2E%2E/b"), "/a/%2E%2E/b");
+}
+
+#[test]
+fn traversal_above_root() {
+    assert_eq!(normalized("/../x"), "/x");
+    assert_eq!(normalized("/a/../../x"), "/x");
+    assert_eq!(normalized("/.."), "/");
+    assert!(escapes_root("/../x"));
+    assert!(escapes_root("/a/../../x"));
+    assert!(!escapes_root("/a/../x"));
+}
+
+#[test]
+fn trailing_slash() {
+    assert_eq!(normalized("/"), "/");
+    assert_eq!(normalized("/a/b/"), "/a/b/");
+    assert_eq!(normalized("/a/b/."), "/a/b/");
+    assert_eq!(normalized("/a/b/c/.."), "/a/b/");
+    assert_eq!(normalized("/a/.."), "/");
+}
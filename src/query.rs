@@ -0,0 +1,152 @@
+use core::iter::Map;
+use fluent_uri::encoding::{EString, Encoder, Table, encoder};
+use std::borrow::Cow;
+
+/// One `&`-separated segment of a request query.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Query<'a> {
+    q: Cow<'a, str>,
+    trusted: bool,
+}
+
+impl<'a> Query<'a> {
+    pub fn new(query: &'a str) -> Self {
+        Self {
+            q: Cow::Borrowed(query),
+            trusted: false,
+        }
+    }
+
+    /// A segment the crate encoded itself, which is always valid.
+    pub(crate) fn encoded(query: String) -> Self {
+        Self {
+            q: Cow::Owned(query),
+            trusted: true,
+        }
+    }
+
+    /// An owned segment that is still checked on write.
+    #[cfg(feature = "arbitrary")]
+    pub(crate) fn owned(query: String) -> Self {
+        Self {
+            q: Cow::Owned(query),
+            trusted: false,
+        }
+    }
+
+    /// `key=value` with both sides percent-encoded, spaces as `%20`.
+    pub(crate) fn pair(key: &str, value: &str) -> Self {
+        let mut pair = EString::<encoder::Query>::with_capacity(key.len() + value.len() + 1);
+        pair.encode::<PairPart>(key);
+        pair.push('=');
+        pair.encode::<PairPart>(value);
+        Self::encoded(pair.into_string())
+    }
+
+    pub(crate) fn from_pair<K: AsRef<str>, V: AsRef<str>>((key, value): (K, V)) -> Self {
+        Self::pair(key.as_ref(), value.as_ref())
+    }
+
+    pub(crate) fn checked(self) -> Result<Cow<'a, str>, InvalidQuery> {
+        if !self.trusted {
+            #[cfg(test)]
+            CHECKS.with(|c| c.set(c.get() + 1));
+            validate(&self.q)?;
+        }
+        Ok(self.q)
+    }
+}
+
+/// The key or value of a pair: a query character other than the `&`, `;`,
+/// `=` and `+` that delimit pairs or stand for spaces in forms.
+struct PairPart(());
+
+impl Encoder for PairPart {
+    const TABLE: &'static Table = &encoder::Query::TABLE.sub(&Table::new(b"&;=+"));
+}
+
+/// What [`Request::query_separator`](crate::Request::query_separator) joins
+/// query segments with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuerySeparator {
+    #[default]
+    Ampersand,
+    /// `;`, which some older APIs split on instead.
+    Semicolon,
+}
+
+impl QuerySeparator {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Ampersand => "&",
+            Self::Semicolon => ";",
+        }
+    }
+}
+
+/// The iterator that [`Request::queries`](crate::Request::queries) chains on.
+pub type IntoQueries<'a, Q> =
+    Map<<Q as IntoIterator>::IntoIter, fn(<Q as IntoIterator>::Item) -> Query<'a>>;
+
+/// The iterator that [`Request::query_pairs`](crate::Request::query_pairs)
+/// chains on.
+pub type QueryPairs<'a, P> =
+    Map<<P as IntoIterator>::IntoIter, fn(<P as IntoIterator>::Item) -> Query<'a>>;
+
+/// A query segment that has already been checked for valid percent-encoding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct EncodedQuery<'a>(&'a str);
+
+impl<'a> EncodedQuery<'a> {
+    pub const fn new(query: &'a str) -> Result<Self, InvalidQuery> {
+        match validate(query) {
+            Ok(()) => Ok(Self(query)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// # Safety
+    ///
+    /// Caller must guarantee that `query` is a percent-encoded query segment.
+    pub const unsafe fn new_unchecked(query: &'a str) -> Self {
+        Self(query)
+    }
+
+    pub const fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl<'a> From<EncodedQuery<'a>> for Query<'a> {
+    fn from(q: EncodedQuery<'a>) -> Self {
+        Self {
+            q: Cow::Borrowed(q.0),
+            trusted: true,
+        }
+    }
+}
+
+/// The query contains a byte that is not allowed, at the given position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidQuery(pub usize);
+
+impl core::fmt::Display for InvalidQuery {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid byte in query at {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidQuery {}
+
+/// Checks that `query` only holds `pchar`s, `/` and `?`.
+pub(crate) const fn validate(query: &str) -> Result<(), InvalidQuery> {
+    match crate::path::validate_encoded(query.as_bytes(), b"/?") {
+        Ok(()) => Ok(()),
+        Err(pos) => Err(InvalidQuery(pos)),
+    }
+}
+
+#[cfg(test)]
+thread_local! {
+    pub(crate) static CHECKS: core::cell::Cell<usize> = const { core::cell::Cell::new(0) };
+}
@@ -0,0 +1,232 @@
+//! Deriving the next request of a redirect chain.
+
+use crate::OwnedRequest;
+use crate::consts::names;
+use fluent_uri::component::Authority;
+use fluent_uri::{Uri, UriRef};
+
+/// Headers that describe the body, dropped along with it.
+const CONTENT_HEADERS: &[&str] = &[
+    names::CONTENT_ENCODING,
+    names::CONTENT_LENGTH,
+    names::CONTENT_TYPE,
+    "Content-Language",
+    "Content-Location",
+    names::TRANSFER_ENCODING,
+];
+
+/// How [`follow_redirect_with`] treats credentials.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RedirectPolicy<'a> {
+    /// Headers, matched without regard to case, that are dropped when the
+    /// redirect leaves the origin of the previous request.
+    pub cross_origin_strip: &'a [&'a str],
+}
+
+impl RedirectPolicy<'static> {
+    /// Strips `Authorization`, `Proxy-Authorization` and `Cookie`.
+    pub const DEFAULT: Self = Self {
+        cross_origin_strip: &[names::AUTHORIZATION, "Proxy-Authorization", names::COOKIE],
+    };
+}
+
+impl Default for RedirectPolicy<'static> {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedirectError {
+    /// The status is not one of 301, 302, 303, 307 or 308.
+    NotRedirect(u16),
+    /// The `Location` is not a URI reference, or does not resolve against
+    /// the base.
+    InvalidLocation,
+    /// The target is not an `http` or `https` URI with a host.
+    UnsupportedTarget,
+}
+
+impl core::fmt::Display for RedirectError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::NotRedirect(status) => write!(f, "status {status} is not a redirect"),
+            Self::InvalidLocation => f.write_str("invalid Location"),
+            Self::UnsupportedTarget => f.write_str("redirect target is not an http or https URI with a host"),
+        }
+    }
+}
+
+impl std::error::Error for RedirectError {}
+
+/// Like [`follow_redirect_with`] under [`RedirectPolicy::DEFAULT`].
+pub fn follow_redirect(
+    prev: &OwnedRequest,
+    status: u16,
+    location: &str,
+    base: &Uri<&str>,
+) -> Result<OwnedRequest, RedirectError> {
+    follow_redirect_with(prev, status, location, base, &RedirectPolicy::DEFAULT)
+}
+
+/// The request that follows `prev`, which was sent to `base` and answered
+/// with `status` and `location`.
+///
+/// - 303 turns the request into a `GET` (a `HEAD` stays one) without a body.
+/// - 301 and 302 do the same for a `POST`, as browsers do, and otherwise
+///   keep the method and body.
+/// - 307 and 308 keep the method and body.
+///
+/// A relative `location` is resolved against `base`, any fragment dropped,
+/// and `Host` set to the new authority. Leaving the origin of `base` drops
+/// the headers of the policy.
+pub fn follow_redirect_with(
+    prev: &OwnedRequest,
+    status: u16,
+    location: &str,
+    base: &Uri<&str>,
+    policy: &RedirectPolicy<'_>,
+) -> Result<OwnedRequest, RedirectError> {
+    let (method, keep_body) = match (status, prev.method.as_str()) {
+        (303, "HEAD") => ("HEAD", false),
+        (303, _) | (301 | 302, "POST") => ("GET", false),
+        (301 | 302 | 307 | 308, method) => (method, true),
+        (status, _) => return Err(RedirectError::NotRedirect(status)),
+    };
+
+    let target = UriRef::parse(location)
+        .map_err(|_| RedirectError::InvalidLocation)?
+        .resolve_against(base)
+        .map_err(|_| RedirectError::InvalidLocation)?;
+    let (target_origin, host) =
+        origin(target.scheme().as_str(), target.authority()).ok_or(RedirectError::UnsupportedTarget)?;
+    let same_origin =
+        origin(base.scheme().as_str(), base.authority()).is_some_and(|(base_origin, _)| base_origin == target_origin);
+
+    let mut headers = Vec::with_capacity(prev.headers.len() + 1);
+    headers.push((names::HOST.to_owned(), host.as_bytes().to_vec()));
+    for (name, value) in &prev.headers {
+        let drop = |list: &[&str]| list.iter().any(|h| h.eq_ignore_ascii_case(name));
+        if name.eq_ignore_ascii_case(names::HOST)
+            || (!keep_body && drop(CONTENT_HEADERS))
+            || (!same_origin && drop(policy.cross_origin_strip))
+        {
+            continue;
+        }
+        headers.push((name.clone(), value.clone()));
+    }
+
+    let path = match target.path().as_str() {
+        "" => "/",
+        path => path,
+    };
+    Ok(OwnedRequest {
+        method: method.to_owned(),
+        path: path.to_owned(),
+        queries: target.query().map(|q| q.as_str().to_owned()).into_iter().collect(),
+        headers,
+        version: prev.version.clone(),
+        body: if keep_body { prev.body.clone() } else { Vec::new() },
+    })
+}
+
+/// The lowercased scheme, host and effective port, and the `Host` value.
+fn origin<'u>(scheme: &str, authority: Option<Authority<'u>>) -> Option<((String, String, u16), &'u str)> {
+    let scheme = scheme.to_ascii_lowercase();
+    let default = match scheme.as_str() {
+        "http" => 80,
+        "https" => 443,
+        _ => return None,
+    };
+    let authority = authority?;
+    if authority.host().is_empty() {
+        return None;
+    }
+    let port = authority.port_to_u16().ok()?.unwrap_or(default);
+    let host = match authority.as_str().rsplit_once('@') {
+        Some((_, host)) => host,
+        None => authority.as_str(),
+    };
+    Some(((scheme, authority.host().to_ascii_lowercase(), port), host))
+}
+
+#[cfg(test)]
+fn post() -> OwnedRequest {
+    crate::Request::post()
+        .path("/submit")
+        .v1_1()
+        .header("Host", b"example.com")
+        .header("Authorization", b"Bearer t0ken")
+        .header("Cookie", b"session=1")
+        .header("Content-Type", b"text/plain")
+        .header("Accept", b"*/*")
+        .to_owned_request()
+        .unwrap()
+        .with_body(b"hello".to_vec())
+        .unwrap()
+}
+
+#[cfg(test)]
+fn header<'r>(req: &'r OwnedRequest, name: &str) -> Option<&'r [u8]> {
+    req.headers().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| v)
+}
+
+#[test]
+fn redirect_statuses() {
+    let base = Uri::parse("https://example.com/submit").unwrap();
+    let cases = [(301, "GET", false), (302, "GET", false), (303, "GET", false), (307, "POST", true), (308, "POST", true)];
+    for (status, method, keeps_body) in cases {
+        let next = follow_redirect(&post(), status, "/done", &base).unwrap();
+        assert_eq!(next.method(), method, "{status}");
+        assert_eq!(next.path(), "/done");
+        assert_eq!(next.body().is_empty(), !keeps_body, "{status}");
+        assert_eq!(header(&next, "Content-Type").is_some(), keeps_body, "{status}");
+        assert_eq!(header(&next, "Authorization"), Some(&b"Bearer t0ken"[..]));
+        assert_eq!(header(&next, "Accept"), Some(&b"*/*"[..]));
+    }
+
+    let put = post().with_body(Vec::new()).unwrap();
+    let put = OwnedRequest { method: "PUT".into(), ..put };
+    assert_eq!(follow_redirect(&put, 301, "/b", &base).unwrap().method(), "PUT");
+    assert_eq!(follow_redirect(&put, 303, "/b", &base).unwrap().method(), "GET");
+    let head = OwnedRequest { method: "HEAD".into(), ..post() };
+    assert_eq!(follow_redirect(&head, 303, "/b", &base).unwrap().method(), "HEAD");
+
+    assert_eq!(follow_redirect(&post(), 304, "/b", &base), Err(RedirectError::NotRedirect(304)));
+    assert_eq!(follow_redirect(&post(), 302, "ftp://example.com/", &base), Err(RedirectError::UnsupportedTarget));
+    assert_eq!(follow_redirect(&post(), 302, "a b", &base), Err(RedirectError::InvalidLocation));
+}
+
+#[test]
+fn relative_location() {
+    let base = Uri::parse("https://example.com/a/b/c?x=1").unwrap();
+    let next = follow_redirect(&post(), 307, "../d?y=2#frag", &base).unwrap();
+    assert_eq!(next.path(), "/a/d");
+    assert_eq!(next.queries().collect::<Vec<_>>(), ["y=2"]);
+    assert_eq!(header(&next, "Host"), Some(&b"example.com"[..]));
+
+    let mut buf = Vec::new();
+    next.write_to(&mut buf).unwrap();
+    assert!(buf.starts_with(b"POST /a/d?y=2 HTTP/1.1\r\nHost: example.com\r\n"));
+}
+
+#[test]
+fn cross_origin_strips_credentials() {
+    let base = Uri::parse("https://example.com/submit").unwrap();
+    let next = follow_redirect(&post(), 307, "https://user@other.example:8443/x", &base).unwrap();
+    assert_eq!(header(&next, "Host"), Some(&b"other.example:8443"[..]));
+    assert_eq!(header(&next, "Authorization"), None);
+    assert_eq!(header(&next, "Cookie"), None);
+    assert_eq!(header(&next, "Accept"), Some(&b"*/*"[..]));
+
+    // a different port or scheme is another origin, too
+    let next = follow_redirect(&post(), 307, "http://example.com/x", &base).unwrap();
+    assert_eq!(header(&next, "Authorization"), None);
+    let next = follow_redirect(&post(), 307, "https://EXAMPLE.com:443/x", &base).unwrap();
+    assert_eq!(header(&next, "Authorization"), Some(&b"Bearer t0ken"[..]));
+
+    let policy = RedirectPolicy { cross_origin_strip: &["accept"] };
+    let next = follow_redirect_with(&post(), 307, "https://other.example/", &base, &policy).unwrap();
+    assert_eq!(header(&next, "Authorization"), Some(&b"Bearer t0ken"[..]));
+    assert_eq!(header(&next, "Accept"), None);
+}
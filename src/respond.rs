@@ -0,0 +1,140 @@
+use crate::{EmptyHeaders, Field, Response, consts, version};
+use core::iter::Chain;
+use core::option;
+
+/// What a response has to echo back from the request it answers.
+///
+/// Built by [`Response::respond_to`] and applied with
+/// [`ResponseContext::apply`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResponseContext {
+    minor: u8,
+    keep_alive: bool,
+    head: bool,
+}
+
+impl<'a> Response<'a, EmptyHeaders<'a>, version::UNSPECIFIED> {
+    /// Captures the version, connection and method of a parsed request.
+    ///
+    /// HTTP/1.0 connections close unless the request asked for
+    /// `keep-alive`; HTTP/1.1 connections persist unless it asked for
+    /// `close`.
+    pub fn respond_to(req_version_minor: u8, req_headers: &[httparse::Header], req_method: &str) -> ResponseContext {
+        let mut close = false;
+        let mut keep_alive = false;
+        for header in req_headers {
+            if !header.name.eq_ignore_ascii_case(consts::names::CONNECTION) {
+                continue;
+            }
+            for option in header.value.split(|&ch| ch == b',') {
+                let option = option.trim_ascii();
+                close |= option.eq_ignore_ascii_case(b"close");
+                keep_alive |= option.eq_ignore_ascii_case(b"keep-alive");
+            }
+        }
+
+        ResponseContext {
+            minor: req_version_minor.min(1),
+            keep_alive: !close && (req_version_minor >= 1 || keep_alive),
+            head: req_method == "HEAD",
+        }
+    }
+}
+
+impl ResponseContext {
+    /// Whether the connection stays open after the response.
+    pub fn keep_alive(&self) -> bool {
+        self.keep_alive
+    }
+
+    /// Whether the response must be written without a body, as for `HEAD`.
+    pub fn suppress_body(&self) -> bool {
+        self.head
+    }
+
+    /// Sets the response version to the request's and adds a `Connection`
+    /// header when the default for that version does not hold. The answer
+    /// to a `HEAD` request is marked with [`Response::to_head_request`].
+    pub fn apply<'a, T, V, B>(
+        self,
+        res: Response<'a, T, V, B>,
+    ) -> Response<'a, Chain<T, option::IntoIter<Field<'a>>>, version::Dynamic<'static>, B>
+    where
+        T: Iterator<Item = Field<'a>>,
+    {
+        let (version, connection) = match (self.minor, self.keep_alive) {
+            (0, true) => ("1.0", Some(consts::CONNECTION_KEEP_ALIVE)),
+            (0, false) => ("1.0", None),
+            (_, true) => ("1.1", None),
+            (_, false) => ("1.1", Some(consts::CONNECTION_CLOSE)),
+        };
+
+        let Response {
+            code,
+            reason,
+            head_request,
+            unique_headers,
+            headers,
+            version: _,
+            body,
+        } = res;
+
+        Response {
+            code,
+            reason,
+            head_request: head_request || self.head,
+            unique_headers,
+            headers: headers.chain(connection.map(Field::from)),
+            version: version::Dynamic::new(version),
+            body,
+        }
+    }
+}
+
+#[cfg(test)]
+fn head_for(minor: u8, headers: &[httparse::Header], method: &str) -> (ResponseContext, String) {
+    let ctx = Response::respond_to(minor, headers, method);
+    let mut buf = Vec::new();
+    ctx.apply(Response::new(http::StatusCode::OK)).write_to(&mut buf).unwrap();
+    (ctx, String::from_utf8(buf).unwrap())
+}
+
+#[test]
+fn http_1_0_keep_alive() {
+    let headers = [httparse::Header { name: "connection", value: b"Keep-Alive" }];
+    let (ctx, head) = head_for(0, &headers, "GET");
+    assert!(ctx.keep_alive());
+    assert!(!ctx.suppress_body());
+    assert_eq!(head, "HTTP/1.0 200 OK\r\nConnection: keep-alive\r\n\r\n");
+
+    let (ctx, head) = head_for(0, &[], "GET");
+    assert!(!ctx.keep_alive());
+    assert_eq!(head, "HTTP/1.0 200 OK\r\n\r\n");
+}
+
+#[test]
+fn http_1_1_close() {
+    let headers = [
+        httparse::Header { name: "Accept", value: b"*/*" },
+        httparse::Header { name: "Connection", value: b"TE, close" },
+    ];
+    let (ctx, head) = head_for(1, &headers, "GET");
+    assert!(!ctx.keep_alive());
+    assert_eq!(head, "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n");
+
+    let (ctx, head) = head_for(1, &[], "GET");
+    assert!(ctx.keep_alive());
+    assert_eq!(head, "HTTP/1.1 200 OK\r\n\r\n");
+}
+
+#[test]
+fn head_request() {
+    let (ctx, head) = head_for(1, &[], "HEAD");
+    assert!(ctx.suppress_body());
+    assert_eq!(head, "HTTP/1.1 200 OK\r\n\r\n");
+    assert!(!Response::respond_to(1, &[], "head").suppress_body());
+
+    let mut buf = Vec::new();
+    ctx.apply(Response::new(http::StatusCode::OK)).text("hello").write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 5\r\n\r\n");
+}
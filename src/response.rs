@@ -1,135 +1,763 @@
 use crate::{EmptyHeaders, HeaderWriteError, Version, version};
+use crate::{Body, Field, IntoFields, NoBody};
+use crate::consts::names;
 use core::iter::{self, Chain, Once};
-use httparse::Header;
+use core::option;
+use std::io::Write as _;
 
+/// Like [`Request`](crate::Request), the type parameters default to those of
+/// a new response, so `Response` names what [`Response::new`] returns.
 #[derive(Clone)]
-pub struct Response<T, V> {
-    version: V,
-    code: http::StatusCode,
-    headers: T,
+pub struct Response<'a, T = EmptyHeaders<'a>, V = version::UNSPECIFIED, B = NoBody> {
+    pub(crate) version: V,
+    pub(crate) code: http::StatusCode,
+    pub(crate) reason: Option<&'a str>,
+    pub(crate) head_request: bool,
+    pub(crate) unique_headers: &'static [&'static str],
+    pub(crate) headers: T,
+    pub(crate) body: B,
 }
 
-impl<'a> Response<EmptyHeaders<'a>, version::UNSPECIFIED> {
+/// A response without headers yet, once its version is set.
+pub type BasicResponse<'a, V> = Response<'a, EmptyHeaders<'a>, V>;
+
+impl<'a> Response<'a, EmptyHeaders<'a>, version::UNSPECIFIED> {
     pub fn new(status_code: http::StatusCode) -> Self {
         Self {
             code: status_code,
+            reason: None,
+            head_request: false,
+            unique_headers: &[],
             version: version::UNSPECIFIED,
             headers: EmptyHeaders::new(),
+            body: NoBody,
+        }
+    }
+
+    /// A response with the numeric status `code`, such as one relayed from
+    /// an upstream server. The reason phrase is looked up as for
+    /// [`Response::new`].
+    ///
+    /// Codes outside `100..=599`, the range RFC 9110 defines, fail.
+    pub fn from_code(code: u16) -> Result<Self, InvalidStatusCode> {
+        match code {
+            100..=599 => http::StatusCode::from_u16(code).map(Self::new).map_err(|_| InvalidStatusCode(code)),
+            _ => Err(InvalidStatusCode(code)),
         }
     }
+
+    pub fn ok() -> Self {
+        Self::new(http::StatusCode::OK)
+    }
+
+    pub fn created() -> Self {
+        Self::new(http::StatusCode::CREATED)
+    }
+
+    pub fn no_content() -> Self {
+        Self::new(http::StatusCode::NO_CONTENT)
+    }
+
+    pub fn bad_request() -> Self {
+        Self::new(http::StatusCode::BAD_REQUEST)
+    }
+
+    pub fn not_found() -> Self {
+        Self::new(http::StatusCode::NOT_FOUND)
+    }
+
+    pub fn internal_server_error() -> Self {
+        Self::new(http::StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// An error response for a handler that failed with `err`, such as while
+    /// opening the file it serves:
+    ///
+    /// - `NotFound` is `404 Not Found`,
+    /// - `PermissionDenied` is `403 Forbidden`,
+    /// - `TimedOut` is `504 Gateway Timeout`,
+    /// - and anything else `500 Internal Server Error`.
+    ///
+    /// The body is the reason phrase as plain text. The message of `err` is
+    /// never sent, as it may name paths or other internals.
+    pub fn from_io_error(err: &std::io::Error) -> Response<'a, Chain<EmptyHeaders<'a>, Once<Field<'a>>>, version::UNSPECIFIED, &'a [u8]> {
+        Self::from_io_error_with(err, |_| None)
+    }
+
+    /// Like [`Response::from_io_error`], with the status `map` returns for
+    /// `err`, or the default one for `None`.
+    pub fn from_io_error_with(
+        err: &std::io::Error,
+        map: impl FnOnce(&std::io::Error) -> Option<http::StatusCode>,
+    ) -> Response<'a, Chain<EmptyHeaders<'a>, Once<Field<'a>>>, version::UNSPECIFIED, &'a [u8]> {
+        use http::StatusCode;
+        use std::io::ErrorKind;
+
+        let code = map(err).unwrap_or(match err.kind() {
+            ErrorKind::NotFound => StatusCode::NOT_FOUND,
+            ErrorKind::PermissionDenied => StatusCode::FORBIDDEN,
+            ErrorKind::TimedOut => StatusCode::GATEWAY_TIMEOUT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        });
+        Self::new(code).text(code.canonical_reason().unwrap_or("Error"))
+    }
 }
 
-impl<T, V> Response<T, V> {
-    pub fn version<V2>(self, version: V2) -> Response<T, V2> {
+impl<'a, T, V, B> Response<'a, T, V, B> {
+    pub fn version<V2>(self, version: V2) -> Response<'a, T, V2, B> {
         let Self {
             code,
+            reason,
+            head_request,
+            unique_headers,
             headers,
             version: _,
+            body,
         } = self;
 
         Response {
             code,
+            reason,
+            head_request,
+            unique_headers,
             headers,
             version,
+            body,
         }
     }
 
-    pub fn v1(self) -> Response<T, version::V1> {
+    pub fn v1(self) -> Response<'a, T, version::V1, B> {
         self.version(version::V1)
     }
 
-    pub fn v1_1(self) -> Response<T, version::V1_1> {
+    pub fn v1_1(self) -> Response<'a, T, version::V1_1, B> {
         self.version(version::V1_1)
     }
+
+    /// Sets a version picked at runtime, such as that of the request.
+    pub fn any_version(self, version: version::AnyVersion) -> Response<'a, T, version::AnyVersion, B> {
+        self.version(version)
+    }
+
+    /// Replaces the reason phrase, which is otherwise the canonical one
+    /// for the status, or empty for a code without one.
+    ///
+    /// A phrase with a control other than a tab, such as CR or LF, fails
+    /// the write with [`ResponseWriteError::InvalidReason`].
+    pub fn reason(mut self, reason: &'a str) -> Self {
+        self.reason = Some(reason);
+        self
+    }
+
+    pub(crate) fn checked_reason(&self) -> Result<&str, ResponseWriteError> {
+        let reason = reason_phrase(self.code, self.reason);
+        match reason.bytes().any(|ch| ch.is_ascii_control() && ch != b'\t') {
+            true => Err(ResponseWriteError::InvalidReason),
+            false => Ok(reason),
+        }
+    }
+
+    /// Like [`Request::unique_headers`](crate::Request::unique_headers).
+    pub fn unique_headers(mut self, names: &'static [&'static str]) -> Self {
+        assert!(names.len() <= crate::field::MAX_UNIQUE_HEADERS, "more than 64 unique headers");
+        self.unique_headers = names;
+        self
+    }
+
+    /// Marks the response as the answer to a `HEAD` request, so the head
+    /// is written without the body. Its framing is still announced, as the
+    /// same `GET` would have been.
+    pub fn to_head_request(mut self) -> Self {
+        self.head_request = true;
+        self
+    }
+
+    /// Whether the body is written after the head; a `1xx`, `204` or `304`
+    /// response, or one to `HEAD`, has none (RFC 9110, section 6.4.1), even
+    /// if one is set.
+    pub(crate) fn sends_body(&self) -> bool {
+        !(self.head_request || self.code.is_informational() || matches!(self.code.as_u16(), 204 | 304))
+    }
+
+    pub(crate) fn set_body<B2: Body>(self, body: B2) -> Response<'a, T, V, B2> {
+        let Self {
+            code,
+            reason,
+            head_request,
+            unique_headers,
+            headers,
+            version,
+            body: _,
+        } = self;
+
+        Response {
+            code,
+            reason,
+            head_request,
+            unique_headers,
+            headers,
+            version,
+            body,
+        }
+    }
 }
 
-impl<'a, T, V> Response<T, V>
+impl<'a, T, V, B> Response<'a, T, V, B>
 where
-    T: Iterator<Item = Header<'a>>,
+    T: Iterator<Item = Field<'a>>,
 {
-    pub fn header(self, name: &'a str, value: &'a [u8]) -> Response<Chain<T, Once<Header<'a>>>, V> {
-        let h = Header { name, value };
-        self.headers(iter::once(h))
+    pub fn header(self, name: &'a str, value: &'a [u8]) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::new(name, value))
+    }
+
+    /// Like [`Request::header_owned`](crate::Request::header_owned).
+    pub fn header_owned(self, name: &'a str, value: impl Into<Vec<u8>>) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::owned(name, value.into()))
+    }
+
+    /// Like [`Request::header_str`](crate::Request::header_str).
+    pub fn header_str(self, name: &'a str, value: &'a str) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::new(name, value.as_bytes()))
+    }
+
+    /// Like [`Request::header_int`](crate::Request::header_int).
+    pub fn header_int(self, name: &'a str, value: u64) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::int(name, value))
+    }
+
+    /// Like [`Request::try_header`](crate::Request::try_header).
+    #[allow(clippy::type_complexity)]
+    pub fn try_header(
+        self,
+        name: &'a str,
+        value: &'a [u8],
+    ) -> Result<Response<'a, Chain<T, Once<Field<'a>>>, V, B>, HeaderWriteError> {
+        Field::new(name, value).validate()?;
+        Ok(self.field(Field::trusted(name, value)))
+    }
+
+    pub fn headers<H>(self, h: H) -> Response<'a, Chain<T, IntoFields<'a, H>>, V, B>
+    where
+        H: IntoIterator,
+        H::Item: Into<Field<'a>>,
+    {
+        self.fields(h.into_iter().map(Into::into as fn(H::Item) -> Field<'a>))
+    }
+
+    /// Like [`Request::header_base64`](crate::Request::header_base64).
+    #[cfg(feature = "base64")]
+    pub fn header_base64(self, name: &'a str, raw: &'a [u8]) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.header_base64_with(name, raw, crate::Base64Alphabet::Standard)
+    }
+
+    #[cfg(feature = "base64")]
+    pub fn header_base64_with(
+        self,
+        name: &'a str,
+        raw: &'a [u8],
+        alphabet: crate::Base64Alphabet,
+    ) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::base64(name, raw, alphabet))
+    }
+
+    /// Adds a `Date` header of `time` as an IMF-fixdate, truncated to the
+    /// second. A time before 1970 or after 9999 fails the write with an
+    /// invalid header.
+    #[cfg(feature = "date")]
+    pub fn date(self, time: std::time::SystemTime) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(match crate::date::format(time) {
+            Some(date) => Field::inline(names::DATE, crate::field::Inline::new(&date)),
+            None => Field::invalid(names::DATE, 0),
+        })
+    }
+
+    /// Adds a `Date` header taken from `cache`, which re-formats the time at
+    /// most once per second.
+    #[cfg(feature = "date")]
+    pub fn date_cached(self, cache: &crate::DateCache) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        let value = crate::field::Inline::new(&cache.now_value());
+        self.field(Field::inline("Date", value))
+    }
+
+    /// Like [`Request::priority`](crate::Request::priority), for a server
+    /// overriding the priority the client asked for.
+    pub fn priority(self, urgency: u8, incremental: bool) -> Response<'a, Chain<T, option::IntoIter<Field<'a>>>, V, B> {
+        self.fields(Field::priority(urgency, incremental).into_iter())
+    }
+
+    /// Adds `Deprecation` with the time the resource was deprecated, as a
+    /// structured field date such as `@1688169599`, or `true` if `when` is
+    /// unknown. Times before the epoch are written as `@0`.
+    pub fn deprecation(self, when: Option<std::time::SystemTime>) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(deprecation_field(when))
+    }
+
+    /// Adds `Sunset` with the time the resource stops responding, as an
+    /// IMF-fixdate truncated to the second.
+    #[cfg(feature = "date")]
+    pub fn sunset(self, when: std::time::SystemTime) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(sunset_field(when))
+    }
+
+    /// Adds `Deprecation` and `Sunset` like [`Response::deprecation`] and
+    /// [`Response::sunset`], and a `Link` with `rel="deprecation"` to the
+    /// documentation at `docs_url`.
+    ///
+    /// A `docs_url` that is not a URI reference fails the write.
+    #[cfg(feature = "date")]
+    pub fn deprecation_notice(
+        self,
+        when: Option<std::time::SystemTime>,
+        sunset_at: std::time::SystemTime,
+        docs_url: &'a str,
+    ) -> Response<'a, Chain<T, core::array::IntoIter<Field<'a>, 3>>, V, B> {
+        let link = crate::origin::link(names::LINK, docs_url, b">; rel=\"deprecation\"; type=\"text/html\"");
+        self.fields([deprecation_field(when), sunset_field(sunset_at), link].into_iter())
+    }
+
+    /// Picks a coding with [`choose_encoding`](crate::choose_encoding) and
+    /// adds it as `Content-Encoding`, followed by `Vary: Accept-Encoding`.
+    ///
+    /// Returns the coding the body has to be encoded with. `identity` adds
+    /// no `Content-Encoding`, and neither does `None`, which leaves the
+    /// caller to answer `415` or send the body unencoded.
+    #[allow(clippy::type_complexity)]
+    pub fn negotiate_content_encoding(
+        self,
+        accept_encoding: &str,
+        available: &[&'a str],
+    ) -> (Option<&'a str>, Response<'a, Chain<T, Chain<option::IntoIter<Field<'a>>, Once<Field<'a>>>>, V, B>) {
+        let coding = crate::choose_encoding(accept_encoding, available);
+        let content_encoding = coding
+            .filter(|c| !c.eq_ignore_ascii_case("identity"))
+            .map(|c| Field::new(names::CONTENT_ENCODING, c.as_bytes()));
+        let vary = iter::once(crate::consts::VARY_ACCEPT_ENCODING.into());
+        (coding, self.fields(content_encoding.into_iter().chain(vary)))
+    }
+
+    /// Adds `Content-Security-Policy` with `csp`, which fails the write if
+    /// [`Csp::check`](crate::Csp::check) rejects it.
+    pub fn content_security_policy(self, csp: &'a crate::Csp<'a>) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::csp(names::CONTENT_SECURITY_POLICY, csp))
+    }
+
+    /// Like [`Response::content_security_policy`], but only reports
+    /// violations instead of enforcing the policy.
+    pub fn content_security_policy_report_only(
+        self,
+        csp: &'a crate::Csp<'a>,
+    ) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::csp(names::CONTENT_SECURITY_POLICY_REPORT_ONLY, csp))
+    }
+
+    /// Adds `Server-Timing` with the metrics of `timing`; an empty list, a
+    /// name that is not a token or a control byte in a description fails
+    /// the write.
+    pub fn server_timing(self, timing: &'a crate::ServerTiming<'a>) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::server_timing(names::SERVER_TIMING, timing))
+    }
+
+    /// Adds a `Set-Cookie` header; each call adds its own line, as cookies
+    /// cannot be folded into one. A cookie that fails
+    /// [`SetCookie`](crate::SetCookie)'s checks fails the write.
+    pub fn set_cookie(self, cookie: &'a crate::SetCookie<'a>) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::set_cookie(cookie))
+    }
+
+    /// Like [`Request::connection_close`](crate::Request::connection_close).
+    pub fn connection_close(self) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(crate::consts::CONNECTION_CLOSE.into())
+    }
+
+    /// Like [`Request::connection_keep_alive`](crate::Request::connection_keep_alive).
+    pub fn connection_keep_alive(self) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(crate::consts::CONNECTION_KEEP_ALIVE.into())
+    }
+
+    /// Like [`Request::content_type`](crate::Request::content_type).
+    pub fn content_type(self, mime: &'a str) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::new(names::CONTENT_TYPE, mime.as_bytes()))
+    }
+
+    /// Adds `Content-Type: application/json` without setting a body, unlike
+    /// `Response::json`.
+    pub fn content_type_json(self) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(crate::consts::CONTENT_TYPE_JSON.into())
+    }
+
+    /// Adds `Content-Type: application/x-www-form-urlencoded`.
+    pub fn content_type_form(self) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(crate::consts::CONTENT_TYPE_FORM.into())
+    }
+
+    /// Adds `Content-Type: text/plain; charset=utf-8` without setting a body,
+    /// unlike [`Response::text`].
+    pub fn content_type_text(self) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(crate::consts::CONTENT_TYPE_TEXT.into())
     }
 
-    pub fn headers<H: Iterator<Item = Header<'a>>>(self, h: H) -> Response<Chain<T, H>, V> {
+    /// Adds `Content-Type: text/html; charset=utf-8` without setting a body,
+    /// unlike [`Response::html`].
+    pub fn content_type_html(self) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(crate::consts::CONTENT_TYPE_HTML.into())
+    }
+
+    /// Splices an already serialized header line, such as one a proxy kept
+    /// from the original message, into the head verbatim.
+    ///
+    /// A missing trailing CRLF is added. Only the name before the first `:`
+    /// is checked, along with stray CR, LF or NUL bytes, which fail the write
+    /// with [`HeaderWriteError::InvalidRawLine`].
+    pub fn raw_header_line(self, line: &'a [u8]) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.field(Field::raw(line))
+    }
+
+    /// Like [`Response::raw_header_line`], for each of `lines` in order.
+    pub fn raw_header_lines<L>(self, lines: L) -> Response<'a, Chain<T, IntoFields<'a, L>>, V, B>
+    where
+        L: IntoIterator<Item = &'a [u8]>,
+    {
+        self.fields(lines.into_iter().map(Field::raw as fn(&'a [u8]) -> Field<'a>))
+    }
+
+    pub(crate) fn field(self, f: Field<'a>) -> Response<'a, Chain<T, Once<Field<'a>>>, V, B> {
+        self.fields(iter::once(f))
+    }
+
+    pub(crate) fn fields<H: Iterator<Item = Field<'a>>>(self, h: H) -> Response<'a, Chain<T, H>, V, B> {
         let Self {
             code,
+            reason,
+            head_request,
+            unique_headers,
             headers,
             version,
+            body,
         } = self;
 
         let headers = headers.chain(h);
 
         Response {
             code,
+            reason,
+            head_request,
+            unique_headers,
             headers,
             version,
+            body,
         }
     }
 }
 
-impl<'a, T, V> Response<T, V>
+/// The body setters, which a response can only take one of.
+impl<'a, T, V> Response<'a, T, V>
+where
+    T: Iterator<Item = Field<'a>>,
+{
+    /// Sends `bytes` as the body, with a `Content-Length` of its length
+    /// unless a `Content-Length` or `Transfer-Encoding` header is already set.
+    ///
+    /// A `1xx`, `204` or `304` response is written without the body; a `304`
+    /// keeps the `Content-Length`, the others get none.
+    pub fn body(self, bytes: &'a [u8]) -> Response<'a, T, V, &'a [u8]> {
+        self.set_body(bytes)
+    }
+
+    /// Like [`Request::body_from_reader`](crate::Request::body_from_reader).
+    pub fn body_from_reader<R: std::io::Read>(self, reader: R, len: u64) -> Response<'a, T, V, crate::ReaderBody<R>> {
+        self.set_body(crate::ReaderBody::new(reader, len))
+    }
+
+    /// Sends each of `chunks` as it is produced, as a chunked body; see
+    /// [`ChunkedBody`](crate::ChunkedBody).
+    pub fn chunked<I>(self, chunks: I) -> Response<'a, T, V, crate::ChunkedBody<I::IntoIter>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+    {
+        self.set_body(crate::ChunkedBody::new(chunks))
+    }
+
+    /// Like [`Response::chunked`], with `trailers` after the last chunk; see
+    /// [`ChunkedBody::trailers`](crate::ChunkedBody::trailers).
+    pub fn chunked_with_trailers<'t, I, H>(
+        self,
+        chunks: I,
+        trailers: H,
+    ) -> Response<'a, T, V, crate::ChunkedBody<I::IntoIter, H::IntoIter>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<[u8]>,
+        H: IntoIterator<Item = Field<'t>>,
+        H::IntoIter: Clone,
+    {
+        self.set_body(crate::ChunkedBody::new(chunks).trailers(trailers))
+    }
+
+    /// Sends `body` as `text/plain; charset=utf-8`, with a `Content-Length`
+    /// of its length in bytes.
+    pub fn text(self, body: &'a str) -> Response<'a, Chain<T, Once<Field<'a>>>, V, &'a [u8]> {
+        self.field(crate::consts::CONTENT_TYPE_TEXT.into()).set_body(body.as_bytes())
+    }
+
+    /// Like [`Response::text`], as `text/html; charset=utf-8`.
+    pub fn html(self, body: &'a str) -> Response<'a, Chain<T, Once<Field<'a>>>, V, &'a [u8]> {
+        self.field(crate::consts::CONTENT_TYPE_HTML.into()).set_body(body.as_bytes())
+    }
+
+    /// Sends `value` as an `application/json` body.
+    ///
+    /// The value is serialized when the response is written, before anything
+    /// else, so that a failure is [`ResponseWriteError::BodySerialize`] with
+    /// nothing written, and a success has an exact `Content-Length`.
+    #[cfg(feature = "json")]
+    pub fn json<J: serde::Serialize + ?Sized>(self, value: &'a J) -> Response<'a, Chain<T, Once<Field<'a>>>, V, crate::Json<'a, J>> {
+        self.field(crate::consts::CONTENT_TYPE_JSON.into()).set_body(crate::Json::new(value))
+    }
+
+    /// Like [`Response::json`], but serializes `value` while writing it as a
+    /// chunked body, for values too large to buffer.
+    #[cfg(feature = "json")]
+    pub fn json_stream<J: serde::Serialize + ?Sized>(
+        self,
+        value: &'a J,
+    ) -> Response<'a, Chain<T, Once<Field<'a>>>, V, crate::JsonStream<'a, J>> {
+        self.field(crate::consts::CONTENT_TYPE_JSON.into()).set_body(crate::JsonStream::new(value))
+    }
+}
+
+impl<'a, T, V, B> Response<'a, T, V, B>
 where
-    T: Iterator<Item = Header<'a>>,
+    T: Iterator<Item = Field<'a>>,
     V: Version<'a>,
+    B: Body,
 {
-    pub fn write_to<W: std::io::Write>(&mut self, w: &mut W) -> Result<usize, ResponseWriteError> {
-        let version = self.version.as_str();
+    /// Like [`Request::write_to`](crate::Request::write_to).
+    pub fn write_to<W: std::io::Write + ?Sized>(&mut self, w: &mut W) -> Result<usize, ResponseWriteError> {
+        let mut w = crate::Counter::new(w);
+        self.write_counted(&mut w).map_err(|e| e.with_written(w.written))
+    }
 
-        if version.len() != 3
-            || !version
-                .as_bytes()
-                .iter()
-                .any(|ch| ch.is_ascii_digit() || matches!(ch, b'.'))
-        {
-            return Err(ResponseWriteError::InvalidVersion);
+    /// Like [`Request::write_to_fmt`](crate::Request::write_to_fmt).
+    pub fn write_to_fmt<F: core::fmt::Write + ?Sized>(&mut self, f: &mut F) -> Result<usize, ResponseWriteError> {
+        let mut w = crate::fmt_write::Utf8Writer::new(f);
+        let len = self.write_to(&mut w)?;
+        w.finish().map_err(|e| ResponseWriteError::from(e).with_written(len))?;
+        Ok(len)
+    }
+
+    /// Like [`Request::write_to_repeatable`](crate::Request::write_to_repeatable).
+    pub fn write_to_repeatable<W: std::io::Write + ?Sized>(&self, w: &mut W) -> Result<usize, ResponseWriteError>
+    where
+        Self: Clone,
+    {
+        self.clone().write_to(w)
+    }
+
+    /// Like [`Request::estimate_len`](crate::Request::estimate_len).
+    pub fn estimate_len(&self) -> Result<usize, ResponseWriteError>
+    where
+        Self: Clone,
+    {
+        self.clone().write_to(&mut std::io::sink())
+    }
+
+    /// Like [`Request::validate`](crate::Request::validate).
+    pub fn validate(&self) -> Result<(), ResponseWriteError>
+    where
+        Self: Clone,
+    {
+        self.estimate_len().map(drop)
+    }
+
+    /// Like [`Request::to_vec`](crate::Request::to_vec).
+    pub fn to_vec(&mut self) -> Result<Vec<u8>, ResponseWriteError> {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Like [`Response::write_to`], but also returns a copy of the bytes that `w`
+    /// accepted.
+    pub fn write_to_captured<W: std::io::Write + ?Sized>(
+        &mut self,
+        w: &mut W,
+    ) -> Result<(usize, Vec<u8>), ResponseWriteError> {
+        let mut tee = crate::TeeWriter::new(w, Vec::new());
+        let len = self.write_to(&mut tee)?;
+        Ok((len, tee.into_parts().1))
+    }
+
+    fn write_counted<W: std::io::Write + ?Sized>(&mut self, w: &mut W) -> Result<usize, ResponseWriteError> {
+        let len = self.write_head(w)?;
+        if !self.sends_body() {
+            return Ok(len);
         }
+        Ok(len + self.body.write_body(w)?)
+    }
+
+    /// The framing of the body. A `304` still announces the length the full
+    /// response would have, while a `1xx` or `204` must not announce any.
+    pub(crate) fn body_framing(&mut self) -> Result<crate::Framing, crate::BodyError> {
+        if self.code.is_informational() || self.code == http::StatusCode::NO_CONTENT {
+            return Ok(crate::Framing::None);
+        }
+        self.body.framing()
+    }
+
+    /// Writes everything up to and including the empty line that ends the
+    /// head, once every check of it passed.
+    pub(crate) fn write_head<W: std::io::Write + ?Sized>(&mut self, w: &mut W) -> Result<usize, ResponseWriteError> {
+        let version = self.checked_version()?;
+        let framing = self.body_framing().map_err(ResponseWriteError::BodySerialize)?;
 
         let code = self.code.as_str();
-        let reason = self.code.canonical_reason().unwrap_or_default();
+        let reason = self.checked_reason()?;
 
-        write!(w, "HTTP/{version} {code} {reason}\r\n")?;
+        let mut head = crate::Stage::new();
+        write!(head, "HTTP/{version} {code} {reason}\r\n")?;
 
         let mut len = 9 + version.len() + code.len() + reason.len();
+        let mut framed = crate::field::FramingHeaders::default();
+        let mut unique = crate::field::UniqueHeaders::new(self.unique_headers);
 
         for header in &mut self.headers {
-            len += crate::write_header(w, header).map_err(|e| (len, e))?;
+            header.validate().map_err(|e| (len, e))?;
+            if framed.conflicts(&header) {
+                return Err(ResponseWriteError::ConflictingFraming { buffer_offset: 0 });
+            }
+            if unique.repeats(&header) {
+                return Err(ResponseWriteError::DuplicateHeader { buffer_offset: len });
+            }
+            // SAFETY: header was validated above
+            len += unsafe { header.write_unchecked(&mut head)? };
         }
+        if framed.disagrees(framing) {
+            return Err(ResponseWriteError::ConflictingFraming { buffer_offset: 0 });
+        }
+
+        len += crate::body::write_framing(&mut head, framing, framed.any())?;
+        w.write_all(head.as_bytes())?;
+        Ok(len)
+    }
 
-        write!(w, "\r\n")?;
-        Ok(len + 2)
+    pub(crate) fn checked_version(&self) -> Result<&'a str, ResponseWriteError> {
+        let version = self.version.as_str();
+
+        if !self.version.is_known_valid() && !crate::version::is_valid(version)
+        {
+            return Err(ResponseWriteError::InvalidVersion);
+        }
+        Ok(version)
     }
 
     /// # Safety
     ///
     /// Caller must guarantee that all response fields are valid.
-    pub unsafe fn write_to_unchecked<W: std::io::Write>(&mut self, w: &mut W) -> std::io::Result<usize> {
+    ///
+    /// A body that fails to encode is reported as [`std::io::ErrorKind::Other`].
+    pub unsafe fn write_to_unchecked<W: std::io::Write + ?Sized>(&mut self, w: &mut W) -> std::io::Result<usize> {
+        let framing = self.body_framing().map_err(std::io::Error::other)?;
         let code = self.code.as_str();
-        let reason = self.code.canonical_reason().unwrap_or_default();
+        let reason = reason_phrase(self.code, self.reason);
         let version = self.version.as_str();
 
         write!(w, "HTTP/{version} {code} {reason}\r\n")?;
 
         let mut len = 9 + version.len() + code.len() + reason.len();
+        let mut framed = false;
 
         for header in &mut self.headers {
-            len += unsafe { crate::write_header_unchecked(w, header)? };
+            framed |= header.is_framing();
+            len += unsafe { header.write_unchecked(w)? };
+        }
+
+        len += crate::body::write_framing(w, framing, framed)?;
+        if !self.sends_body() {
+            return Ok(len);
         }
+        Ok(len + self.body.write_body(w)?)
+    }
+}
 
-        write!(w, "\r\n")?;
-        Ok(len + 2)
+/// The reason phrase the status line is written with, split from the
+/// response so the headers can be borrowed alongside it.
+pub(crate) fn reason_phrase(code: http::StatusCode, reason: Option<&str>) -> &str {
+    match reason {
+        Some(reason) => reason,
+        None => code.canonical_reason().unwrap_or_default(),
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+fn deprecation_field<'a>(when: Option<std::time::SystemTime>) -> Field<'a> {
+    let Some(when) = when else {
+        return Field::trusted(names::DEPRECATION, b"true");
+    };
+    let secs = when.duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    Field::inline(names::DEPRECATION, crate::field::Inline::sf_date(secs))
+}
+
+#[cfg(feature = "date")]
+fn sunset_field<'a>(when: std::time::SystemTime) -> Field<'a> {
+    let value = crate::field::Inline::new(&crate::date::format_clamped(when));
+    Field::inline(names::SUNSET, value)
+}
+
+/// Like the `Display` of a [`Request`](crate::Request).
+impl<'a, T, V, B> core::fmt::Display for Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+    B: Body,
+    Self: Clone,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        crate::fmt_write::display_lossy(f, self.clone().to_vec())
+    }
+}
+
+/// The status code is not a three-digit code from `100` to `599`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidStatusCode(pub u16);
+
+impl core::fmt::Display for InvalidStatusCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "status code {} is not from 100 to 599", self.0)
+    }
+}
+
+impl std::error::Error for InvalidStatusCode {}
+
+#[derive(Debug)]
 pub enum ResponseWriteError {
     InvalidVersion,
+    /// The phrase of [`Response::reason`] holds a control byte.
+    InvalidReason,
     InvalidHeader {
+        /// Where the invalid header starts in the head; nothing has been written.
         buffer_offset: usize,
         err: HeaderWriteError,
     },
-    Io,
+    /// Like [`RequestWriteError::ConflictingFraming`](crate::RequestWriteError::ConflictingFraming).
+    ConflictingFraming { buffer_offset: usize },
+    /// Like [`RequestWriteError::DuplicateHeader`](crate::RequestWriteError::DuplicateHeader).
+    DuplicateHeader { buffer_offset: usize },
+    /// The body could not be encoded; nothing was written.
+    BodySerialize(crate::BodyError),
+    Io {
+        source: std::io::Error,
+        /// Bytes the writer accepted before it failed.
+        written: usize,
+    },
+}
+
+impl ResponseWriteError {
+    pub(crate) fn with_written(self, written: usize) -> Self {
+        match self {
+            Self::Io { source, .. } => Self::Io { source, written },
+            e => e,
+        }
+    }
 }
 
 impl From<(usize, HeaderWriteError)> for ResponseWriteError {
@@ -139,8 +767,47 @@ impl From<(usize, HeaderWriteError)> for ResponseWriteError {
 }
 
 impl From<std::io::Error> for ResponseWriteError {
-    fn from(_: std::io::Error) -> ResponseWriteError {
-       ResponseWriteError::Io
+    /// The byte count is filled in by the write method that saw the error.
+    fn from(source: std::io::Error) -> ResponseWriteError {
+        ResponseWriteError::Io { source, written: 0 }
+    }
+}
+
+impl core::fmt::Display for ResponseWriteError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidVersion => f.write_str("invalid HTTP version"),
+            Self::InvalidReason => f.write_str("invalid reason phrase"),
+            Self::InvalidHeader { buffer_offset, err } => write!(f, "{err}, after {buffer_offset} bytes"),
+            Self::ConflictingFraming { buffer_offset } => {
+                write!(f, "conflicting framing headers, after {buffer_offset} bytes")
+            }
+            Self::DuplicateHeader { buffer_offset } => write!(f, "repeated single-value header, after {buffer_offset} bytes"),
+            Self::BodySerialize(err) => write!(f, "failed to encode the body: {err}"),
+            Self::Io { source, written } => write!(f, "I/O error after {written} bytes: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for ResponseWriteError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidHeader { err, .. } => Some(err),
+            Self::BodySerialize(err) => Some(&**err),
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ResponseWriteError> for std::io::Error {
+    /// Like the conversion of a
+    /// [`RequestWriteError`](crate::RequestWriteError).
+    fn from(err: ResponseWriteError) -> std::io::Error {
+        match err {
+            ResponseWriteError::Io { source, .. } => source,
+            err => std::io::Error::new(std::io::ErrorKind::InvalidData, err.to_string()),
+        }
     }
 }
 
@@ -162,3 +829,571 @@ fn response() {
     assert!(pres.parse(&buf).unwrap().is_complete());
     assert_eq!(pres.headers.len(), 3)
 }
+
+#[cfg(feature = "date")]
+#[test]
+fn response_date_cached() {
+    let cache = crate::DateCache::new();
+    let mut res = Response::new(http::StatusCode::OK).v1_1().date_cached(&cache);
+
+    let mut buf = Vec::new();
+    res.write_to(&mut buf).unwrap();
+
+    let mut headers = [httparse::EMPTY_HEADER; 4];
+    let mut pres = httparse::Response::new(&mut headers);
+    assert!(pres.parse(&buf).unwrap().is_complete());
+    assert_eq!(pres.headers[0].name, "Date");
+    assert_eq!(pres.headers[0].value.len(), crate::date::IMF_FIXDATE_LEN);
+}
+
+#[test]
+fn negotiate_content_encoding() {
+    let (coding, mut res) = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .negotiate_content_encoding("gzip;q=1.0, br;q=0.8, *;q=0.1", &["br", "gzip"]);
+    assert_eq!(coding, Some("gzip"));
+    let mut buf = Vec::new();
+    res.write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\nVary: Accept-Encoding\r\n\r\n");
+
+    let (coding, mut res) = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .negotiate_content_encoding("br;q=0", &["br", "identity"]);
+    assert_eq!(coding, Some("identity"));
+    let mut buf = Vec::new();
+    res.write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nVary: Accept-Encoding\r\n\r\n");
+}
+
+#[test]
+fn content_security_policy() {
+    use crate::{Csp, Source};
+
+    let csp = Csp::new().default_src(&[Source::SelfOrigin]).frame_ancestors(&[Source::None]);
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::OK)
+        .v1_1()
+        .content_security_policy(&csp)
+        .content_security_policy_report_only(&csp)
+        .write_to(&mut buf)
+        .unwrap();
+    assert_eq!(
+        buf,
+        b"HTTP/1.1 200 OK\r\n\
+          Content-Security-Policy: default-src 'self'; frame-ancestors 'none'\r\n\
+          Content-Security-Policy-Report-Only: default-src 'self'; frame-ancestors 'none'\r\n\r\n"
+    );
+
+    let csp = Csp::new().default_src(&[Source::None, Source::SelfOrigin]);
+    let err = Response::new(http::StatusCode::OK).v1_1().content_security_policy(&csp).write_to(&mut Vec::new());
+    assert!(matches!(err, Err(ResponseWriteError::InvalidHeader { .. })));
+}
+
+#[test]
+fn response_raw_header_line() {
+    let mut res = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .raw_header_line(b"Server:  upstream/1.0")
+        .header("a", b"b");
+
+    let mut buf = Vec::new();
+    let len = res.write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nServer:  upstream/1.0\r\na: b\r\n\r\n");
+    assert_eq!(len, buf.len());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn response_json() {
+    let value = serde_json::json!({ "ok": true, "items": (0..5000).collect::<Vec<_>>() });
+
+    let mut buf = Vec::new();
+    let len = Response::new(http::StatusCode::OK).v1_1().json(&value).write_to(&mut buf).unwrap();
+    assert_eq!(len, buf.len());
+    let body = serde_json::to_vec(&value).unwrap();
+    let head = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n", body.len());
+    assert_eq!(buf, [head.as_bytes(), &body].concat());
+
+    let mut streamed = Vec::new();
+    let len = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .json_stream(&value)
+        .write_to(&mut streamed)
+        .unwrap();
+    assert_eq!(len, streamed.len());
+    let head = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n";
+    let decoded = crate::body::decode_chunked(&streamed[head.len()..]);
+    assert_eq!(serde_json::from_slice::<serde_json::Value>(&decoded).unwrap(), value);
+}
+
+#[test]
+fn response_io_error_written() {
+    let new = || Response::new(http::StatusCode::OK).v1_1().header("a", b"b");
+    let head_len = new().write_to(&mut Vec::new()).unwrap();
+
+    for n in [0, 8, 17, 20, head_len - 1] {
+        let err = new()
+            .write_to(&mut crate::request::FailAfter { remaining: n })
+            .unwrap_err();
+        match err {
+            ResponseWriteError::Io { written, .. } => assert_eq!(written, n),
+            e => panic!("{e:?}"),
+        }
+    }
+}
+
+#[test]
+fn server_timing() {
+    use core::time::Duration;
+
+    let timing = crate::ServerTiming::new()
+        .metric("db")
+        .dur(Duration::from_micros(53_200))
+        .desc("cache miss")
+        .metric("app")
+        .dur(Duration::from_micros(47_200));
+    let mut buf = Vec::with_capacity(256);
+    let mut res = Response::new(http::StatusCode::OK).v1_1().server_timing(&timing);
+    crate::no_alloc::assert_no_alloc("server_timing", || res.write_to(&mut buf).unwrap());
+    assert_eq!(
+        buf,
+        b"HTTP/1.1 200 OK\r\nServer-Timing: db;dur=53.2;desc=\"cache miss\", app;dur=47.2\r\n\r\n"
+    );
+
+    let bad = crate::ServerTiming::new().metric("db;dur");
+    let err = Response::new(http::StatusCode::OK).v1_1().server_timing(&bad).write_to(&mut Vec::new());
+    assert!(matches!(
+        err,
+        Err(ResponseWriteError::InvalidHeader { err: HeaderWriteError::InvalidValue(2), .. })
+    ));
+}
+
+#[test]
+fn deprecation() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let head = |res: &mut Response<'static, _, _>| {
+        let mut buf = Vec::new();
+        res.write_to(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+    let deprecated = UNIX_EPOCH + Duration::from_secs(1_688_169_599);
+    assert_eq!(
+        head(&mut Response::new(http::StatusCode::OK).v1_1().deprecation(Some(deprecated))),
+        "HTTP/1.1 200 OK\r\nDeprecation: @1688169599\r\n\r\n"
+    );
+    assert_eq!(
+        head(&mut Response::new(http::StatusCode::OK).v1_1().deprecation(None)),
+        "HTTP/1.1 200 OK\r\nDeprecation: true\r\n\r\n"
+    );
+    assert_eq!(
+        head(&mut Response::new(http::StatusCode::OK).v1_1().deprecation(Some(UNIX_EPOCH - Duration::from_secs(1)))),
+        "HTTP/1.1 200 OK\r\nDeprecation: @0\r\n\r\n"
+    );
+}
+
+#[cfg(feature = "date")]
+#[test]
+fn date() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let mut buf = Vec::new();
+    let time = UNIX_EPOCH + Duration::from_millis(784_111_777_500);
+    Response::ok().v1_1().date(time).write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nDate: Sun, 06 Nov 1994 08:49:37 GMT\r\n\r\n");
+
+    for bad in [UNIX_EPOCH - Duration::from_secs(1), UNIX_EPOCH + Duration::from_secs(253_402_300_800)] {
+        let err = Response::ok().v1_1().date(bad).write_to(&mut Vec::new());
+        assert!(matches!(err, Err(ResponseWriteError::InvalidHeader { buffer_offset: 17, .. })));
+    }
+}
+
+#[cfg(feature = "date")]
+#[test]
+fn sunset() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let sunset = UNIX_EPOCH + Duration::from_secs(1_830_297_600);
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::OK).v1_1().sunset(sunset).write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nSunset: Sat, 01 Jan 2028 00:00:00 GMT\r\n\r\n");
+
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::OK)
+        .v1_1()
+        .deprecation_notice(Some(UNIX_EPOCH + Duration::from_secs(1_688_169_599)), sunset, "https://api.example.com/docs/v1")
+        .write_to(&mut buf)
+        .unwrap();
+    assert_eq!(
+        buf,
+        b"HTTP/1.1 200 OK\r\n\
+          Deprecation: @1688169599\r\n\
+          Sunset: Sat, 01 Jan 2028 00:00:00 GMT\r\n\
+          Link: <https://api.example.com/docs/v1>; rel=\"deprecation\"; type=\"text/html\"\r\n\r\n"
+    );
+
+    let err = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .deprecation_notice(None, sunset, "https://api.example.com/a b")
+        .write_to(&mut Vec::new());
+    assert!(matches!(
+        err,
+        Err(ResponseWriteError::InvalidHeader { err: HeaderWriteError::InvalidValue(26), .. })
+    ));
+}
+
+#[test]
+fn text_and_html() {
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::NOT_FOUND).v1_1().text("nope").write_to(&mut buf).unwrap();
+    assert_eq!(
+        buf,
+        b"HTTP/1.1 404 Not Found\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 4\r\n\r\nnope"
+    );
+
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::OK).v1_1().html("<p>hi</p>").write_to(&mut buf).unwrap();
+    assert_eq!(
+        buf,
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: 9\r\n\r\n<p>hi</p>"
+    );
+
+    // the length counts bytes, not chars
+    let body = "grüße, 世界 🌍";
+    assert_eq!(body.chars().count(), 11);
+    let mut buf = Vec::new();
+    let len = Response::new(http::StatusCode::OK).v1_1().text(body).write_to(&mut buf).unwrap();
+    let head = "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 20\r\n\r\n";
+    assert_eq!(buf, [head.as_bytes(), body.as_bytes()].concat());
+    assert_eq!(len, buf.len());
+}
+
+#[test]
+fn default_type_parameters() {
+    struct Prepared<'a> {
+        not_found: Response<'a>,
+        ok: BasicResponse<'a, version::V1_1>,
+    }
+
+    fn write(res: Response<'_>) -> Vec<u8> {
+        let mut buf = Vec::new();
+        res.v1_1().text("gone").write_to(&mut buf).unwrap();
+        buf
+    }
+
+    let prepared = Prepared {
+        not_found: Response::new(http::StatusCode::NOT_FOUND),
+        ok: Response::new(http::StatusCode::OK).v1_1(),
+    };
+    assert!(write(prepared.not_found).starts_with(b"HTTP/1.1 404 Not Found\r\n"));
+
+    // a stored response still borrows shorter-lived values afterwards
+    let value = String::from("no-store");
+    let mut buf = Vec::new();
+    let mut ok = prepared.ok.header("Cache-Control", value.as_bytes());
+    ok.write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nCache-Control: no-store\r\n\r\n");
+}
+
+#[test]
+fn into_io_error() {
+    let err = Response::new(http::StatusCode::OK).write_to(&mut Vec::new()).unwrap_err();
+    let err = std::io::Error::from(err);
+    assert_eq!((err.kind(), err.to_string()), (std::io::ErrorKind::InvalidData, "invalid HTTP version".into()));
+
+    // an I/O error survives the round trip through ResponseWriteError
+    let source = std::io::Error::new(std::io::ErrorKind::BrokenPipe, "peer went away");
+    let err = std::io::Error::from(ResponseWriteError::from(source));
+    assert_eq!((err.kind(), err.to_string()), (std::io::ErrorKind::BrokenPipe, "peer went away".into()));
+}
+
+#[test]
+fn from_io_error() {
+    use std::io::{Error, ErrorKind};
+
+    let written = |res: Response<_, _, &[u8]>| {
+        let mut buf = Vec::new();
+        res.v1_1().write_to(&mut buf).unwrap();
+        String::from_utf8(buf).unwrap()
+    };
+    let secret = "/srv/private/keys/id_rsa: permission denied (os error 13)";
+    let cases = [
+        (ErrorKind::NotFound, "404 Not Found", "Not Found"),
+        (ErrorKind::PermissionDenied, "403 Forbidden", "Forbidden"),
+        (ErrorKind::TimedOut, "504 Gateway Timeout", "Gateway Timeout"),
+        (ErrorKind::InvalidData, "500 Internal Server Error", "Internal Server Error"),
+    ];
+    for (kind, status, body) in cases {
+        let err = Error::new(kind, secret);
+        let out = written(Response::from_io_error(&err));
+        assert_eq!(
+            out,
+            format!(
+                "HTTP/1.1 {status}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+        );
+        assert!(!out.contains("srv") && !out.contains("os error"));
+    }
+
+    let err = Error::new(ErrorKind::AlreadyExists, secret);
+    let conflict = |e: &Error| (e.kind() == ErrorKind::AlreadyExists).then_some(http::StatusCode::CONFLICT);
+    assert!(written(Response::from_io_error_with(&err, conflict)).starts_with("HTTP/1.1 409 Conflict\r\n"));
+    let err = Error::from(ErrorKind::NotFound);
+    assert!(written(Response::from_io_error_with(&err, conflict)).starts_with("HTTP/1.1 404 Not Found\r\n"));
+    let custom = |_: &Error| http::StatusCode::from_u16(599).ok();
+    assert!(written(Response::from_io_error_with(&err, custom)).ends_with("\r\n\r\nError"));
+}
+
+#[test]
+fn response_body() {
+    let mut buf = Vec::new();
+    let len = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .header("Content-Type", b"application/octet-stream")
+        .body(b"\x00binary\r\n\r\n")
+        .write_to(&mut buf)
+        .unwrap();
+    assert_eq!(len, buf.len());
+    let mut headers = [httparse::EMPTY_HEADER; 4];
+    let mut parsed = httparse::Response::new(&mut headers);
+    let httparse::Status::Complete(head_len) = parsed.parse(&buf).unwrap() else {
+        panic!("incomplete head");
+    };
+    assert_eq!(parsed.headers[1].name, "Content-Length");
+    assert_eq!(parsed.headers[1].value, b"11");
+    assert_eq!(&buf[head_len..], b"\x00binary\r\n\r\n");
+
+    let written = |code, framing: Option<(&'static str, &'static [u8])>| {
+        let mut buf = Vec::new();
+        let res = Response::new(http::StatusCode::from_u16(code).unwrap()).v1_1();
+        let len = res.headers(framing.map(|(name, value)| crate::Header { name, value })).body(b"hello").write_to(&mut buf).unwrap();
+        assert_eq!(len, buf.len());
+        String::from_utf8(buf).unwrap()
+    };
+    assert_eq!(written(304, None), "HTTP/1.1 304 Not Modified\r\nContent-Length: 5\r\n\r\n");
+    assert_eq!(written(204, None), "HTTP/1.1 204 No Content\r\n\r\n");
+    assert_eq!(written(103, None), "HTTP/1.1 103 Early Hints\r\n\r\n");
+    assert_eq!(
+        written(200, Some(("content-length", b"5"))),
+        "HTTP/1.1 200 OK\r\ncontent-length: 5\r\n\r\nhello"
+    );
+    let mut framed = Response::ok().v1_1().header("Transfer-Encoding", b"identity").body(b"hello");
+    assert!(matches!(framed.to_vec(), Err(ResponseWriteError::ConflictingFraming { .. })));
+
+    let mut buf = Vec::new();
+    // SAFETY: the response is valid
+    unsafe { Response::new(http::StatusCode::NOT_MODIFIED).v1_1().body(b"hello").write_to_unchecked(&mut buf) }.unwrap();
+    assert_eq!(buf, b"HTTP/1.1 304 Not Modified\r\nContent-Length: 5\r\n\r\n");
+}
+
+#[test]
+fn chunked_body() {
+    let rows = ["id,name\n", "", "1,ada\n", "2,grace\n"].map(str::as_bytes);
+    let mut buf = Vec::new();
+    let len = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .header("Content-Type", b"text/csv")
+        .chunked(rows.iter().map(|row| row.to_vec()))
+        .write_to(&mut buf)
+        .unwrap();
+    assert_eq!(len, buf.len());
+
+    let head = b"HTTP/1.1 200 OK\r\nContent-Type: text/csv\r\nTransfer-Encoding: chunked\r\n\r\n";
+    assert!(buf.starts_with(head));
+    assert_eq!(&buf[head.len()..], b"8\r\nid,name\n\r\n6\r\n1,ada\n\r\n8\r\n2,grace\n\r\n0\r\n\r\n");
+    assert_eq!(crate::body::decode_chunked(&buf[head.len()..]), rows.concat());
+
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::OK).v1_1().chunked(core::iter::empty::<&[u8]>()).write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n");
+}
+
+#[test]
+fn conflicting_framing() {
+    let res = || Response::ok().v1_1().header("Transfer-Encoding", b"chunked").header("CONTENT-LENGTH", b"0");
+    let mut buf = Vec::new();
+    let err = res().write_to(&mut buf);
+    assert!(matches!(err, Err(ResponseWriteError::ConflictingFraming { buffer_offset: 0 })));
+    assert!(buf.is_empty());
+    let err = res().write_to_vectored(&mut Vec::new());
+    assert!(matches!(err, Err(ResponseWriteError::ConflictingFraming { buffer_offset: 0 })));
+
+    let mut buf = Vec::new();
+    let err = Response::ok().v1_1().header("Content-Length", b"5").header("Content-Length", b"7").write_to(&mut buf);
+    assert!(matches!(err, Err(ResponseWriteError::ConflictingFraming { buffer_offset: 0 })));
+    assert!(buf.is_empty());
+
+    let mut buf = Vec::new();
+    let err = Response::ok().v1_1().header("X-Ok", b"1").header_owned("X-Id", "1\r\n").write_to(&mut buf);
+    assert!(matches!(err, Err(ResponseWriteError::InvalidHeader { buffer_offset: 26, .. })));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn estimate_len() {
+    let res = Response::new(http::StatusCode::NOT_FOUND).v1_1().text("missing");
+    let mut buf = Vec::new();
+    assert_eq!(res.estimate_len().unwrap(), res.clone().write_to(&mut buf).unwrap());
+}
+
+#[test]
+fn to_vec() {
+    let bytes = Response::new(http::StatusCode::OK).v1_1().text("ok").to_vec().unwrap();
+    assert_eq!(bytes, b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 2\r\n\r\nok");
+}
+
+#[test]
+fn unique_headers() {
+    let res = || Response::ok().v1_1().header("Set-Cookie", b"a=1").header("Set-Cookie", b"b=2");
+    let mut buf = Vec::new();
+    res().unique_headers(crate::consts::UNIQUE_HEADERS).write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nSet-Cookie: a=1\r\nSet-Cookie: b=2\r\n\r\n");
+    let mut buf = Vec::new();
+    let err = res().unique_headers(&["set-cookie"]).write_to(&mut buf);
+    assert!(matches!(err, Err(ResponseWriteError::DuplicateHeader { buffer_offset: 34 })));
+    assert!(buf.is_empty());
+    let err = res().unique_headers(&["set-cookie"]).write_to_vectored(&mut Vec::new());
+    assert!(matches!(err, Err(ResponseWriteError::DuplicateHeader { .. })));
+}
+
+#[test]
+fn framing_headers_match_the_body() {
+    let conflicting = |err: Result<usize, ResponseWriteError>| matches!(err, Err(ResponseWriteError::ConflictingFraming { .. }));
+    let res = || Response::new(http::StatusCode::OK).v1_1();
+    let chunks = [b"abc".as_slice(), b"de"];
+    assert!(conflicting(res().header("Content-Length", b"100").chunked(chunks).write_to(&mut Vec::new())));
+    assert!(conflicting(res().header("Transfer-Encoding", b"chunked").body(b"hello").write_to(&mut Vec::new())));
+    assert!(conflicting(res().raw_header_line(b"Transfer-Encoding: chunked\r\n").body(b"hello").write_to(&mut Vec::new())));
+    assert!(conflicting(res().header("Content-Length", b"4").body(b"hello").write_to_vectored(&mut Vec::new())));
+    assert!(conflicting(res().header("Transfer-Encoding", b"gzip").chunked(chunks).write_to(&mut Vec::new())));
+
+    // headers that agree with the body are kept, without a second one
+    let buf = res().header("Content-Length", b"5").body(b"hello").to_vec().unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nhello");
+    let buf = res().header("transfer-encoding", b"gzip, Chunked").chunked(chunks).to_vec().unwrap();
+    assert!(buf.starts_with(b"HTTP/1.1 200 OK\r\ntransfer-encoding: gzip, Chunked\r\n\r\n3\r\n"));
+    // and so is one framing a message without a body
+    let buf = res().to_head_request().header("Content-Length", b"42").to_vec().unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\nContent-Length: 42\r\n\r\n");
+}
+
+#[test]
+fn set_cookie() {
+    let session = crate::SetCookie::new("session", "38afes7a8").path("/").secure().http_only();
+    let theme = crate::SetCookie::new("theme", "dark").max_age(core::time::Duration::from_secs(3600));
+    let buf = Response::new(http::StatusCode::OK).v1_1().set_cookie(&session).set_cookie(&theme).to_vec().unwrap();
+    assert_eq!(
+        buf,
+        b"HTTP/1.1 200 OK\r\n\
+Set-Cookie: session=38afes7a8; Path=/; Secure; HttpOnly\r\n\
+Set-Cookie: theme=dark; Max-Age=3600\r\n\r\n"
+    );
+
+    let bad = crate::SetCookie::new("id", "a b");
+    let err = Response::new(http::StatusCode::OK).v1_1().set_cookie(&bad).write_to(&mut Vec::new());
+    assert!(matches!(
+        err,
+        Err(ResponseWriteError::InvalidHeader { err: HeaderWriteError::InvalidValue(4), .. })
+    ));
+}
+
+#[test]
+fn chunked_trailers() {
+    let trailers = [Field::new("Checksum", b"sha-256=:abc=:"), Field::new("Server-Timing", b"total;dur=12")];
+    let mut buf = Vec::new();
+    let len = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .header("Trailer", b"Checksum, Server-Timing")
+        .chunked_with_trailers([b"hello".as_slice(), b" world"], trailers.clone())
+        .write_to(&mut buf)
+        .unwrap();
+    assert_eq!(len, buf.len());
+    let head = b"HTTP/1.1 200 OK\r\nTrailer: Checksum, Server-Timing\r\nTransfer-Encoding: chunked\r\n\r\n";
+    assert!(buf.starts_with(head));
+    assert_eq!(
+        &buf[head.len()..],
+        b"5\r\nhello\r\n6\r\n world\r\n0\r\nChecksum: sha-256=:abc=:\r\nServer-Timing: total;dur=12\r\n\r\n"
+    );
+    let (body, parsed) = crate::body::decode_chunked_trailers(&buf[head.len()..]);
+    assert_eq!(body, b"hello world");
+    assert_eq!(parsed, [("Checksum".to_owned(), b"sha-256=:abc=:".to_vec()), ("Server-Timing".to_owned(), b"total;dur=12".to_vec())]);
+
+    let mut buf = Vec::new();
+    let err = Response::new(http::StatusCode::OK)
+        .v1_1()
+        .chunked_with_trailers([b"x"], [Field::new("Bad Name", b"1")])
+        .write_to(&mut buf);
+    assert!(matches!(err, Err(ResponseWriteError::BodySerialize(_))));
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn custom_reason() {
+    let buf = Response::new(http::StatusCode::OK).reason("Everything Is Fine").v1_1().to_vec().unwrap();
+    assert_eq!(buf, b"HTTP/1.1 200 Everything Is Fine\r\n\r\n");
+    let code = http::StatusCode::from_u16(599).unwrap();
+    assert_eq!(Response::new(code).v1_1().to_vec().unwrap(), b"HTTP/1.1 599 \r\n\r\n");
+    let phrase = format!("Network {}", "Connect Timeout");
+    let buf = Response::new(code).reason(&phrase).v1_1().to_vec().unwrap();
+    assert_eq!(buf, b"HTTP/1.1 599 Network Connect Timeout\r\n\r\n");
+    let mut w = Vec::new();
+    Response::new(code).reason("Tab\tOK").v1_1().write_to_vectored(&mut w).unwrap();
+    assert_eq!(w, b"HTTP/1.1 599 Tab\tOK\r\n\r\n");
+
+    for reason in ["OK\r\nX-Injected: 1", "bad\n", "nul\0"] {
+        let mut w = Vec::new();
+        let err = Response::new(http::StatusCode::OK).reason(reason).v1_1().write_to(&mut w);
+        assert!(matches!(err, Err(ResponseWriteError::InvalidReason)), "{reason:?}");
+        assert!(w.is_empty());
+        let err = Response::new(http::StatusCode::OK).reason(reason).v1_1().write_to_vectored(&mut w);
+        assert!(matches!(err, Err(ResponseWriteError::InvalidReason)), "{reason:?}");
+    }
+}
+
+#[test]
+fn status_constructors() {
+    let cases = [
+        (Response::ok(), "200 OK"),
+        (Response::created(), "201 Created"),
+        (Response::no_content(), "204 No Content"),
+        (Response::bad_request(), "400 Bad Request"),
+        (Response::not_found(), "404 Not Found"),
+        (Response::internal_server_error(), "500 Internal Server Error"),
+    ];
+    for (res, line) in cases {
+        let buf = res.v1_1().to_vec().unwrap();
+        assert_eq!(buf, format!("HTTP/1.1 {line}\r\n\r\n").as_bytes());
+    }
+    let buf = Response::not_found().v1_1().text("missing").to_vec().unwrap();
+    assert!(buf.starts_with(b"HTTP/1.1 404 Not Found\r\n"));
+}
+
+#[test]
+fn from_code() {
+    let buf = Response::from_code(418).unwrap().v1_1().to_vec().unwrap();
+    assert_eq!(buf, b"HTTP/1.1 418 I'm a teapot\r\n\r\n");
+    let buf = Response::from_code(599).unwrap().v1_1().to_vec().unwrap();
+    assert_eq!(buf, b"HTTP/1.1 599 \r\n\r\n");
+    for code in [0, 99, 600, 999, 1000, u16::MAX] {
+        assert_eq!(Response::from_code(code).err(), Some(InvalidStatusCode(code)));
+    }
+}
+
+#[test]
+fn head_response() {
+    let mut buf = Vec::new();
+    let len = Response::ok().v1_1().to_head_request().text("hello").write_to(&mut buf).unwrap();
+    let head = b"HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 5\r\n\r\n";
+    assert_eq!(buf, head);
+    assert_eq!(len, head.len());
+
+    let mut w = Vec::new();
+    Response::ok().to_head_request().v1_1().chunked([b"a"]).write_to_vectored(&mut w).unwrap();
+    assert_eq!(w, b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n");
+
+    let mut buf = Vec::new();
+    Response::new(http::StatusCode::NOT_MODIFIED).v1_1().body(b"cached").write_to(&mut buf).unwrap();
+    assert!(buf.ends_with(b"\r\n\r\n"));
+    assert_eq!(buf, b"HTTP/1.1 304 Not Modified\r\nContent-Length: 6\r\n\r\n");
+}
@@ -0,0 +1,204 @@
+//! `Server-Timing` values.
+
+use crate::field::decimal;
+use core::time::Duration;
+
+/// One metric of a [`ServerTiming`] header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metric<'a> {
+    name: &'a str,
+    dur: Option<Duration>,
+    desc: Option<&'a str>,
+}
+
+impl<'a> Metric<'a> {
+    pub fn new(name: &'a str) -> Self {
+        Self {
+            name,
+            dur: None,
+            desc: None,
+        }
+    }
+
+    /// Written in milliseconds, rounded to the microsecond.
+    pub fn dur(mut self, dur: Duration) -> Self {
+        self.dur = Some(dur);
+        self
+    }
+
+    /// Written as a quoted string, escaping `"` and `\`.
+    pub fn desc(mut self, desc: &'a str) -> Self {
+        self.desc = Some(desc);
+        self
+    }
+
+    fn chunks(&self) -> impl Iterator<Item = &'a [u8]> {
+        let dur = self.dur.map(|dur| {
+            let micros = (dur.as_nanos() + 500) / 1000;
+            let frac = (micros % 1000) as u16;
+            let frac_digits = match frac {
+                0 => 0,
+                _ if frac.is_multiple_of(100) => 1,
+                _ if frac.is_multiple_of(10) => 2,
+                _ => 3,
+            };
+            let point: &'a [u8] = if frac_digits > 0 { b"." } else { b"" };
+            let frac = [frac / 100, frac / 10 % 10, frac % 10].into_iter().take(frac_digits).map(digit);
+            [b";dur=".as_slice()].into_iter().chain(decimal(micros / 1000)).chain([point]).chain(frac)
+        });
+        let desc = self.desc.map(|desc| {
+            let escaped = desc.as_bytes().split_inclusive(|&ch| matches!(ch, b'"' | b'\\')).flat_map(|part| {
+                match part.split_last() {
+                    Some((&(b'"' | b'\\'), text)) => [text, b"\\", &part[part.len() - 1..]],
+                    _ => [part, b"", b""],
+                }
+            });
+            [b";desc=\"".as_slice()].into_iter().chain(escaped).chain([b"\"".as_slice()])
+        });
+        [self.name.as_bytes()]
+            .into_iter()
+            .chain(dur.into_iter().flatten())
+            .chain(desc.into_iter().flatten())
+    }
+
+    /// The position of the first byte the metric cannot be written with.
+    fn check(&self) -> Result<(), usize> {
+        if let Some(pos) = crate::invalid_token_pos(self.name.as_bytes()) {
+            return Err(pos);
+        }
+        let Some(desc) = self.desc else {
+            return Ok(());
+        };
+        let start: usize = Metric { desc: None, ..*self }.chunks().map(<[u8]>::len).sum();
+        let mut escapes = 0;
+        for (pos, &ch) in desc.as_bytes().iter().enumerate() {
+            match ch {
+                b'"' | b'\\' => escapes += 1,
+                b'\t' | b' '..=b'~' | 0x80.. => {}
+                _ => return Err(start + 7 + pos + escapes),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn digit<'s>(d: u16) -> &'s [u8] {
+    crate::field::digit(d)
+}
+
+/// A `Server-Timing` header, built metric by metric and written by
+/// [`Response::server_timing`](crate::Response::server_timing).
+///
+/// [`ServerTiming::dur`] and [`ServerTiming::desc`] apply to the metric
+/// added last; a header without metrics fails to write.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServerTiming<'a> {
+    metrics: Vec<Metric<'a>>,
+}
+
+impl<'a> ServerTiming<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn metric(mut self, name: &'a str) -> Self {
+        self.metrics.push(Metric::new(name));
+        self
+    }
+
+    pub fn dur(self, dur: Duration) -> Self {
+        self.last(|m| m.dur(dur))
+    }
+
+    pub fn desc(self, desc: &'a str) -> Self {
+        self.last(|m| m.desc(desc))
+    }
+
+    fn last(mut self, f: impl FnOnce(Metric<'a>) -> Metric<'a>) -> Self {
+        if let Some(m) = self.metrics.last_mut() {
+            *m = f(*m);
+        }
+        self
+    }
+
+    /// The position in the value of the first byte that cannot be written.
+    pub(crate) fn check(&self) -> Result<(), usize> {
+        if self.metrics.is_empty() {
+            return Err(0);
+        }
+        let mut offset = 0;
+        for (i, metric) in self.metrics.iter().enumerate() {
+            offset += if i > 0 { 2 } else { 0 };
+            metric.check().map_err(|pos| offset + pos)?;
+            offset += metric.chunks().map(<[u8]>::len).sum::<usize>();
+        }
+        Ok(())
+    }
+
+    /// The header value as the chunks it is written in.
+    pub(crate) fn chunks(&self) -> impl Iterator<Item = &[u8]> {
+        self.metrics.iter().enumerate().flat_map(|(i, metric)| {
+            let sep: &[u8] = if i > 0 { b", " } else { b"" };
+            [sep].into_iter().chain(metric.chunks())
+        })
+    }
+}
+
+impl<'a> FromIterator<Metric<'a>> for ServerTiming<'a> {
+    fn from_iter<I: IntoIterator<Item = Metric<'a>>>(iter: I) -> Self {
+        Self {
+            metrics: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+fn value(timing: &ServerTiming<'_>) -> String {
+    String::from_utf8(timing.chunks().collect::<Vec<_>>().concat()).unwrap()
+}
+
+#[test]
+fn durations() {
+    let dur = |d| value(&ServerTiming::new().metric("m").dur(d));
+    assert_eq!(dur(Duration::from_micros(53_200)), "m;dur=53.2");
+    assert_eq!(dur(Duration::from_millis(47)), "m;dur=47");
+    assert_eq!(dur(Duration::ZERO), "m;dur=0");
+    assert_eq!(dur(Duration::from_micros(1)), "m;dur=0.001");
+    assert_eq!(dur(Duration::from_micros(250)), "m;dur=0.25");
+    assert_eq!(dur(Duration::from_nanos(1_499)), "m;dur=0.001");
+    assert_eq!(dur(Duration::from_nanos(1_500)), "m;dur=0.002");
+    assert_eq!(dur(Duration::from_nanos(999_999_600)), "m;dur=1000");
+    assert_eq!(dur(Duration::from_secs(12_345)), "m;dur=12345000");
+}
+
+#[test]
+fn desc_quoting() {
+    let timing = ServerTiming::new().metric("cdn").desc(r#"say "hi" \o/"#);
+    assert_eq!(value(&timing), r#"cdn;desc="say \"hi\" \\o/""#);
+    assert_eq!(timing.check(), Ok(()));
+    assert_eq!(value(&ServerTiming::new().metric("x").desc("")), r#"x;desc="""#);
+
+    let bad = ServerTiming::new().metric("ok").metric("db").dur(Duration::from_millis(5)).desc("a\"b\nc");
+    // `ok, db;dur=5;desc="a\"b` precedes the newline
+    assert_eq!(bad.check(), Err(23));
+}
+
+#[test]
+fn metrics_in_order() {
+    let timing = ServerTiming::new()
+        .metric("db")
+        .dur(Duration::from_micros(53_200))
+        .desc("cache miss")
+        .metric("app")
+        .dur(Duration::from_micros(47_200))
+        .metric("miss");
+    assert_eq!(value(&timing), r#"db;dur=53.2;desc="cache miss", app;dur=47.2, miss"#);
+    assert_eq!(timing.check(), Ok(()));
+
+    let collected: ServerTiming<'_> = [Metric::new("a"), Metric::new("b").dur(Duration::from_millis(1))].into_iter().collect();
+    assert_eq!(value(&collected), "a, b;dur=1");
+
+    assert_eq!(ServerTiming::new().check(), Err(0));
+    assert_eq!(ServerTiming::new().dur(Duration::ZERO).check(), Err(0));
+    assert_eq!(ServerTiming::new().metric("a").metric("b c").check(), Err(4));
+}
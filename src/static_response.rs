@@ -0,0 +1,244 @@
+//! Support for [`static_response!`](crate::static_response).
+
+/// Serializes a whole response at compile time.
+///
+/// The head is written as HTTP/1.1 with the canonical reason phrase, and a
+/// `Content-Length` matching the body is added unless the status forbids a
+/// body (1xx, 204, 304). An invalid header is a compile error.
+///
+/// ```
+/// use http_write::static_response;
+///
+/// static OK: &[u8] = static_response!(200, "content-type" => "text/plain"; body: "ok");
+/// static MOVED: &[u8] = static_response!(301, "location" => "/new");
+///
+/// assert_eq!(
+///     OK,
+///     b"HTTP/1.1 200 OK\r\ncontent-type: text/plain\r\nContent-Length: 2\r\n\r\nok"
+/// );
+/// ```
+#[macro_export]
+macro_rules! static_response {
+    ($code:expr $(, $name:expr => $value:expr)* $(,)? ; body: $body:expr $(,)?) => {
+        $crate::__static_response!($code, [$(($name, $value)),*], $body)
+    };
+    ($code:expr $(, $name:expr => $value:expr)* $(,)?) => {
+        $crate::__static_response!($code, [$(($name, $value)),*], "")
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __static_response {
+    ($code:expr, [$($header:expr),*], $body:expr) => {{
+        const HEADERS: &[(&str, &str)] = &[$($header),*];
+        const LEN: usize = $crate::__private::static_len($code, HEADERS, $body);
+        const BYTES: [u8; LEN] = $crate::__private::static_bytes($code, HEADERS, $body);
+        &BYTES
+    }};
+}
+
+const CONTENT_LENGTH: &[u8] = b"Content-Length: ";
+
+pub const fn static_len(code: u16, headers: &[(&str, &str)], body: &str) -> usize {
+    let reason = match canonical_reason(code) {
+        Some(reason) => reason,
+        None => panic!("static_response!: unknown status code"),
+    };
+
+    let mut len = 9 + 3 + 1 + reason.len() + 2;
+
+    let mut i = 0;
+    while i < headers.len() {
+        let (name, value) = headers[i];
+        if crate::check_header(name.as_bytes(), value.as_bytes()).is_err() {
+            panic!("static_response!: invalid header");
+        }
+        if name.eq_ignore_ascii_case("content-length") {
+            panic!("static_response!: Content-Length is computed from the body");
+        }
+        len += name.len() + 2 + value.len() + 2;
+        i += 1;
+    }
+
+    if has_body(code) {
+        len += CONTENT_LENGTH.len() + digits(body.len()) + 2;
+    } else if !body.is_empty() {
+        panic!("static_response!: this status code cannot have a body");
+    }
+
+    len + 2 + body.len()
+}
+
+pub const fn static_bytes<const N: usize>(
+    code: u16,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> [u8; N] {
+    let mut out = [0; N];
+    let mut pos = put(&mut out, 0, b"HTTP/1.1 ");
+    out[pos] = b'0' + (code / 100) as u8;
+    out[pos + 1] = b'0' + (code / 10 % 10) as u8;
+    out[pos + 2] = b'0' + (code % 10) as u8;
+    pos = put(&mut out, pos + 3, b" ");
+    if let Some(reason) = canonical_reason(code) {
+        pos = put(&mut out, pos, reason.as_bytes());
+    }
+    pos = put(&mut out, pos, b"\r\n");
+
+    let mut i = 0;
+    while i < headers.len() {
+        let (name, value) = headers[i];
+        pos = put(&mut out, pos, name.as_bytes());
+        pos = put(&mut out, pos, b": ");
+        pos = put(&mut out, pos, value.as_bytes());
+        pos = put(&mut out, pos, b"\r\n");
+        i += 1;
+    }
+
+    if has_body(code) {
+        pos = put(&mut out, pos, CONTENT_LENGTH);
+        let mut n = body.len();
+        let mut d = digits(n);
+        while d > 0 {
+            out[pos + d - 1] = b'0' + (n % 10) as u8;
+            n /= 10;
+            d -= 1;
+        }
+        pos += digits(body.len());
+        pos = put(&mut out, pos, b"\r\n");
+    }
+
+    pos = put(&mut out, pos, b"\r\n");
+    put(&mut out, pos, body.as_bytes());
+    out
+}
+
+const fn has_body(code: u16) -> bool {
+    !(code < 200 || code == 204 || code == 304)
+}
+
+const fn digits(mut n: usize) -> usize {
+    let mut d = 1;
+    while n >= 10 {
+        n /= 10;
+        d += 1;
+    }
+    d
+}
+
+const fn put(out: &mut [u8], pos: usize, bytes: &[u8]) -> usize {
+    let mut i = 0;
+    while i < bytes.len() {
+        out[pos + i] = bytes[i];
+        i += 1;
+    }
+    pos + bytes.len()
+}
+
+/// The canonical reason phrase for `code`, matching `http::StatusCode`.
+pub(crate) const fn canonical_reason(code: u16) -> Option<&'static str> {
+    Some(match code {
+        100 => "Continue",
+        101 => "Switching Protocols",
+        102 => "Processing",
+        103 => "Early Hints",
+        200 => "OK",
+        201 => "Created",
+        202 => "Accepted",
+        203 => "Non Authoritative Information",
+        204 => "No Content",
+        205 => "Reset Content",
+        206 => "Partial Content",
+        207 => "Multi-Status",
+        208 => "Already Reported",
+        226 => "IM Used",
+        300 => "Multiple Choices",
+        301 => "Moved Permanently",
+        302 => "Found",
+        303 => "See Other",
+        304 => "Not Modified",
+        305 => "Use Proxy",
+        307 => "Temporary Redirect",
+        308 => "Permanent Redirect",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        402 => "Payment Required",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        406 => "Not Acceptable",
+        407 => "Proxy Authentication Required",
+        408 => "Request Timeout",
+        409 => "Conflict",
+        410 => "Gone",
+        411 => "Length Required",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        414 => "URI Too Long",
+        415 => "Unsupported Media Type",
+        416 => "Range Not Satisfiable",
+        417 => "Expectation Failed",
+        418 => "I'm a teapot",
+        421 => "Misdirected Request",
+        422 => "Unprocessable Entity",
+        423 => "Locked",
+        424 => "Failed Dependency",
+        425 => "Too Early",
+        426 => "Upgrade Required",
+        428 => "Precondition Required",
+        429 => "Too Many Requests",
+        431 => "Request Header Fields Too Large",
+        451 => "Unavailable For Legal Reasons",
+        500 => "Internal Server Error",
+        501 => "Not Implemented",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        504 => "Gateway Timeout",
+        505 => "HTTP Version Not Supported",
+        506 => "Variant Also Negotiates",
+        507 => "Insufficient Storage",
+        508 => "Loop Detected",
+        510 => "Not Extended",
+        511 => "Network Authentication Required",
+        _ => return None,
+    })
+}
+
+#[test]
+fn static_response_parses() {
+    static OK: &[u8] = crate::static_response!(200, "content-type" => "text/plain"; body: "ok");
+
+    let mut headers = [httparse::EMPTY_HEADER; 4];
+    let mut res = httparse::Response::new(&mut headers);
+    let head = match res.parse(OK).unwrap() {
+        httparse::Status::Complete(n) => n,
+        httparse::Status::Partial => panic!(),
+    };
+
+    assert_eq!(res.code, Some(200));
+    assert_eq!(res.reason, Some("OK"));
+    assert_eq!(res.headers[1].name, "Content-Length");
+    assert_eq!(res.headers[1].value, b"2");
+    assert_eq!(&OK[head..], b"ok");
+}
+
+#[test]
+fn static_response_without_body() {
+    const NO_CONTENT: &[u8] = crate::static_response!(204, "x-a" => "1");
+    const MOVED: &[u8] = crate::static_response!(301, "location" => "/new",);
+
+    assert_eq!(NO_CONTENT, b"HTTP/1.1 204 No Content\r\nx-a: 1\r\n\r\n");
+    assert_eq!(
+        MOVED,
+        b"HTTP/1.1 301 Moved Permanently\r\nlocation: /new\r\nContent-Length: 0\r\n\r\n"
+    );
+}
+
+#[test]
+fn reasons_match_http() {
+    for code in 100..1000 {
+        let status = http::StatusCode::from_u16(code).unwrap();
+        assert_eq!(canonical_reason(code), status.canonical_reason(), "{code}");
+    }
+}
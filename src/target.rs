@@ -0,0 +1,121 @@
+//! The forms a request target takes besides a bare path (RFC 9112, section 3.2).
+
+use crate::path::validate_encoded;
+use std::io::{self, Write};
+
+/// What comes before the path in the request line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Target<'a> {
+    /// The path and query alone.
+    Origin,
+    /// The scheme and authority, then the path and query, for a request to a
+    /// forward proxy.
+    Absolute { scheme: &'a str, authority: &'a str },
+    /// The host and port alone, for `CONNECT`.
+    Authority { host: &'a str, port: u16 },
+    /// `*`, for an `OPTIONS` request about the server as a whole.
+    Asterisk,
+}
+
+impl Target<'_> {
+    /// Checks the scheme, authority or host, failing with the offset of the
+    /// first bad byte.
+    pub(crate) fn validate(&self) -> Result<(), usize> {
+        match *self {
+            Self::Origin | Self::Asterisk => Ok(()),
+            Self::Absolute { scheme, authority } => {
+                validate_scheme(scheme)?;
+                validate_authority(authority).map_err(|pos| scheme.len() + 3 + pos)
+            }
+            Self::Authority { host, .. } => validate_host(host),
+        }
+    }
+
+    /// Whether the path and query follow the prefix.
+    pub(crate) fn has_path(&self) -> bool {
+        !matches!(self, Self::Authority { .. } | Self::Asterisk)
+    }
+
+    pub(crate) fn write_prefix<W: Write + ?Sized>(&self, w: &mut W) -> io::Result<usize> {
+        match *self {
+            Self::Origin => Ok(0),
+            Self::Asterisk => w.write_all(b"*").map(|()| 1),
+            Self::Absolute { scheme, authority } => {
+                write!(w, "{scheme}://{authority}")?;
+                Ok(scheme.len() + 3 + authority.len())
+            }
+            Self::Authority { host, port } => {
+                let mut w = crate::Counter::new(w);
+                write!(w, "{host}:{port}")?;
+                Ok(w.written)
+            }
+        }
+    }
+}
+
+/// `ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )`
+fn validate_scheme(scheme: &str) -> Result<(), usize> {
+    match scheme.bytes().position(|ch| !(ch.is_ascii_alphanumeric() || matches!(ch, b'+' | b'-' | b'.'))) {
+        Some(pos) => Err(pos),
+        None if !scheme.starts_with(|ch: char| ch.is_ascii_alphabetic()) => Err(0),
+        None => Ok(()),
+    }
+}
+
+/// A non-empty host, with an optional userinfo and port, and the brackets
+/// of an IP literal.
+fn validate_authority(authority: &str) -> Result<(), usize> {
+    if authority.is_empty() {
+        return Err(0);
+    }
+    validate_encoded(authority.as_bytes(), b"[]")
+}
+
+/// A registered name, or an IP literal in brackets; userinfo and the port
+/// have no place in it.
+fn validate_host(host: &str) -> Result<(), usize> {
+    if let Some(literal) = host.strip_prefix('[') {
+        let literal = literal.strip_suffix(']').ok_or(host.len())?;
+        return match literal.bytes().position(|ch| !(ch.is_ascii_hexdigit() || matches!(ch, b':' | b'.'))) {
+            Some(pos) => Err(1 + pos),
+            None if literal.is_empty() => Err(1),
+            None => Ok(()),
+        };
+    }
+    if host.is_empty() {
+        return Err(0);
+    }
+    match host.bytes().position(|ch| matches!(ch, b':' | b'@')) {
+        Some(pos) => Err(pos),
+        None => validate_encoded(host.as_bytes(), b""),
+    }
+}
+
+#[test]
+fn absolute_validation() {
+    let absolute = |scheme, authority| Target::Absolute { scheme, authority }.validate();
+    assert_eq!(absolute("http", "example.com"), Ok(()));
+    assert_eq!(absolute("coap+tcp", "[::1]:5683"), Ok(()));
+    assert_eq!(absolute("", "example.com"), Err(0));
+    assert_eq!(absolute("1http", "example.com"), Err(0));
+    assert_eq!(absolute("ht tp", "example.com"), Err(2));
+    assert_eq!(absolute("http", ""), Err(7));
+    assert_eq!(absolute("http", "exa mple.com"), Err(10));
+    assert_eq!(absolute("http", "example.com/x"), Err(18));
+    assert_eq!(absolute("http", "host\r\n"), Err(11));
+}
+
+#[test]
+fn authority_validation() {
+    let host = |host| Target::Authority { host, port: 443 }.validate();
+    assert_eq!(host("example.com"), Ok(()));
+    assert_eq!(host("xn--bcher-kva.example"), Ok(()));
+    assert_eq!(host("[2001:db8::1]"), Ok(()));
+    assert_eq!(host(""), Err(0));
+    assert_eq!(host("[]"), Err(1));
+    assert_eq!(host("[::1"), Err(4));
+    assert_eq!(host("[::g]"), Err(3));
+    assert_eq!(host("example.com:443"), Err(11));
+    assert_eq!(host("user@example.com"), Err(4));
+    assert_eq!(host("exa mple.com"), Err(3));
+}
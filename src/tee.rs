@@ -0,0 +1,98 @@
+use std::io::{self, Write};
+
+/// Forwards writes to a primary writer and mirrors what it accepted into a
+/// capture sink, e.g. to log the exact bytes sent on a socket.
+///
+/// Short writes are respected: the capture only ever receives the prefix
+/// the primary reported as written, so it never holds bytes that did not
+/// go out.
+#[derive(Debug)]
+pub struct TeeWriter<W, C> {
+    primary: W,
+    capture: C,
+}
+
+impl<W: Write, C: Write> TeeWriter<W, C> {
+    pub fn new(primary: W, capture: C) -> Self {
+        Self { primary, capture }
+    }
+
+    pub fn into_parts(self) -> (W, C) {
+        (self.primary, self.capture)
+    }
+}
+
+impl<W: Write, C: Write> Write for TeeWriter<W, C> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.primary.write(buf)?;
+        self.capture.write_all(&buf[..n])?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.primary.flush()?;
+        self.capture.flush()
+    }
+}
+
+#[cfg(test)]
+struct Short {
+    received: Vec<u8>,
+    limit: Option<usize>,
+}
+
+#[cfg(test)]
+impl Write for Short {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let max = match self.limit {
+            Some(0) => return Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed")),
+            Some(limit) => limit,
+            None => usize::MAX,
+        };
+        // accept at most three bytes per call
+        let n = buf.len().min(3).min(max);
+        self.limit = self.limit.map(|l| l - n);
+        self.received.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn tee_short_writes() {
+    let primary = Short {
+        received: Vec::new(),
+        limit: None,
+    };
+    let mut tee = TeeWriter::new(primary, Vec::new());
+    let len = crate::Request::get()
+        .v1_1()
+        .header("a", b"1")
+        .write_to(&mut tee)
+        .unwrap();
+
+    let (primary, capture) = tee.into_parts();
+    assert_eq!(len, capture.len());
+    assert_eq!(primary.received, capture);
+}
+
+#[test]
+fn tee_failing_primary() {
+    let primary = Short {
+        received: Vec::new(),
+        limit: Some(10),
+    };
+    let mut tee = TeeWriter::new(primary, Vec::new());
+    crate::Request::get()
+        .v1_1()
+        .header("a", b"1")
+        .write_to(&mut tee)
+        .unwrap_err();
+
+    let (primary, capture) = tee.into_parts();
+    assert_eq!(primary.received.len(), 10);
+    assert_eq!(primary.received, capture);
+}
@@ -0,0 +1,202 @@
+use crate::{HeaderWriteError, InvalidPath, InvalidQuery, OwnedRequest};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A canned request, e.g. a health check kept in a config file.
+///
+/// ```json
+/// {
+///     "method": "GET",
+///     "path": "/health",
+///     "query": [["verbose", "1"]],
+///     "headers": { "host": "example.com" },
+///     "body": { "text": "ping" }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RequestTemplate {
+    pub method: String,
+    pub path: String,
+    /// Already-encoded `key=value` pairs.
+    #[serde(default)]
+    pub query: Vec<(String, String)>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Defaults to `1.1`.
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub body: Option<TemplateBody>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "lowercase")]
+pub enum TemplateBody {
+    Text(String),
+    Base64(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TemplateError {
+    /// The method is not a token; holds the position of the offending byte.
+    InvalidMethod(usize),
+    InvalidPath(InvalidPath),
+    InvalidQuery {
+        index: usize,
+        err: InvalidQuery,
+    },
+    InvalidVersion,
+    InvalidHeader {
+        name: String,
+        err: HeaderWriteError,
+    },
+    /// The base64 body is malformed at the given position.
+    InvalidBody(usize),
+    /// The template has a body and also sets `Content-Length` or
+    /// `Transfer-Encoding` itself.
+    ConflictingFraming,
+}
+
+impl core::fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidMethod(pos) => write!(f, "invalid method at byte {pos}"),
+            Self::InvalidPath(err) => err.fmt(f),
+            Self::InvalidQuery { index, err } => write!(f, "query {index}: {err}"),
+            Self::InvalidVersion => f.write_str("invalid HTTP version"),
+            Self::InvalidHeader { name, err } => write!(f, "header `{name}`: {err}"),
+            Self::InvalidBody(pos) => write!(f, "malformed base64 body at byte {pos}"),
+            Self::ConflictingFraming => f.write_str("template sets a framing header alongside its body"),
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidPath(err) => Some(err),
+            Self::InvalidQuery { err, .. } => Some(err),
+            Self::InvalidHeader { err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl RequestTemplate {
+    /// Validates every part of the template and builds a request from it.
+    ///
+    /// A `Content-Length` header is added when the template has a body, so
+    /// such a template cannot set `Content-Length` or `Transfer-Encoding`.
+    pub fn to_request(&self) -> Result<OwnedRequest, TemplateError> {
+        if let Some(pos) = crate::invalid_token_pos(self.method.as_bytes()) {
+            return Err(TemplateError::InvalidMethod(pos));
+        }
+
+        crate::path::validate(&self.path).map_err(TemplateError::InvalidPath)?;
+
+        let mut queries = Vec::with_capacity(self.query.len());
+        for (index, (key, value)) in self.query.iter().enumerate() {
+            let q = format!("{key}={value}");
+            crate::query::validate(&q).map_err(|err| TemplateError::InvalidQuery { index, err })?;
+            queries.push(q);
+        }
+
+        let version = self.version.as_deref().unwrap_or("1.1");
+        if !crate::version::is_valid(version) {
+            return Err(TemplateError::InvalidVersion);
+        }
+
+        let mut headers = Vec::with_capacity(self.headers.len() + 1);
+        for (name, value) in &self.headers {
+            crate::check_header(name.as_bytes(), value.as_bytes()).map_err(|err| TemplateError::InvalidHeader {
+                name: name.clone(),
+                err,
+            })?;
+            headers.push((name.clone(), value.clone().into_bytes()));
+        }
+
+        let body = match &self.body {
+            Some(TemplateBody::Text(text)) => Some(text.clone().into_bytes()),
+            Some(TemplateBody::Base64(b64)) => {
+                Some(crate::base64::decode(b64.as_bytes()).map_err(TemplateError::InvalidBody)?)
+            }
+            None => None,
+        };
+
+        if let Some(body) = &body {
+            let framing = [crate::consts::names::CONTENT_LENGTH, crate::consts::names::TRANSFER_ENCODING];
+            if self.headers.keys().any(|name| framing.iter().any(|f| f.eq_ignore_ascii_case(name))) {
+                return Err(TemplateError::ConflictingFraming);
+            }
+            headers.push(("content-length".into(), body.len().to_string().into_bytes()));
+        }
+
+        Ok(OwnedRequest {
+            method: self.method.clone(),
+            path: self.path.clone(),
+            queries,
+            headers,
+            version: version.into(),
+            body: body.unwrap_or_default(),
+        })
+    }
+}
+
+#[test]
+fn template_from_json() {
+    let template: RequestTemplate = serde_json::from_str(
+        r#"{
+            "method": "POST",
+            "path": "/health",
+            "query": [["verbose", "1"]],
+            "headers": { "host": "example.com" },
+            "body": { "base64": "cGluZw==" }
+        }"#,
+    )
+    .unwrap();
+
+    let req = template.to_request().unwrap();
+
+    for _ in 0..2 {
+        let mut buf = Vec::new();
+        let len = req.write_to(&mut buf).unwrap();
+        assert_eq!(len, buf.len());
+        assert_eq!(
+            buf,
+            b"POST /health?verbose=1 HTTP/1.1\r\nhost: example.com\r\ncontent-length: 4\r\n\r\nping"
+        );
+    }
+}
+
+#[test]
+fn template_errors() {
+    let parse = |json: &str| serde_json::from_str::<RequestTemplate>(json);
+
+    let bad_method = parse(r#"{ "method": "GE T", "path": "/" }"#).unwrap();
+    assert_eq!(bad_method.to_request(), Err(TemplateError::InvalidMethod(2)));
+
+    let bad_header = parse(r#"{ "method": "GET", "path": "/", "headers": { "x y": "1" } }"#).unwrap();
+    assert_eq!(
+        bad_header.to_request(),
+        Err(TemplateError::InvalidHeader {
+            name: "x y".into(),
+            err: HeaderWriteError::InvalidName(1),
+        })
+    );
+
+    let framed = parse(
+        r#"{ "method": "POST", "path": "/", "headers": { "Content-Length": "9" }, "body": { "text": "ping" } }"#,
+    )
+    .unwrap();
+    assert_eq!(framed.to_request(), Err(TemplateError::ConflictingFraming));
+    let chunked = parse(
+        r#"{ "method": "POST", "path": "/", "headers": { "transfer-encoding": "chunked" }, "body": { "text": "ping" } }"#,
+    )
+    .unwrap();
+    assert_eq!(chunked.to_request(), Err(TemplateError::ConflictingFraming));
+    let no_body = parse(r#"{ "method": "POST", "path": "/", "headers": { "Content-Length": "0" } }"#).unwrap();
+    assert!(no_body.to_request().is_ok());
+
+    assert!(parse(r#"{ "method": "GET", "path": "/", "extra": 1 }"#).is_err());
+}
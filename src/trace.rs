@@ -0,0 +1,163 @@
+//! W3C Trace Context headers, `traceparent` and `tracestate`.
+
+use crate::Field;
+use crate::consts::names;
+use crate::field::Inline;
+
+/// The most entries a `tracestate` may hold.
+const MAX_ENTRIES: usize = 32;
+/// The longest `tracestate` value.
+const MAX_LEN: usize = 512;
+
+/// `00-{trace_id}-{span_id}-{flags}` in lowercase hex; an all-zero id fails
+/// the write at its position.
+pub(crate) fn traceparent<'a>(trace_id: [u8; 16], span_id: [u8; 8], sampled: bool) -> Field<'a> {
+    if trace_id == [0; 16] {
+        return Field::invalid(names::TRACEPARENT, 3);
+    }
+    if span_id == [0; 8] {
+        return Field::invalid(names::TRACEPARENT, 36);
+    }
+
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut buf = *b"00-00000000000000000000000000000000-0000000000000000-00";
+    for (start, id) in [(3, trace_id.as_slice()), (36, span_id.as_slice())] {
+        for (byte, out) in id.iter().zip(buf[start..].chunks_exact_mut(2)) {
+            out[0] = HEX[(byte >> 4) as usize];
+            out[1] = HEX[(byte & 0xf) as usize];
+        }
+    }
+    buf[54] = b'0' + u8::from(sampled);
+    Field::inline(names::TRACEPARENT, Inline::new(&buf))
+}
+
+/// The `tracestate` of `entries`, or `None` when there are none.
+pub(crate) fn tracestate<'a>(entries: &'a [(&'a str, &'a str)]) -> Option<Field<'a>> {
+    if entries.is_empty() {
+        return None;
+    }
+    Some(match check_tracestate(entries) {
+        Ok(()) => Field::pairs(names::TRACESTATE, entries, b","),
+        Err(pos) => Field::invalid(names::TRACESTATE, pos),
+    })
+}
+
+/// The position in the written value of the first entry, key or value that
+/// breaks the grammar or limits of the spec.
+fn check_tracestate(entries: &[(&str, &str)]) -> Result<(), usize> {
+    let mut offset = 0;
+    for (i, (key, value)) in entries.iter().enumerate() {
+        offset += usize::from(i > 0);
+        if i == MAX_ENTRIES {
+            return Err(offset);
+        }
+        check_key(key.as_bytes()).map_err(|pos| offset + pos)?;
+        if entries[..i].iter().any(|(k, _)| k == key) {
+            return Err(offset);
+        }
+        offset += key.len() + 1;
+        check_value(value.as_bytes()).map_err(|pos| offset + pos)?;
+        offset += value.len();
+    }
+    if offset > MAX_LEN {
+        return Err(MAX_LEN);
+    }
+    Ok(())
+}
+
+/// A lowercase `key`, or `tenant@system` for a multi-tenant vendor.
+fn check_key(key: &[u8]) -> Result<(), usize> {
+    let is_key_char = |&ch: &u8| matches!(ch, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'*' | b'/');
+    let check = |part: &[u8], first_digit: bool, max: usize| {
+        match part.first() {
+            Some(b'a'..=b'z') => {}
+            Some(b'0'..=b'9') if first_digit => {}
+            _ => return Err(0),
+        }
+        if let Some(pos) = part.iter().position(|ch| !is_key_char(ch)) {
+            return Err(pos);
+        }
+        if part.len() > max {
+            return Err(max);
+        }
+        Ok(())
+    };
+
+    match key.iter().position(|&ch| ch == b'@') {
+        None => check(key, false, 256),
+        Some(at) => {
+            check(&key[..at], true, 241)?;
+            check(&key[at + 1..], false, 14).map_err(|pos| at + 1 + pos)
+        }
+    }
+}
+
+/// Printable ASCII without `,` or `=`, and not ending in a space.
+fn check_value(value: &[u8]) -> Result<(), usize> {
+    if let Some(pos) = value.iter().position(|&ch| !matches!(ch, b' '..=b'~') || ch == b',' || ch == b'=') {
+        return Err(pos);
+    }
+    match value {
+        [] => Err(0),
+        [.., b' '] => Err(value.len() - 1),
+        _ if value.len() > 256 => Err(256),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+fn written(field: Field<'_>) -> Result<String, crate::HeaderWriteError> {
+    field.validate()?;
+    let mut buf = Vec::new();
+    unsafe { field.write_unchecked(&mut buf) }.unwrap();
+    Ok(String::from_utf8(buf).unwrap())
+}
+
+#[test]
+fn traceparent_value() {
+    let trace_id = 0x4bf92f3577b34da6a3ce929d0e0e4736u128.to_be_bytes();
+    let span_id = 0x00f067aa0ba902b7u64.to_be_bytes();
+    assert_eq!(
+        written(traceparent(trace_id, span_id, true)).unwrap(),
+        "traceparent: 00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01\r\n"
+    );
+    assert_eq!(
+        written(traceparent(trace_id, span_id, false)).unwrap(),
+        "traceparent: 00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00\r\n"
+    );
+
+    use crate::HeaderWriteError::InvalidValue;
+    assert_eq!(written(traceparent([0; 16], span_id, true)), Err(InvalidValue(3)));
+    assert_eq!(written(traceparent(trace_id, [0; 8], true)), Err(InvalidValue(36)));
+}
+
+#[test]
+fn tracestate_limits() {
+    let entries = [("congo", "t61rcWkgMzE"), ("rojo", "00f067aa0ba902b7"), ("acme@tenant1", "x y")];
+    assert_eq!(
+        written(tracestate(&entries).unwrap()).unwrap(),
+        "tracestate: congo=t61rcWkgMzE,rojo=00f067aa0ba902b7,acme@tenant1=x y\r\n"
+    );
+    assert!(tracestate(&[]).is_none());
+
+    assert_eq!(check_tracestate(&[("Congo", "1")]), Err(0));
+    assert_eq!(check_tracestate(&[("a", "1"), ("1abc", "1")]), Err(4));
+    assert_eq!(check_tracestate(&[("1tenant@sys", "1")]), Ok(()));
+    assert_eq!(check_tracestate(&[("tenant@1sys", "1")]), Err(7));
+    assert_eq!(check_tracestate(&[("tenant@", "1")]), Err(7));
+    assert_eq!(check_tracestate(&[("tenant@abcdefghijklmno", "1")]), Err(21));
+    assert_eq!(check_tracestate(&[("a", "1,2")]), Err(3));
+    assert_eq!(check_tracestate(&[("a", "trailing ")]), Err(10));
+    assert_eq!(check_tracestate(&[("a", "")]), Err(2));
+    assert_eq!(check_tracestate(&[("a", "1"), ("a", "2")]), Err(4));
+
+    let keys: Vec<String> = (0..33).map(|i| format!("k{i}")).collect();
+    let many: Vec<(&str, &str)> = keys.iter().map(|k| (k.as_str(), "v")).collect();
+    assert_eq!(check_tracestate(&many[..32]), Ok(()));
+    let at = many[..32].iter().map(|(k, v)| k.len() + v.len() + 2).sum::<usize>();
+    assert_eq!(check_tracestate(&many), Err(at));
+
+    let long = "v".repeat(253);
+    assert_eq!(check_tracestate(&[("a", &long), ("b", &long)]), Ok(()));
+    assert_eq!(check_tracestate(&[("a", &long), ("b", &long), ("c", "v")]), Err(MAX_LEN));
+}
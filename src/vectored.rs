@@ -0,0 +1,284 @@
+//! Writing the head in `write_vectored` calls instead of one write per piece.
+
+use crate::path::Normalize;
+use crate::query::Query;
+use crate::{Body, Field, Request, RequestWriteError, Response, ResponseWriteError, Version};
+use std::borrow::Cow;
+use std::io::{self, IoSlice, Write};
+
+/// The most buffers passed to one `write_vectored` call, the `IOV_MAX` of
+/// Linux and the BSDs.
+const MAX_BATCH: usize = 1024;
+
+impl<'a, T, Q, V, B> Request<'a, T, Q, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    Q: Iterator<Item = Query<'a>>,
+    V: Version<'a>,
+    B: Body,
+{
+    /// Like [`Request::write_to`], but everything is checked before the head
+    /// goes out in as few `write_vectored` calls as the writer allows, taking
+    /// up to 1024 buffers each. The body follows with its own writes.
+    ///
+    /// Meant for a raw socket, where each write is a syscall; a buffered
+    /// writer gains nothing from it. Unlike [`Request::write_to`] it
+    /// allocates, as the pieces of the head are collected before the first
+    /// write.
+    pub fn write_to_vectored<W: Write + ?Sized>(&mut self, w: &mut W) -> Result<usize, RequestWriteError> {
+        let version = self.checked_version()?;
+        let path = self.checked_path()?;
+        let framing = self.checked_framing()?;
+        let queries = self
+            .queries
+            .by_ref()
+            .map(Query::checked)
+            .collect::<Result<Vec<Cow<'a, str>>, _>>()
+            .map_err(|_| RequestWriteError::InvalidQuery)?;
+
+        let mut prefix = Vec::new();
+        self.target.write_prefix(&mut prefix)?;
+        let mut normalized = Vec::new();
+        if !self.target.has_path() && !queries.is_empty() {
+            return Err(RequestWriteError::QueryNotAllowed);
+        }
+        let path = match self.normalize {
+            _ if !self.target.has_path() => &[],
+            Normalize::Off => path.as_bytes(),
+            Normalize::DotSegments | Normalize::NoTraversal => {
+                self.write_path(&mut normalized, path)?;
+                &normalized
+            }
+        };
+        let mut line = vec![self.method.as_str().as_bytes(), b" ", &prefix, path];
+        for (i, q) in queries.iter().enumerate() {
+            line.extend([if i == 0 { b"?" } else { self.query_separator.as_str().as_bytes() }, q.as_bytes()]);
+        }
+        line.extend([b" HTTP/".as_slice(), version.as_bytes(), b"\r\n"]);
+
+        let unique = self.unique_headers;
+        let fields = checked_fields(&mut self.drain_headers(), &line, framing, unique)?;
+        if self.host_required() && !fields.iter().any(Field::is_host) {
+            return Err(RequestWriteError::MissingHost { buffer_offset: 0 });
+        }
+        let mut w = crate::Counter::new(w);
+        let head = write_head(&mut w, &line, &fields).map_err(|e| RequestWriteError::from(e).with_written(w.written))?;
+        let body = self.body.write_body(&mut w).map_err(|e| RequestWriteError::from(e).with_written(w.written))?;
+        Ok(head + body)
+    }
+}
+
+impl<'a, T, V, B> Response<'a, T, V, B>
+where
+    T: Iterator<Item = Field<'a>>,
+    V: Version<'a>,
+    B: Body,
+{
+    /// Like [`Request::write_to_vectored`].
+    pub fn write_to_vectored<W: Write + ?Sized>(&mut self, w: &mut W) -> Result<usize, ResponseWriteError> {
+        let version = self.checked_version()?;
+        let framing = self.body_framing().map_err(ResponseWriteError::BodySerialize)?;
+        let code = self.code.as_str();
+        self.checked_reason()?;
+        let reason = crate::response::reason_phrase(self.code, self.reason);
+        let line = [b"HTTP/".as_slice(), version.as_bytes(), b" ", code.as_bytes(), b" ", reason.as_bytes(), b"\r\n"];
+
+        let fields = checked_fields(&mut self.headers, &line, framing, self.unique_headers)?;
+        let mut w = crate::Counter::new(w);
+        let head = write_head(&mut w, &line, &fields).map_err(|e| ResponseWriteError::from(e).with_written(w.written))?;
+        if !self.sends_body() {
+            return Ok(head);
+        }
+        let body = self.body.write_body(&mut w).map_err(|e| ResponseWriteError::from(e).with_written(w.written))?;
+        Ok(head + body)
+    }
+}
+
+/// Why [`checked_fields`] rejected the headers, before anything was written.
+enum FieldsError {
+    Header(usize, crate::HeaderWriteError),
+    ConflictingFraming,
+    DuplicateHeader,
+}
+
+impl From<FieldsError> for RequestWriteError {
+    fn from(err: FieldsError) -> Self {
+        match err {
+            FieldsError::Header(offset, err) => (offset, err).into(),
+            FieldsError::ConflictingFraming => Self::ConflictingFraming { buffer_offset: 0 },
+            FieldsError::DuplicateHeader => Self::DuplicateHeader { buffer_offset: 0 },
+        }
+    }
+}
+
+impl From<FieldsError> for ResponseWriteError {
+    fn from(err: FieldsError) -> Self {
+        match err {
+            FieldsError::Header(offset, err) => (offset, err).into(),
+            FieldsError::ConflictingFraming => Self::ConflictingFraming { buffer_offset: 0 },
+            FieldsError::DuplicateHeader => Self::DuplicateHeader { buffer_offset: 0 },
+        }
+    }
+}
+
+/// Drains and validates `headers`, adding the framing header they lack.
+fn checked_fields<'a>(
+    headers: &mut impl Iterator<Item = Field<'a>>,
+    line: &[&[u8]],
+    framing: crate::Framing,
+    unique: &'static [&'static str],
+) -> Result<Vec<Field<'a>>, FieldsError> {
+    let mut len = line.iter().map(|piece| piece.len()).sum();
+    let mut framed = crate::field::FramingHeaders::default();
+    let mut unique = crate::field::UniqueHeaders::new(unique);
+    let mut fields = Vec::new();
+    for header in headers {
+        header.validate().map_err(|e| FieldsError::Header(len, e))?;
+        if framed.conflicts(&header) {
+            return Err(FieldsError::ConflictingFraming);
+        }
+        if unique.repeats(&header) {
+            return Err(FieldsError::DuplicateHeader);
+        }
+        len += header.line_chunks().map(<[u8]>::len).sum::<usize>();
+        fields.push(header);
+    }
+    if framed.disagrees(framing) {
+        return Err(FieldsError::ConflictingFraming);
+    }
+    fields.extend(crate::body::framing_field(framing, framed.any()));
+    Ok(fields)
+}
+
+fn write_head<W: Write + ?Sized>(w: &mut W, line: &[&[u8]], fields: &[Field<'_>]) -> io::Result<usize> {
+    let pieces = line.iter().copied().chain(fields.iter().flat_map(Field::line_chunks));
+    write_all_vectored(w, pieces.chain([b"\r\n".as_slice()]))
+}
+
+/// Writes all of `bufs` in batches of `write_vectored` calls, retrying on
+/// [`io::ErrorKind::Interrupted`] like [`Write::write_all`].
+pub(crate) fn write_all_vectored<'b, W: Write + ?Sized>(
+    w: &mut W,
+    bufs: impl Iterator<Item = &'b [u8]>,
+) -> io::Result<usize> {
+    let mut slices: Vec<IoSlice<'_>> = bufs.filter(|b| !b.is_empty()).map(IoSlice::new).collect();
+    let mut rest = slices.as_mut_slice();
+    let mut written = 0;
+    while !rest.is_empty() {
+        let batch = rest.len().min(MAX_BATCH);
+        match w.write_vectored(&rest[..batch]) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => {
+                written += n;
+                IoSlice::advance_slices(&mut rest, n);
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(written)
+}
+
+/// Counts the calls that reach it, as a raw socket would make syscalls.
+#[cfg(test)]
+#[derive(Default)]
+struct Syscalls {
+    out: Vec<u8>,
+    calls: usize,
+}
+
+#[cfg(test)]
+impl Write for Syscalls {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.calls += 1;
+        self.out.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.calls += 1;
+        let mut n = 0;
+        for buf in bufs {
+            self.out.extend_from_slice(buf);
+            n += buf.len();
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn many_headers(names: &[String]) -> Request<'_, impl Iterator<Item = Field<'_>> + Clone, impl Iterator<Item = Query<'_>> + Clone, crate::version::V1_1, &[u8]> {
+    Request::post()
+        .path("/a/./b/../upload")
+        .normalize_path()
+        .query("x=1")
+        .query("y=2")
+        .v1_1()
+        .headers(names.iter().map(|name| crate::Header { name, value: b"value" }))
+        .origin("https", "example.com", None)
+        .raw_header_line(b"X-Raw: kept as is\r\n")
+        .body(b"payload")
+}
+
+#[test]
+fn fewer_writes() {
+    let names: Vec<String> = (0..40).map(|i| format!("X-Header-{i}")).collect();
+
+    let mut plain = Syscalls::default();
+    let plain_len = many_headers(&names).write_to(&mut plain).unwrap();
+    let mut vectored = Syscalls::default();
+    let vectored_len = many_headers(&names).write_to_vectored(&mut vectored).unwrap();
+
+    assert_eq!(vectored.out, plain.out);
+    assert_eq!(vectored_len, plain_len);
+    assert_eq!(vectored_len, plain.out.len());
+    assert!(vectored.out.starts_with(b"POST /a/upload?x=1&y=2 HTTP/1.1\r\nX-Header-0: value\r\n"));
+    // one for the head and one for the body
+    assert_eq!(vectored.calls, 2);
+    // write_to stages the head, so it only writes once it is checked
+    assert_eq!(plain.calls, 2);
+
+    let res = |w: &mut Syscalls| {
+        Response::new(http::StatusCode::NOT_MODIFIED)
+            .v1_1()
+            .headers(names.iter().map(|name| crate::Header { name, value: b"value" }))
+            .body(b"elided")
+            .write_to_vectored(w)
+            .unwrap()
+    };
+    let mut w = Syscalls::default();
+    let len = res(&mut w);
+    assert_eq!(len, w.out.len());
+    assert_eq!(w.calls, 1);
+    assert!(w.out.ends_with(b"X-Header-39: value\r\nContent-Length: 6\r\n\r\n"));
+}
+
+#[test]
+fn vectored_errors() {
+    let mut w = Syscalls::default();
+    let err = Request::get().path("/").query("a b").v1_1().write_to_vectored(&mut w);
+    assert!(matches!(err, Err(RequestWriteError::InvalidQuery)));
+    let err = Request::get().path("/").v1_1().header("X-Ok", b"1").header("Bad Name", b"2").write_to_vectored(&mut w);
+    assert!(matches!(
+        err,
+        Err(RequestWriteError::InvalidHeader { buffer_offset: 25, err: crate::HeaderWriteError::InvalidName(3) })
+    ));
+    let err = Response::new(http::StatusCode::OK).v1_1().header("X", b"a\nb").write_to_vectored(&mut w);
+    assert!(matches!(err, Err(ResponseWriteError::InvalidHeader { buffer_offset: 17, .. })));
+    assert_eq!(w.calls, 0, "nothing is written before the checks pass");
+
+    let mut full = [0u8; 10];
+    let err = Request::get().path("/").v1_1().write_to_vectored(&mut full.as_mut_slice());
+    assert!(matches!(err, Err(RequestWriteError::Io { written: 10, .. })));
+
+    let names: Vec<String> = (0..600).map(|i| format!("X-{i}")).collect();
+    let mut w = Syscalls::default();
+    let len = many_headers(&names).write_to_vectored(&mut w).unwrap();
+    assert_eq!(len, w.out.len());
+    // 2400 header pieces do not fit one batch
+    assert_eq!(w.calls, 4);
+}
@@ -1,22 +1,115 @@
 pub trait Version<'a> {
     fn as_str(&self) -> &'a str;
+
+    /// Whether [`Version::as_str`] is known to be a version that can be
+    /// written, so writing skips checking it.
+    fn is_known_valid(&self) -> bool {
+        false
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct V1;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct V1_1;
+/// `2`, for the version token alone, as in `Upgrade` or when echoing a
+/// version; HTTP/2 messages are framed in binary, not written as lines.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct V2;
+/// `3`, for the version token alone, as with [`V2`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct V3;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct UNSPECIFIED;
+/// Whether `version` can follow `HTTP/`: a digit, optionally followed by a
+/// `.` and a digit.
+pub(crate) const fn is_valid(version: &str) -> bool {
+    match version.as_bytes() {
+        [major] => major.is_ascii_digit(),
+        [major, b'.', minor] => major.is_ascii_digit() && minor.is_ascii_digit(),
+        _ => false,
+    }
+}
+
+/// A version held as a string, such as one read from a parsed message.
+///
+/// [`Version::as_str`] returns the stored slice verbatim; it is checked when
+/// the message is written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Dynamic<'a>(&'a str);
 
+impl<'a> Dynamic<'a> {
+    pub const fn new(version: &'a str) -> Self {
+        Self(version)
+    }
+
+    /// Like [`Dynamic::new`], but fails unless `version` is a digit,
+    /// optionally followed by a `.` and a digit, such as `1.1` or `2`.
+    pub const fn parse(version: &'a str) -> Result<Self, MalformedVersion> {
+        if is_valid(version) {
+            Ok(Self(version))
+        } else {
+            Err(MalformedVersion)
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Dynamic<'a> {
+    fn from(version: &'a str) -> Self {
+        Self::new(version)
+    }
+}
+
+/// The string passed to [`Dynamic::parse`] is not of the form `N` or `N.N`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MalformedVersion;
+
+impl core::fmt::Display for MalformedVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("malformed HTTP version")
+    }
+}
+
+impl std::error::Error for MalformedVersion {}
+
 impl<'a> Version<'a> for V1 {
     fn as_str(&self) -> &'a str {
         "1.0"
     }
+
+    fn is_known_valid(&self) -> bool {
+        true
+    }
 }
 
 impl<'a> Version<'a> for V1_1 {
     fn as_str(&self) -> &'a str {
         "1.1"
     }
+
+    fn is_known_valid(&self) -> bool {
+        true
+    }
+}
+
+impl<'a> Version<'a> for V2 {
+    fn as_str(&self) -> &'a str {
+        "2"
+    }
+
+    fn is_known_valid(&self) -> bool {
+        true
+    }
+}
+
+impl<'a> Version<'a> for V3 {
+    fn as_str(&self) -> &'a str {
+        "3"
+    }
+
+    fn is_known_valid(&self) -> bool {
+        true
+    }
 }
 
 impl<'a> Version<'a> for UNSPECIFIED {
@@ -30,3 +123,175 @@ impl<'a> Version<'a> for Dynamic<'a> {
         self.0
     }
 }
+
+/// A version picked at runtime, such as the one a client asked with.
+///
+/// Variants order by protocol revision.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AnyVersion {
+    V0_9,
+    V1_0,
+    V1_1,
+    V2,
+    V3,
+}
+
+impl<'a> Version<'a> for AnyVersion {
+    fn as_str(&self) -> &'a str {
+        match self {
+            Self::V0_9 => "0.9",
+            Self::V1_0 => "1.0",
+            Self::V1_1 => "1.1",
+            Self::V2 => "2",
+            Self::V3 => "3",
+        }
+    }
+
+    fn is_known_valid(&self) -> bool {
+        true
+    }
+}
+
+/// Written as [`AnyVersion`] writes the same version.
+impl<'a> Version<'a> for http::Version {
+    fn as_str(&self) -> &'a str {
+        AnyVersion::from(*self).as_str()
+    }
+
+    fn is_known_valid(&self) -> bool {
+        true
+    }
+}
+
+impl core::fmt::Display for AnyVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// The string is not one of the versions [`AnyVersion`] parses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnknownVersion;
+
+impl core::fmt::Display for UnknownVersion {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("unknown HTTP version")
+    }
+}
+
+impl std::error::Error for UnknownVersion {}
+
+impl core::str::FromStr for AnyVersion {
+    type Err = UnknownVersion;
+
+    /// Parses the version as written after `HTTP/`, with or without that
+    /// prefix; `2.0` and `3.0` are accepted for `2` and `3`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let version = s.strip_prefix("HTTP/").unwrap_or(s);
+        match version {
+            "0.9" => Ok(Self::V0_9),
+            "1.0" => Ok(Self::V1_0),
+            "1.1" => Ok(Self::V1_1),
+            "2" | "2.0" => Ok(Self::V2),
+            "3" | "3.0" => Ok(Self::V3),
+            _ => Err(UnknownVersion),
+        }
+    }
+}
+
+impl From<V1> for AnyVersion {
+    fn from(_: V1) -> Self {
+        Self::V1_0
+    }
+}
+
+impl From<V1_1> for AnyVersion {
+    fn from(_: V1_1) -> Self {
+        Self::V1_1
+    }
+}
+
+impl From<V2> for AnyVersion {
+    fn from(_: V2) -> Self {
+        Self::V2
+    }
+}
+
+impl From<V3> for AnyVersion {
+    fn from(_: V3) -> Self {
+        Self::V3
+    }
+}
+
+impl From<AnyVersion> for http::Version {
+    fn from(version: AnyVersion) -> Self {
+        match version {
+            AnyVersion::V0_9 => Self::HTTP_09,
+            AnyVersion::V1_0 => Self::HTTP_10,
+            AnyVersion::V1_1 => Self::HTTP_11,
+            AnyVersion::V2 => Self::HTTP_2,
+            AnyVersion::V3 => Self::HTTP_3,
+        }
+    }
+}
+
+impl From<http::Version> for AnyVersion {
+    fn from(version: http::Version) -> Self {
+        match version {
+            http::Version::HTTP_09 => Self::V0_9,
+            http::Version::HTTP_10 => Self::V1_0,
+            http::Version::HTTP_11 => Self::V1_1,
+            http::Version::HTTP_2 => Self::V2,
+            http::Version::HTTP_3 => Self::V3,
+            _ => unreachable!("http::Version has no other values"),
+        }
+    }
+}
+
+#[test]
+fn any_version_conversions() {
+    let all = [AnyVersion::V0_9, AnyVersion::V1_0, AnyVersion::V1_1, AnyVersion::V2, AnyVersion::V3];
+    for version in all {
+        assert_eq!(version.to_string().parse(), Ok(version));
+        assert_eq!(format!("HTTP/{version}").parse(), Ok(version));
+        assert_eq!(AnyVersion::from(http::Version::from(version)), version);
+    }
+    assert!(all.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(http::Version::from(AnyVersion::V2), http::Version::HTTP_2);
+    assert_eq!(AnyVersion::from(V1), AnyVersion::V1_0);
+    assert_eq!(AnyVersion::from(V1_1), AnyVersion::V1_1);
+    assert_eq!("2.0".parse(), Ok(AnyVersion::V2));
+    assert_eq!("1.2".parse::<AnyVersion>(), Err(UnknownVersion));
+    assert_eq!("http/1.1".parse::<AnyVersion>(), Err(UnknownVersion));
+}
+
+#[test]
+fn http_version() {
+    assert_eq!(http::Version::HTTP_10.as_str(), "1.0");
+    assert_eq!(http::Version::HTTP_2.as_str(), "2");
+    assert_eq!(http::Version::HTTP_3.as_str(), "3");
+
+    let mut buf = Vec::new();
+    crate::Request::get().path("/").version(http::Version::HTTP_11).write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"GET / HTTP/1.1\r\n\r\n");
+    let mut buf = Vec::new();
+    crate::Response::ok().version(http::Version::HTTP_10).write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"HTTP/1.0 200 OK\r\n\r\n");
+}
+
+#[test]
+fn dynamic() {
+    assert_eq!(Dynamic::parse("1.1"), Ok(Dynamic::new("1.1")));
+    assert_eq!(Dynamic::parse("2.0").map(|v| v.as_str()), Ok("2.0"));
+    assert_eq!(Dynamic::parse("2"), Ok(Dynamic::new("2")));
+    assert_eq!(Dynamic::parse("3"), Ok(Dynamic::new("3")));
+    for bad in ["", "11", "1.", "1.1.1", "a.b", "HTTP/1.1", " 1.1"] {
+        assert_eq!(Dynamic::parse(bad), Err(MalformedVersion), "{bad:?}");
+    }
+    assert_eq!(Dynamic::from("anything").as_str(), "anything");
+
+    let parsed = String::from("1.0");
+    let mut buf = Vec::new();
+    crate::Request::get().path("/").version(Dynamic::parse(&parsed).unwrap()).write_to(&mut buf).unwrap();
+    assert_eq!(buf, b"GET / HTTP/1.0\r\n\r\n");
+}
@@ -0,0 +1,5 @@
+use http_write::{Response, StatusCode};
+
+fn main() {
+    let _ = Response::new(StatusCode::OK).text("one").html("two");
+}
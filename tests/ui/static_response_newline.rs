@@ -0,0 +1,5 @@
+static BAD: &[u8] = http_write::static_response!(200, "x-bad" => "a\nb"; body: "ok");
+
+fn main() {
+    let _ = BAD;
+}